@@ -1,9 +1,20 @@
-use crate::depot::{DepotMessage, DepotState, KrateMetadata};
-use crate::errors::Error;
 use crate::events::{AppEvent, Event, EventHandler};
 use crate::keys::key_handler;
-use crate::ui::{render, views::View};
+use crate::state::{DepotMessage, DepotState, Finished, Task};
+use crate::ui::render;
+use crate::ui::views::{View, error_view::ErrorView};
+use depot_rs::backend::{CargoBackend, CargoCli};
+use depot_rs::depot::{Depot, fetch_metadata, install_krate, install_spec};
+use depot_rs::depotfile::ToolSpec;
+use depot_rs::errors::Error;
+use depot_rs::operation::{OperationKind, on_start};
+use depot_rs::records::InstallRecords;
+use depot_rs::tracking::InstallTracking;
+use futures::future::join_all;
 use ratatui::DefaultTerminal;
+use std::sync::Arc;
+use tokio::sync::Notify;
+use versions::SemVer;
 
 /// The main application which holds the state and logic of the application.
 #[derive(Debug)]
@@ -17,20 +28,160 @@ pub struct App {
 
 impl App {
     /// Construct a new instance of [`App`].
-    pub fn new() -> Self {
-        let has_initialized = false;
+    pub async fn new() -> Result<Self, Error> {
+        Self::with_backend(Arc::new(CargoCli)).await
+    }
+
+    /// Construct an [`App`] that runs cargo through `backend`.
+    ///
+    /// The installed crates are listed in the background, so that the first frame can be drawn
+    /// right away. Has to run on the runtime, which the listing and the events are spawned on.
+    pub async fn with_backend(backend: Arc<dyn CargoBackend>) -> Result<Self, Error> {
+        Ok(Self::with_state(DepotState::new(backend)?).await)
+    }
 
-        Self {
+    /// Construct an [`App`] around `state`, e.g. one with its own policy, hooks and journal.
+    pub async fn with_state(state: DepotState) -> Self {
+        let mut app = Self {
             running: true,
             events: EventHandler::new(),
-            state: DepotState::default(),
+            state,
             view: View::default(),
-            has_initialized,
+            has_initialized: false,
+        };
+        app.list_krates();
+
+        app
+    }
+
+    /// Lists the installed crates in the background. Their info is fetched once they are listed.
+    pub fn list_krates(&mut self) {
+        self.state.listing = true;
+        self.state.startup_error = None;
+        self.has_initialized = false;
+
+        let backend = self.state.backend.clone();
+        let sender = self.events.get_sender();
+        tokio::spawn(async move {
+            let msg = match Depot::get(backend.as_ref()).await {
+                Ok(depot) => DepotMessage::Listed(Box::new(depot)),
+                Err(e) => DepotMessage::ListFailed(e.to_string()),
+            };
+            let _ = sender.send(Event::App(AppEvent::DepotEvent(msg)));
+        });
+    }
+
+    /// Queues an install, update or uninstall of `name` and runs it in the background. Installs
+    /// follow `spec` when given.
+    ///
+    /// The depot is only changed once the operation finished, see [`DepotState::finish_operation`].
+    pub fn spawn_operation(&mut self, kind: OperationKind, name: String, spec: Option<ToolSpec>) {
+        if self.state.operations.is_active(&name) {
+            return;
+        }
+        let id = self.state.operations.queue(kind, &name);
+
+        let backend = self.state.backend.clone();
+        let journal = self.state.journal.clone();
+        let hooks = self.state.hooks.clone();
+        // Only new crates need metadata, the search view may already have fetched it.
+        let metadata = self
+            .state
+            .search
+            .details
+            .get(&name)
+            .filter(|m| m.fetch_error().is_none())
+            .cloned();
+        let installed = self.state.depot.store.0.iter().find(|k| k.name == name);
+        let is_new = installed.is_none();
+        // The version the pre-hooks are told will be installed.
+        let target = match (kind, &spec) {
+            (OperationKind::Uninstall, _) => None,
+            (_, Some(spec)) => spec.version.as_deref().and_then(SemVer::new),
+            (_, None) => match installed {
+                Some(krate) => Some(krate.latest_version()),
+                None => metadata.as_ref().and_then(|m| m.latest_version().cloned()),
+            },
+        };
+        let cancel = Arc::new(Notify::new());
+        let cancelled = cancel.clone();
+        let sender = self.events.get_sender();
+        let task = tokio::spawn(async move {
+            let send = |msg| sender.send(Event::App(AppEvent::DepotEvent(msg)));
+            let starting = sender.clone();
+            let started = move || {
+                let msg = DepotMessage::OperationStarted(id);
+                let _ = starting.send(Event::App(AppEvent::DepotEvent(msg)));
+            };
+
+            let backend = backend.as_ref();
+            let operation = async {
+                match (kind, &spec) {
+                    (OperationKind::Uninstall, _) => backend.uninstall(&name).await.into(),
+                    (_, Some(spec)) => install_spec(backend, &name, spec).await.map(|_| ()),
+                    (_, None) => install_krate(backend, &name).await.map(|_| ()),
+                }
+            };
+            let outcome = on_start(
+                started,
+                journal.run_cancellable(
+                    backend,
+                    &hooks,
+                    kind,
+                    &name,
+                    target.as_ref(),
+                    operation,
+                    cancelled.notified(),
+                ),
+            )
+            .await;
+            let result = outcome.result;
+            let installed = read_installed(backend).await;
+            let metadata = match metadata {
+                _ if !is_new || result.is_err() => None,
+                Some(metadata) => Some(metadata),
+                None => backend.info(&name, None).await.ok(),
+            };
+
+            let _ = send(DepotMessage::OperationFinished(Box::new(Finished {
+                id,
+                result: result.map_err(|e| e.to_string()),
+                installed,
+                metadata,
+                warnings: outcome.warnings,
+            })));
+        });
+        let abort = task.abort_handle();
+        self.state.tasks.insert(id, Task { cancel, abort });
+    }
+
+    /// Cancels the pending or running operation on `name`, killing cargo if it runs. The
+    /// operation is journaled as cancelled, and the depot is reconciled with whatever cargo left
+    /// behind once its task reports back.
+    pub fn cancel_operation(&mut self, name: &str) -> Result<(), Error> {
+        let Some(id) = self
+            .state
+            .operations
+            .last(name)
+            .filter(|o| o.is_active())
+            .map(|o| o.id)
+        else {
+            return Ok(());
+        };
+        if let Some(task) = self.state.tasks.remove(&id) {
+            task.cancel.notify_one();
         }
+        self.state.operations.cancel(id)?;
+
+        Ok(())
     }
 
-    /// Set running to false to quit the application.
+    /// Set running to false to quit the application. Operations that are still running are
+    /// cancelled, which kills their cargo processes.
     pub fn quit(&mut self) {
+        for (_, task) in self.state.tasks.drain() {
+            task.abort.abort();
+        }
         self.running = false;
     }
 
@@ -48,7 +199,7 @@ impl App {
                 }
                 Event::App(event) => {
                     match event {
-                        AppEvent::DepotEvent(msg) => msg.handle(&mut self.state)?,
+                        AppEvent::DepotEvent(msg) => self.handle_message(msg)?,
                     };
                     // Redraw.
                     terminal.draw(|f| render(&mut self.view, &mut self.state, f).unwrap())?;
@@ -58,9 +209,18 @@ impl App {
         Ok(())
     }
 
-    /// Run only once when the app initializes.
+    fn handle_message(&mut self, msg: DepotMessage) -> Result<(), Error> {
+        msg.handle(&mut self.state)?;
+        if self.state.startup_error.is_some() {
+            self.view = View::Error(ErrorView);
+        }
+
+        Ok(())
+    }
+
+    /// Run only once, when the installed crates have been listed.
     fn handle_init(&mut self) -> Result<(), Error> {
-        if !self.has_initialized {
+        if !self.has_initialized && !self.state.listing && self.state.startup_error.is_none() {
             let names: Vec<String> = self
                 .state
                 .depot
@@ -72,23 +232,22 @@ impl App {
 
             if !names.is_empty() {
                 let sender = self.events.get_sender();
+                let backend = self.state.backend.clone();
 
                 tokio::spawn(async move {
-                    let resp: Result<Vec<KrateMetadata>, Error> =
-                        names.iter().map(|n| KrateMetadata::get(n)).collect();
-
-                    match resp {
-                        Ok(r) => sender.send(Event::App(AppEvent::DepotEvent(
-                            DepotMessage::FetchKrateInfo(r),
-                        ))),
-                        Err(_) => sender.send(Event::App(AppEvent::DepotEvent(
-                            DepotMessage::DepotError(crate::errors::ChannelError::KrateInfo),
-                        ))),
-                    }
+                    let info = join_all(
+                        names
+                            .iter()
+                            .map(|n| fetch_metadata(backend.as_ref(), n, None)),
+                    )
+                    .await;
+                    sender.send(Event::App(AppEvent::DepotEvent(
+                        DepotMessage::FetchKrateInfo(info),
+                    )))
                 });
             }
+            self.has_initialized = true;
         }
-        self.has_initialized = true;
 
         Ok(())
     }
@@ -98,13 +257,64 @@ impl App {
     }
 }
 
+/// Cargo's tracking data and depot's records, read again after an operation.
+async fn read_installed(
+    backend: &dyn CargoBackend,
+) -> Result<(InstallTracking, InstallRecords), String> {
+    let tracking = backend.tracking().await.map_err(|e| e.to_string())?;
+    let records = InstallRecords::load().map_err(|e| e.to_string())?;
+
+    Ok((tracking, records))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::app::App;
+    use crate::events::{AppEvent, Event};
+    use crate::state::{DepotMessage, DepotState};
+    use crate::ui::views::View;
+    use depot_rs::backend::fake::FakeBackend;
+    use depot_rs::hooks::Hooks;
+    use depot_rs::journal::Journal;
+    use depot_rs::operation::{OperationKind, OperationState};
+    use pretty_assertions::assert_eq;
+    use std::sync::Arc;
+    use std::time::{Duration, Instant};
+
+    /// Skips the ticks until the next message from a background task.
+    async fn next_message(app: &mut App) -> DepotMessage {
+        loop {
+            if let Event::App(AppEvent::DepotEvent(msg)) = app.events.next().await.unwrap() {
+                return msg;
+            }
+        }
+    }
+
+    /// An app whose state leaves the config and data directories alone.
+    async fn isolated_app(backend: FakeBackend) -> App {
+        let state = DepotState::with_parts(
+            Arc::new(backend),
+            None,
+            Hooks::default(),
+            Journal::new(None),
+        );
+
+        App::with_state(state).await
+    }
+
+    async fn listed_app(backend: FakeBackend) -> App {
+        let mut app = isolated_app(backend).await;
+        let msg = next_message(&mut app).await;
+        assert!(matches!(msg, DepotMessage::Listed(_)));
+        app.handle_message(msg).unwrap();
+
+        app
+    }
 
     #[tokio::test]
     async fn handle_init_initializes_app() {
-        let mut mock_app = App::new();
+        let backend = FakeBackend::default().with_installed("ripgrep", "14.1.1");
+        let mut mock_app = listed_app(backend).await;
         mock_app.handle_init().unwrap();
 
         assert!(mock_app.has_initialized)
@@ -112,10 +322,151 @@ mod tests {
 
     #[tokio::test]
     async fn handle_init_with_no_crates() {
-        let mut mock_app = App::new();
-        mock_app.state.depot.store.0.clear();
+        let mut mock_app = listed_app(FakeBackend::default()).await;
         mock_app.handle_init().unwrap();
 
         assert!(mock_app.handle_init().is_ok())
     }
+
+    #[tokio::test]
+    async fn handle_init_waits_for_listing() {
+        let backend = FakeBackend::default()
+            .with_installed("ripgrep", "14.1.0")
+            .with_latency(Duration::from_millis(200));
+
+        let started = Instant::now();
+        let mut mock_app = isolated_app(backend).await;
+        mock_app.handle_init().unwrap();
+
+        assert!(started.elapsed() < Duration::from_millis(200));
+        assert!(mock_app.state.listing);
+        assert!(!mock_app.state.is_all_synced());
+        assert!(!mock_app.has_initialized);
+    }
+
+    #[tokio::test]
+    async fn handle_init_fetches_krate_info() {
+        let backend = FakeBackend::default()
+            .with_installed("ripgrep", "14.1.0")
+            .with_latest("ripgrep", "14.1.1");
+        let mut mock_app = listed_app(backend).await;
+        mock_app.handle_init().unwrap();
+
+        let msg = next_message(&mut mock_app).await;
+        assert!(matches!(msg, DepotMessage::FetchKrateInfo(_)));
+        mock_app.handle_message(msg).unwrap();
+
+        assert!(mock_app.state.is_all_synced());
+        assert_eq!(mock_app.state.depot.outdated_krate_count().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_lookups_only_show_error_view_when_all_fail() {
+        let backend = FakeBackend::default()
+            .with_installed("bat", "0.24.0")
+            .with_installed("ripgrep", "14.1.1")
+            .failing("bat");
+        let mut mock_app = listed_app(backend).await;
+        mock_app.handle_init().unwrap();
+        let msg = next_message(&mut mock_app).await;
+        mock_app.handle_message(msg).unwrap();
+
+        assert!(matches!(mock_app.view, View::Start(_)));
+        assert_eq!(mock_app.state.depot.unfetched_krate_count(), 1);
+
+        let backend = FakeBackend::default()
+            .with_installed("bat", "0.24.0")
+            .failing("bat");
+        let mut mock_app = listed_app(backend).await;
+        mock_app.handle_init().unwrap();
+        let msg = next_message(&mut mock_app).await;
+        mock_app.handle_message(msg).unwrap();
+
+        assert!(matches!(mock_app.view, View::Error(_)));
+        assert_eq!(
+            mock_app.state.startup_error.as_deref(),
+            Some("could not look up any crate: command failed: cargo info bat")
+        );
+    }
+
+    #[tokio::test]
+    async fn listing_failure_shows_error_view() {
+        let backend = FakeBackend::default()
+            .with_installed("ripgrep", "14.1.1")
+            .failing_list(1);
+        let mut mock_app = isolated_app(backend).await;
+
+        let msg = next_message(&mut mock_app).await;
+        mock_app.handle_message(msg).unwrap();
+        mock_app.handle_init().unwrap();
+
+        assert!(matches!(mock_app.view, View::Error(_)));
+        assert!(mock_app.state.startup_error.is_some());
+        assert!(!mock_app.has_initialized);
+
+        mock_app.list_krates();
+        let msg = next_message(&mut mock_app).await;
+        mock_app.handle_message(msg).unwrap();
+
+        assert_eq!(mock_app.state.startup_error, None);
+        assert_eq!(mock_app.state.depot.crate_count(), 1);
+    }
+
+    #[tokio::test]
+    async fn uninstall_waits_for_cargo() {
+        let backend = FakeBackend::default()
+            .with_installed("bat", "0.24.0")
+            .with_installed("ripgrep", "14.1.1");
+        let mut mock_app = listed_app(backend).await;
+
+        mock_app.spawn_operation(OperationKind::Uninstall, "bat".to_string(), None);
+        assert_eq!(mock_app.state.depot.crate_count(), 2);
+        assert_eq!(
+            mock_app.state.operations.last("bat").unwrap().state,
+            OperationState::Pending
+        );
+
+        let msg = next_message(&mut mock_app).await;
+        assert_eq!(msg, DepotMessage::OperationStarted(0));
+        mock_app.handle_message(msg).unwrap();
+        let msg = next_message(&mut mock_app).await;
+        assert!(matches!(msg, DepotMessage::OperationFinished(_)));
+        mock_app.handle_message(msg).unwrap();
+
+        assert_eq!(mock_app.state.depot.crate_count(), 1);
+        assert_eq!(
+            mock_app.state.operations.last("bat").unwrap().state,
+            OperationState::Succeeded
+        );
+    }
+
+    #[tokio::test]
+    async fn cancelled_uninstall_keeps_crate() {
+        let backend = FakeBackend::default()
+            .with_installed("bat", "0.24.0")
+            .with_latency(Duration::from_millis(100));
+        let mut mock_app = listed_app(backend).await;
+        let dir = std::env::temp_dir().join(format!("depot-app-cancel-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        mock_app.state.journal = Journal::new(Some(dir));
+
+        mock_app.spawn_operation(OperationKind::Uninstall, "bat".to_string(), None);
+        mock_app.cancel_operation("bat").unwrap();
+        assert!(mock_app.state.tasks.is_empty());
+        assert!(!mock_app.state.operations.is_active("bat"));
+
+        let msg = next_message(&mut mock_app).await;
+        assert!(matches!(msg, DepotMessage::OperationFinished(_)));
+        mock_app.handle_message(msg).unwrap();
+
+        assert_eq!(mock_app.state.depot.crate_count(), 1);
+        assert_eq!(
+            mock_app.state.operations.last("bat").unwrap().state,
+            OperationState::Cancelled
+        );
+        let entries = mock_app.state.journal.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].krate, "bat");
+        assert_eq!(entries[0].error.as_deref(), Some("cancelled"));
+    }
 }
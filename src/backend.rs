@@ -0,0 +1,128 @@
+//! Running cargo. Everything that needs cargo goes through a [`CargoBackend`], which is
+//! [`CargoCli`] outside of tests. Only the doctor's toolchain checks and `depot check`'s audit
+//! run their tools directly, since they are about the real toolchain.
+
+use crate::commands::{
+    install_crate_with_args, list_crates, search_crate, search_crate_version, uninstall_crate,
+};
+use crate::config::SearchConfig;
+use crate::depot::{KrateMetadata, Krates};
+use crate::depotfile::ToolSpec;
+use crate::errors::Error;
+use crate::installer::{self, CargoInstaller, InstallMethod, InstallStrategy};
+use crate::search::{self, SearchResult};
+use crate::tracking::InstallTracking;
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use std::fmt::Debug;
+use versions::SemVer;
+
+pub mod fake;
+
+/// Everything depot asks cargo to do. The depot and the views only go through this, so that
+/// they can run against [`fake::FakeBackend`] in tests.
+pub trait CargoBackend: Debug + Send + Sync {
+    /// The installed crates, with only what `cargo install --list` tells filled in.
+    fn list(&self) -> BoxFuture<'_, Result<Krates, Error>>;
+
+    /// What the registry knows about a crate. With `version`, describes that version instead of
+    /// the latest one, e.g. whether it was yanked.
+    fn info<'a>(
+        &'a self,
+        name: &'a str,
+        version: Option<&'a SemVer>,
+    ) -> BoxFuture<'a, Result<KrateMetadata, Error>>;
+
+    /// Searches the registry in `config` for crates matching `query`.
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        config: &'a SearchConfig,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>, Error>>;
+
+    /// Installs or updates a crate and tells how it ended up being installed.
+    fn install<'a>(
+        &'a self,
+        name: &'a str,
+        request: &'a InstallRequest,
+    ) -> BoxFuture<'a, Result<InstallMethod, Error>>;
+
+    /// Uninstalls a crate.
+    fn uninstall<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<(), Error>>;
+
+    /// Cargo's tracking data, read again after every install, update or uninstall to see what
+    /// ended up on disk.
+    fn tracking(&self) -> BoxFuture<'_, Result<InstallTracking, Error>>;
+}
+
+/// Which version of a crate to install, and how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InstallRequest {
+    /// The latest version, using prebuilt binaries as the strategy allows.
+    Latest(InstallStrategy),
+    /// Exactly as a Depotfile describes it. Always built from source, since prebuilt binaries
+    /// can't honour features or other build flags.
+    Spec(ToolSpec),
+}
+
+/// Runs the real `cargo`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct CargoCli;
+
+impl CargoBackend for CargoCli {
+    fn list(&self) -> BoxFuture<'_, Result<Krates, Error>> {
+        async { Krates::from_cargo_list(&list_crates().await?) }.boxed()
+    }
+
+    fn info<'a>(
+        &'a self,
+        name: &'a str,
+        version: Option<&'a SemVer>,
+    ) -> BoxFuture<'a, Result<KrateMetadata, Error>> {
+        async move {
+            let stdout = match version {
+                Some(v) => search_crate_version(name, &v.to_string()).await?,
+                None => search_crate(name).await?,
+            };
+
+            Ok(KrateMetadata::parse(name, &stdout))
+        }
+        .boxed()
+    }
+
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        config: &'a SearchConfig,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>, Error>> {
+        search::search(query, config).boxed()
+    }
+
+    fn install<'a>(
+        &'a self,
+        name: &'a str,
+        request: &'a InstallRequest,
+    ) -> BoxFuture<'a, Result<InstallMethod, Error>> {
+        async move {
+            match request {
+                InstallRequest::Latest(strategy) => {
+                    installer::install(&CargoInstaller, name, *strategy).await
+                }
+                InstallRequest::Spec(spec) => {
+                    install_crate_with_args(spec.install_args(name)).await?;
+                    Ok(InstallMethod::Source)
+                }
+            }
+        }
+        .boxed()
+    }
+
+    fn uninstall<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        uninstall_crate(name).boxed()
+    }
+
+    fn tracking(&self) -> BoxFuture<'_, Result<InstallTracking, Error>> {
+        async { InstallTracking::load() }.boxed()
+    }
+}
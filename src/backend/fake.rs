@@ -0,0 +1,280 @@
+//! An in-memory [`CargoBackend`] for tests.
+
+use super::{CargoBackend, InstallRequest};
+use crate::commands;
+use crate::config::SearchConfig;
+use crate::depot::{Krate, KrateMetadata, Krates};
+use crate::errors::Error;
+use crate::installer::{InstallMethod, InstallStrategy};
+use crate::search::SearchResult;
+use crate::tracking::{InstallTracking, TrackedInstall};
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use versions::SemVer;
+
+/// A [`CargoBackend`] that keeps the installed crates in memory and answers from a script, so
+/// the app can be tested on a machine without cargo.
+#[derive(Debug, Default)]
+pub struct FakeBackend {
+    installed: Mutex<Vec<Krate>>,
+    /// The latest version of each crate known to the fake registry.
+    latest: HashMap<String, SemVer>,
+    /// Crates whose info, install and uninstall fail, and queries whose search fails.
+    failing: HashSet<String>,
+    /// How many times listing fails before it succeeds.
+    list_failures: Mutex<u32>,
+    /// How long every call takes.
+    latency: Duration,
+    calls: Mutex<Vec<String>>,
+}
+
+impl FakeBackend {
+    /// Adds an installed crate with a binary of the same name. It is also in the registry, at
+    /// this version unless [`FakeBackend::with_latest`] says otherwise.
+    pub fn with_installed(mut self, name: &str, version: &str) -> Self {
+        let mut krate = Krate::default();
+        krate.name = name.to_string();
+        krate.version = SemVer::new(version).expect("invalid version");
+        krate.binaries = vec![name.to_string()];
+        self.latest
+            .entry(name.to_string())
+            .or_insert(krate.version.clone());
+        self.installed.get_mut().unwrap().push(krate);
+        self
+    }
+
+    /// Puts a crate in the registry at `version`.
+    pub fn with_latest(mut self, name: &str, version: &str) -> Self {
+        self.latest.insert(
+            name.to_string(),
+            SemVer::new(version).expect("invalid version"),
+        );
+        self
+    }
+
+    /// Makes the info, install and uninstall of `name` fail, and searching for it.
+    pub fn failing(mut self, name: &str) -> Self {
+        self.failing.insert(name.to_string());
+        self
+    }
+
+    /// Makes listing the installed crates fail `times` times before it succeeds.
+    pub fn failing_list(mut self, times: u32) -> Self {
+        *self.list_failures.get_mut().unwrap() = times;
+        self
+    }
+
+    /// Makes every call take `latency`.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// The calls made so far, e.g. `install bat`.
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    fn record(&self, call: String) -> Result<(), Error> {
+        let name = call.rsplit(' ').next().unwrap_or_default().to_string();
+        self.calls.lock().unwrap().push(call.clone());
+        if self.failing.contains(&name) {
+            return Err(Error::CommandFailed(format!("cargo {call}")));
+        }
+
+        Ok(())
+    }
+}
+
+impl CargoBackend for FakeBackend {
+    fn list(&self) -> BoxFuture<'_, Result<Krates, Error>> {
+        async move {
+            tokio::time::sleep(self.latency).await;
+            {
+                let mut failures = self.list_failures.lock().unwrap();
+                if *failures > 0 {
+                    *failures -= 1;
+                    return Err(Error::CommandFailed("cargo install --list".to_string()));
+                }
+            }
+            let mut installed = self.installed.lock().unwrap().clone();
+            installed.sort_by(|a, b| a.name.cmp(&b.name));
+
+            Ok(Krates(installed))
+        }
+        .boxed()
+    }
+
+    fn info<'a>(
+        &'a self,
+        name: &'a str,
+        _version: Option<&'a SemVer>,
+    ) -> BoxFuture<'a, Result<KrateMetadata, Error>> {
+        async move {
+            tokio::time::sleep(self.latency).await;
+            self.record(format!("info {name}"))?;
+            let latest = self
+                .latest
+                .get(name)
+                .ok_or_else(|| Error::KrateInfo(format!("could not find `{name}`")))?;
+
+            Ok(KrateMetadata::with_latest(name, latest.clone()))
+        }
+        .boxed()
+    }
+
+    fn search<'a>(
+        &'a self,
+        query: &'a str,
+        config: &'a SearchConfig,
+    ) -> BoxFuture<'a, Result<Vec<SearchResult>, Error>> {
+        async move {
+            tokio::time::sleep(self.latency).await;
+            self.record(format!("search {query}"))?;
+            let mut results: Vec<SearchResult> = self
+                .latest
+                .iter()
+                .filter(|(name, _)| name.contains(query))
+                .map(|(name, version)| SearchResult {
+                    name: name.clone(),
+                    version: version.to_string(),
+                    description: None,
+                })
+                .collect();
+            results.sort_by(|a, b| a.name.cmp(&b.name));
+            results.truncate(config.limit as usize);
+
+            Ok(results)
+        }
+        .boxed()
+    }
+
+    fn install<'a>(
+        &'a self,
+        name: &'a str,
+        request: &'a InstallRequest,
+    ) -> BoxFuture<'a, Result<InstallMethod, Error>> {
+        async move {
+            tokio::time::sleep(self.latency).await;
+            commands::started();
+            self.record(format!("install {name}"))?;
+            let (version, method) = match request {
+                InstallRequest::Latest(InstallStrategy::SourceOnly) => {
+                    (self.latest.get(name).cloned(), InstallMethod::Source)
+                }
+                InstallRequest::Latest(_) => {
+                    (self.latest.get(name).cloned(), InstallMethod::Prebuilt)
+                }
+                InstallRequest::Spec(spec) => (
+                    spec.version
+                        .as_deref()
+                        .and_then(SemVer::new)
+                        .or_else(|| self.latest.get(name).cloned()),
+                    InstallMethod::Source,
+                ),
+            };
+            let version =
+                version.ok_or_else(|| Error::CommandFailed(format!("could not find `{name}`")))?;
+
+            let mut installed = self.installed.lock().unwrap();
+            installed.retain(|k| k.name != name);
+            let mut krate = Krate::default();
+            krate.name = name.to_string();
+            krate.version = version;
+            krate.binaries = vec![name.to_string()];
+            installed.push(krate);
+
+            Ok(method)
+        }
+        .boxed()
+    }
+
+    fn uninstall<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<(), Error>> {
+        async move {
+            tokio::time::sleep(self.latency).await;
+            commands::started();
+            self.record(format!("uninstall {name}"))?;
+            let mut installed = self.installed.lock().unwrap();
+            if !installed.iter().any(|k| k.name == name) {
+                return Err(Error::CommandFailed(format!("{name} is not installed")));
+            }
+            installed.retain(|k| k.name != name);
+
+            Ok(())
+        }
+        .boxed()
+    }
+
+    fn tracking(&self) -> BoxFuture<'_, Result<InstallTracking, Error>> {
+        async move {
+            tokio::time::sleep(self.latency).await;
+            let mut tracking = InstallTracking::default();
+            for krate in self.installed.lock().unwrap().iter() {
+                let install = TrackedInstall {
+                    bins: krate.binaries.iter().cloned().collect(),
+                    profile: "release".to_string(),
+                    ..Default::default()
+                };
+                tracking.insert(&krate.name, &krate.version, install);
+            }
+
+            Ok(tracking)
+        }
+        .boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FakeBackend;
+    use crate::backend::CargoBackend;
+    use crate::config::{SearchConfig, UsageConfig};
+    use crate::depot::Depot;
+    use crate::records::InstallRecords;
+    use pretty_assertions::assert_eq;
+
+    #[tokio::test]
+    async fn failing_backend_reports_errors() {
+        let backend = FakeBackend::default()
+            .with_installed("bat", "0.24.0")
+            .failing("bat");
+
+        assert!(backend.uninstall("bat").await.is_err());
+        assert!(backend.info("bat", None).await.is_err());
+        assert!(backend.uninstall("ripgrep").await.is_err());
+        assert!(
+            backend
+                .search("bat", &SearchConfig::default())
+                .await
+                .is_err()
+        );
+        assert_eq!(backend.list().await.unwrap().0.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn depot_goes_through_backend() {
+        let backend = FakeBackend::default()
+            .with_installed("bat", "0.24.0")
+            .with_latest("bat-extras", "2024.8.24")
+            .with_latest("ripgrep", "14.1.1");
+        let depot = Depot::get_with(
+            &backend,
+            &InstallRecords::default(),
+            &UsageConfig::default(),
+        )
+        .await
+        .unwrap();
+        let tracked = depot.store.0[0].tracked.as_ref().unwrap();
+        assert_eq!(tracked.profile, "release");
+
+        let results = backend
+            .search("bat", &SearchConfig::default())
+            .await
+            .unwrap();
+        let names: Vec<&str> = results.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["bat", "bat-extras"]);
+    }
+}
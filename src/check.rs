@@ -0,0 +1,659 @@
+//! `depot check`: outdated and yanked crates, policy violations and RustSec advisories, as text,
+//! JUnit or SARIF.
+
+use crate::backend::CargoBackend;
+use crate::commands::{audit_binary, cargo_audit_available};
+use crate::depot::{Depot, Krates, SemverClass, fetch_metadata};
+use crate::errors::Error;
+use crate::paths;
+use crate::policy::{Policy, Violation};
+use futures::future::join_all;
+use serde::Deserialize;
+use serde_json::json;
+use std::fmt::{Display, Write};
+
+/// How bad a finding is. Ordered from least to most severe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Worth knowing, e.g. a minor or patch update.
+    Note,
+    /// Should be looked at, e.g. a major update or an unmaintained dependency.
+    Warning,
+    /// Has to be fixed, e.g. a yanked version, a policy violation or a vulnerability.
+    Error,
+}
+
+impl Severity {
+    /// The SARIF `level` of findings with this severity.
+    fn sarif_level(self) -> &'static str {
+        match self {
+            Self::Note => "note",
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.sarif_level())
+    }
+}
+
+/// What a finding is about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Rule {
+    /// A newer version is available.
+    Outdated,
+    /// The crate breaks the team policy, see [`Policy`].
+    Policy,
+    /// The installed version was yanked from the registry.
+    Yanked,
+    /// A RustSec advisory affects a dependency built into the crate's binaries.
+    Advisory {
+        /// The advisory's ID, e.g. `RUSTSEC-2024-0001`.
+        id: String,
+        /// What the advisory is about.
+        title: String,
+    },
+}
+
+impl Rule {
+    /// The ID of the rule in JUnit and SARIF, the advisory's ID for advisories.
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Outdated => "outdated",
+            Self::Policy => "policy",
+            Self::Yanked => "yanked",
+            Self::Advisory { id, .. } => id,
+        }
+    }
+
+    fn description(&self) -> &str {
+        match self {
+            Self::Outdated => "A newer version of the crate is available",
+            Self::Policy => "The crate breaks the team policy",
+            Self::Yanked => "The installed version of the crate was yanked",
+            Self::Advisory { title, .. } => title,
+        }
+    }
+
+    fn help_url(&self) -> Option<String> {
+        match self {
+            Self::Advisory { id, .. } => Some(format!("https://rustsec.org/advisories/{id}.html")),
+            _ => None,
+        }
+    }
+}
+
+/// Something the check found wrong with a crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Finding {
+    /// The crate it was found for.
+    pub krate: String,
+    /// The rule it breaks.
+    pub rule: Rule,
+    /// How bad it is.
+    pub severity: Severity,
+    /// What is wrong, e.g. `0.24.0 has been yanked`.
+    pub message: String,
+}
+
+/// The findings for every installed crate, and for the crates the policy requires.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CheckReport {
+    /// Every crate that was checked, in order. Each one is a test case in JUnit.
+    pub crates: Vec<String>,
+    /// Everything that was found, in the order of `crates`.
+    pub findings: Vec<Finding>,
+    /// What was left out of the check, e.g. advisories when cargo-audit is not installed.
+    pub notes: Vec<String>,
+    /// Tools that failed, e.g. cargo-audit on a binary it could not read. The report is
+    /// incomplete when there are any.
+    pub failures: Vec<String>,
+}
+
+/// The exit code of a check that could not be completed because a tool failed.
+pub const TOOL_FAILURE_EXIT_CODE: i32 = 3;
+
+impl CheckReport {
+    /// Checks the installed crates for updates, yanked versions and advisories, and against
+    /// `policy` when given. Advisories are only checked when `cargo audit` is installed.
+    pub async fn get(
+        backend: &dyn CargoBackend,
+        depot: &mut Depot,
+        policy: Option<&Policy>,
+    ) -> Result<Self, Error> {
+        let info = join_all(
+            depot
+                .store
+                .0
+                .iter()
+                .map(|k| fetch_metadata(backend, &k.name, Some(&k.version))),
+        )
+        .await;
+        depot.sync(info)?;
+
+        let mut advisories = vec![];
+        // Crates that can't be looked up can't be checked for updates or yanked versions.
+        let mut notes: Vec<String> = depot
+            .store
+            .0
+            .iter()
+            .filter_map(|k| {
+                Some(format!(
+                    "could not look up {}: {}",
+                    k.name,
+                    k.fetch_error()?
+                ))
+            })
+            .collect();
+        let mut failures = vec![];
+        if !cargo_audit_available().await {
+            notes.push("cargo-audit is not installed, so advisories are not checked".to_string());
+        } else if let Some(bin_dir) = paths::cargo_bin_dir() {
+            for krate in &depot.store.0 {
+                for binary in &krate.binaries {
+                    // Binaries built without `cargo auditable` or a failed advisory database
+                    // fetch only fail the binary, not the whole check.
+                    let report = audit_binary(&bin_dir.join(binary)).await;
+                    match report.and_then(|r| parse_audit(&krate.name, &r)) {
+                        Ok(findings) => {
+                            for finding in findings {
+                                if !advisories.contains(&finding) {
+                                    advisories.push(finding);
+                                }
+                            }
+                        }
+                        Err(e) => failures
+                            .push(format!("could not audit {binary} of {}: {e}", krate.name)),
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            notes,
+            failures,
+            ..Self::between(&depot.store, policy, advisories)
+        })
+    }
+
+    fn between(krates: &Krates, policy: Option<&Policy>, advisories: Vec<Finding>) -> Self {
+        let mut crates: Vec<String> = krates.0.iter().map(|k| k.name.clone()).collect();
+        let mut findings = vec![];
+
+        for krate in &krates.0 {
+            if !krate.is_latest() {
+                let class = krate.semver_class();
+                findings.push(Finding {
+                    krate: krate.name.clone(),
+                    rule: Rule::Outdated,
+                    severity: match class {
+                        SemverClass::Major => Severity::Warning,
+                        _ => Severity::Note,
+                    },
+                    message: format!(
+                        "{} is installed, {} is available ({class})",
+                        krate.version,
+                        krate.latest_version()
+                    ),
+                });
+            }
+            if krate.info().yanked {
+                findings.push(Finding {
+                    krate: krate.name.clone(),
+                    rule: Rule::Yanked,
+                    severity: Severity::Error,
+                    message: format!("{} has been yanked", krate.version),
+                });
+            }
+        }
+
+        for violation in policy.map(|p| p.check(krates)).unwrap_or_default() {
+            if let Violation::Missing { krate } = &violation {
+                crates.push(krate.clone());
+            }
+            findings.push(Finding {
+                krate: violation.krate().to_string(),
+                rule: Rule::Policy,
+                severity: Severity::Error,
+                message: violation.to_string(),
+            });
+        }
+
+        findings.extend(advisories);
+        crates.sort();
+
+        Self {
+            crates,
+            findings,
+            ..Default::default()
+        }
+    }
+
+    /// The findings for `krate`.
+    pub fn findings_for(&self, krate: &str) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(move |f| f.krate == krate)
+    }
+
+    /// The exit code for CI: [`TOOL_FAILURE_EXIT_CODE`] when a tool failed, 2 when there are
+    /// errors at or above `fail_on`, 1 when there are only notes or warnings at or above it, 0
+    /// otherwise.
+    pub fn exit_code(&self, fail_on: Severity) -> i32 {
+        if !self.failures.is_empty() {
+            return TOOL_FAILURE_EXIT_CODE;
+        }
+        match self.findings.iter().map(|f| f.severity).max() {
+            Some(worst) if worst >= fail_on && worst == Severity::Error => 2,
+            Some(worst) if worst >= fail_on => 1,
+            _ => 0,
+        }
+    }
+
+    /// A JUnit report with one test case per crate. A test case fails when the crate has
+    /// findings at or above `fail_on`. The other findings are kept in its `system-out`.
+    pub fn to_junit(&self, fail_on: Severity) -> Result<String, Error> {
+        let failures = self
+            .crates
+            .iter()
+            .filter(|k| self.findings_for(k).any(|f| f.severity >= fail_on))
+            .count();
+
+        let mut xml = String::new();
+        writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            xml,
+            r#"<testsuites name="depot" tests="{}" failures="{failures}">"#,
+            self.crates.len()
+        )?;
+        writeln!(
+            xml,
+            r#"  <testsuite name="installed crates" tests="{}" failures="{failures}">"#,
+            self.crates.len()
+        )?;
+        for krate in &self.crates {
+            let (failed, passed): (Vec<&Finding>, Vec<&Finding>) = self
+                .findings_for(krate)
+                .partition(|f| f.severity >= fail_on);
+            let name = escape_xml(krate);
+            if failed.is_empty() && passed.is_empty() {
+                writeln!(xml, r#"    <testcase classname="depot" name="{name}"/>"#)?;
+                continue;
+            }
+
+            writeln!(xml, r#"    <testcase classname="depot" name="{name}">"#)?;
+            if let Some(first) = failed.first() {
+                let details: Vec<String> = failed.iter().map(|f| escape_xml(&f.message)).collect();
+                writeln!(
+                    xml,
+                    r#"      <failure type="{}" message="{}">{}</failure>"#,
+                    escape_xml(first.rule.id()),
+                    escape_xml(&first.message),
+                    details.join("\n")
+                )?;
+            }
+            if !passed.is_empty() {
+                let details: Vec<String> = passed
+                    .iter()
+                    .map(|f| format!("{}: {}", f.severity, escape_xml(&f.message)))
+                    .collect();
+                writeln!(xml, "      <system-out>{}</system-out>", details.join("\n"))?;
+            }
+            writeln!(xml, "    </testcase>")?;
+        }
+        if !self.notes.is_empty() || !self.failures.is_empty() {
+            let details: Vec<String> = self
+                .notes
+                .iter()
+                .map(|n| format!("note: {}", escape_xml(n)))
+                .chain(
+                    self.failures
+                        .iter()
+                        .map(|f| format!("failed: {}", escape_xml(f))),
+                )
+                .collect();
+            writeln!(xml, "    <system-err>{}</system-err>", details.join("\n"))?;
+        }
+        writeln!(xml, "  </testsuite>")?;
+        writeln!(xml, "</testsuites>")?;
+
+        Ok(xml)
+    }
+
+    /// A SARIF 2.1.0 log with a result for every finding. Results point at the crate through a
+    /// logical location, since there is no source file to point at.
+    pub fn to_sarif(&self) -> Result<String, Error> {
+        let mut rules: Vec<&Rule> = vec![];
+        for finding in &self.findings {
+            if !rules.iter().any(|r| r.id() == finding.rule.id()) {
+                rules.push(&finding.rule);
+            }
+        }
+        let rules: Vec<serde_json::Value> = rules
+            .iter()
+            .map(|r| {
+                let mut rule = json!({
+                    "id": r.id(),
+                    "shortDescription": { "text": r.description() },
+                });
+                if let Some(url) = r.help_url() {
+                    rule["helpUri"] = json!(url);
+                }
+                rule
+            })
+            .collect();
+        let results: Vec<serde_json::Value> = self
+            .findings
+            .iter()
+            .map(|f| {
+                json!({
+                    "ruleId": f.rule.id(),
+                    "level": f.severity.sarif_level(),
+                    "message": { "text": format!("{}: {}", f.krate, f.message) },
+                    "locations": [{
+                        "logicalLocations": [{ "name": f.krate, "kind": "package" }],
+                    }],
+                })
+            })
+            .collect();
+        let notifications: Vec<serde_json::Value> = self
+            .notes
+            .iter()
+            .map(|n| json!({ "level": "note", "message": { "text": n } }))
+            .chain(
+                self.failures
+                    .iter()
+                    .map(|f| json!({ "level": "error", "message": { "text": f } })),
+            )
+            .collect();
+
+        let sarif = json!({
+            "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+            "version": "2.1.0",
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "depot",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                        "rules": rules,
+                    },
+                },
+                "results": results,
+                "invocations": [{
+                    "executionSuccessful": self.failures.is_empty(),
+                    "toolExecutionNotifications": notifications,
+                }],
+            }],
+        });
+
+        Ok(serde_json::to_string_pretty(&sarif)?)
+    }
+}
+
+impl Display for CheckReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.findings.is_empty() {
+            writeln!(f, "No findings.")?;
+        }
+        for krate in &self.crates {
+            for finding in self.findings_for(krate) {
+                writeln!(f, "{krate:<24}{}: {}", finding.severity, finding.message)?;
+            }
+        }
+        for note in &self.notes {
+            writeln!(f, "Note: {note}.")?;
+        }
+        for failure in &self.failures {
+            writeln!(f, "Failed: {failure}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The parts of `cargo audit --json` that depot reads.
+#[derive(Debug, Deserialize)]
+struct AuditReport {
+    vulnerabilities: AuditVulnerabilities,
+    /// Unmaintained, unsound and yanked dependencies, by kind.
+    #[serde(default)]
+    warnings: std::collections::BTreeMap<String, Vec<AuditEntry>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuditVulnerabilities {
+    list: Vec<AuditEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuditEntry {
+    /// Missing for yanked dependencies.
+    advisory: Option<Advisory>,
+    package: AuditPackage,
+}
+
+#[derive(Debug, Deserialize)]
+struct Advisory {
+    id: String,
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AuditPackage {
+    name: String,
+    version: String,
+}
+
+/// Reads the findings in the `cargo audit` report of one of `krate`'s binaries. Vulnerabilities
+/// are errors, informational advisories such as unmaintained dependencies are warnings.
+fn parse_audit(krate: &str, report: &str) -> Result<Vec<Finding>, Error> {
+    let report: AuditReport = serde_json::from_str(report)?;
+    let vulnerabilities = report
+        .vulnerabilities
+        .list
+        .into_iter()
+        .map(|e| (Severity::Error, e));
+    let warnings = report
+        .warnings
+        .into_values()
+        .flatten()
+        .map(|e| (Severity::Warning, e));
+
+    Ok(vulnerabilities
+        .chain(warnings)
+        .filter_map(|(severity, entry)| {
+            let advisory = entry.advisory?;
+            Some(Finding {
+                krate: krate.to_string(),
+                message: format!(
+                    "{} {}: {} ({})",
+                    entry.package.name, entry.package.version, advisory.title, advisory.id
+                ),
+                rule: Rule::Advisory {
+                    id: advisory.id,
+                    title: advisory.title,
+                },
+                severity,
+            })
+        })
+        .collect())
+}
+
+fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CheckReport, Finding, Rule, Severity, TOOL_FAILURE_EXIT_CODE, parse_audit};
+    use crate::depot::{Krate, Krates};
+    use crate::policy::Policy;
+    use pretty_assertions::assert_eq;
+    use versions::SemVer;
+
+    fn krate(name: &str, version: &str) -> Krate {
+        let mut k = Krate::default();
+        k.name = name.to_string();
+        k.version = SemVer::new(version).unwrap();
+        k
+    }
+
+    fn advisory(krate: &str) -> Finding {
+        Finding {
+            krate: krate.to_string(),
+            rule: Rule::Advisory {
+                id: "RUSTSEC-2024-0001".to_string(),
+                title: "Out-of-bounds read".to_string(),
+            },
+            severity: Severity::Error,
+            message: "smallvec 1.0.0: Out-of-bounds read (RUSTSEC-2024-0001)".to_string(),
+        }
+    }
+
+    #[test]
+    fn check_against_policy() {
+        let policy = Policy {
+            required: ["cargo-deny".to_string()].into(),
+            ..Default::default()
+        };
+        let krates = Krates(vec![krate("bat", "0.25.0"), krate("ripgrep", "14.1.1")]);
+        let report = CheckReport::between(&krates, Some(&policy), vec![advisory("bat")]);
+
+        assert_eq!(report.crates, vec!["bat", "cargo-deny", "ripgrep"]);
+        assert_eq!(
+            report.to_string(),
+            "bat                     error: smallvec 1.0.0: Out-of-bounds read (RUSTSEC-2024-0001)
+cargo-deny              error: cargo-deny is required but not installed
+"
+        );
+        assert_eq!(report.exit_code(Severity::Error), 2);
+    }
+
+    #[test]
+    fn exit_codes_follow_the_threshold() {
+        let mut report = CheckReport {
+            crates: vec!["bat".to_string()],
+            findings: vec![Finding {
+                krate: "bat".to_string(),
+                rule: Rule::Outdated,
+                severity: Severity::Warning,
+                message: "0.24.0 is installed, 1.0.0 is available (major)".to_string(),
+            }],
+            ..Default::default()
+        };
+
+        assert_eq!(report.exit_code(Severity::Note), 1);
+        assert_eq!(report.exit_code(Severity::Warning), 1);
+        assert_eq!(report.exit_code(Severity::Error), 0);
+
+        report.findings.clear();
+        assert_eq!(report.exit_code(Severity::Note), 0);
+
+        report
+            .failures
+            .push("could not audit bat of bat: no report".to_string());
+        assert_eq!(report.exit_code(Severity::Error), TOOL_FAILURE_EXIT_CODE);
+    }
+
+    #[test]
+    fn write_junit() {
+        let report = CheckReport {
+            crates: vec!["bat".to_string(), "ripgrep".to_string()],
+            findings: vec![advisory("bat")],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            report.to_junit(Severity::Error).unwrap(),
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="depot" tests="2" failures="1">
+  <testsuite name="installed crates" tests="2" failures="1">
+    <testcase classname="depot" name="bat">
+      <failure type="RUSTSEC-2024-0001" message="smallvec 1.0.0: Out-of-bounds read (RUSTSEC-2024-0001)">smallvec 1.0.0: Out-of-bounds read (RUSTSEC-2024-0001)</failure>
+    </testcase>
+    <testcase classname="depot" name="ripgrep"/>
+  </testsuite>
+</testsuites>
+"#
+        );
+    }
+
+    #[test]
+    fn write_sarif() {
+        let report = CheckReport {
+            crates: vec!["bat".to_string()],
+            findings: vec![advisory("bat")],
+            failures: vec!["could not audit bat of bat: no report".to_string()],
+            ..Default::default()
+        };
+        let sarif: serde_json::Value = serde_json::from_str(&report.to_sarif().unwrap()).unwrap();
+        let run = &sarif["runs"][0];
+
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(
+            run["tool"]["driver"]["rules"][0]["helpUri"],
+            "https://rustsec.org/advisories/RUSTSEC-2024-0001.html"
+        );
+        assert_eq!(run["results"][0]["ruleId"], "RUSTSEC-2024-0001");
+        assert_eq!(run["results"][0]["level"], "error");
+        assert_eq!(
+            run["results"][0]["locations"][0]["logicalLocations"][0]["name"],
+            "bat"
+        );
+        assert_eq!(run["invocations"][0]["executionSuccessful"], false);
+        assert_eq!(
+            run["invocations"][0]["toolExecutionNotifications"][0]["level"],
+            "error"
+        );
+    }
+
+    #[test]
+    fn parse_audit_report() {
+        let report = r#"{
+  "database": {},
+  "vulnerabilities": {
+    "found": true,
+    "count": 1,
+    "list": [{
+      "advisory": { "id": "RUSTSEC-2024-0001", "title": "Out-of-bounds read" },
+      "package": { "name": "smallvec", "version": "1.0.0" }
+    }]
+  },
+  "warnings": {
+    "unmaintained": [{
+      "kind": "unmaintained",
+      "advisory": { "id": "RUSTSEC-2024-0002", "title": "ansi_term is unmaintained" },
+      "package": { "name": "ansi_term", "version": "0.12.1" }
+    }],
+    "yanked": [{
+      "kind": "yanked",
+      "advisory": null,
+      "package": { "name": "foo", "version": "0.1.0" }
+    }]
+  }
+}"#;
+        let findings = parse_audit("bat", report).unwrap();
+        // What cargo-audit prints for a binary built without `cargo auditable`.
+        assert!(parse_audit("bat", "").is_err());
+
+        assert_eq!(
+            findings
+                .iter()
+                .map(|f| (f.rule.id(), f.severity))
+                .collect::<Vec<_>>(),
+            vec![
+                ("RUSTSEC-2024-0001", Severity::Error),
+                ("RUSTSEC-2024-0002", Severity::Warning)
+            ]
+        );
+    }
+}
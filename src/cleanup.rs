@@ -0,0 +1,224 @@
+//! Binaries in cargo's bin directory that no crate owns, and binaries that crates own but are
+//! gone.
+
+use crate::depot::Krates;
+use crate::errors::Error;
+use crate::paths;
+use std::collections::HashSet;
+use std::env::consts::EXE_SUFFIX;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Binaries rustup links into cargo's bin directory. They are not installed with `cargo install`,
+/// so they are never orphans.
+const RUSTUP_PROXIES: &[&str] = &[
+    "cargo",
+    "cargo-clippy",
+    "cargo-fmt",
+    "cargo-miri",
+    "clippy-driver",
+    "rls",
+    "rust-analyzer",
+    "rust-gdb",
+    "rust-gdbgui",
+    "rust-lldb",
+    "rustc",
+    "rustdoc",
+    "rustfmt",
+    "rustup",
+];
+
+/// Something in cargo's bin directory that is out of sync with the installed crates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum CleanupItem {
+    /// An executable that no installed crate owns.
+    Orphan(String),
+    /// A binary of an installed crate that is no longer there.
+    Missing {
+        /// The crate that installed the binary.
+        krate: String,
+        /// The binary's file name.
+        binary: String,
+    },
+}
+
+/// Everything in cargo's bin directory that is out of sync, orphans first.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Cleanup(pub Vec<CleanupItem>);
+
+impl Cleanup {
+    /// Compares cargo's bin directory against the binaries of the installed crates.
+    pub fn get(krates: &Krates) -> Result<Self, Error> {
+        let Some(bin_dir) = paths::cargo_bin_dir() else {
+            return Ok(Self::default());
+        };
+        if !bin_dir.exists() {
+            return Ok(Self::find(krates, &[]));
+        }
+
+        let mut files = vec![];
+        for entry in fs::read_dir(&bin_dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir()
+                && let Some(name) = entry.file_name().to_str()
+                && !is_rustup_proxy(&bin_dir, name)
+            {
+                files.push(name.to_string());
+            }
+        }
+
+        Ok(Self::find(krates, &files))
+    }
+
+    /// Finds the orphans among `files` and the binaries of `krates` that are not in `files`.
+    fn find(krates: &Krates, files: &[String]) -> Self {
+        let owned: HashSet<&str> = krates
+            .0
+            .iter()
+            .flat_map(|k| k.binaries.iter().map(String::as_str))
+            .collect();
+        let present: HashSet<&str> = files.iter().map(String::as_str).collect();
+
+        let mut orphans: Vec<CleanupItem> = files
+            .iter()
+            .filter(|f| !f.starts_with('.') && !owned.contains(f.as_str()))
+            .map(|f| CleanupItem::Orphan(f.clone()))
+            .collect();
+        orphans.sort_by(|a, b| a.name().cmp(b.name()));
+
+        let missing = krates.0.iter().flat_map(|k| {
+            k.binaries
+                .iter()
+                .filter(|b| !present.contains(b.as_str()))
+                .map(|b| CleanupItem::Missing {
+                    krate: k.name.clone(),
+                    binary: b.clone(),
+                })
+        });
+
+        Self(orphans.into_iter().chain(missing).collect())
+    }
+
+    /// How many executables no crate owns.
+    pub fn orphan_count(&self) -> usize {
+        self.0
+            .iter()
+            .filter(|i| matches!(i, CleanupItem::Orphan(_)))
+            .count()
+    }
+
+    /// How many binaries of installed crates are gone.
+    pub fn missing_count(&self) -> usize {
+        self.0.len() - self.orphan_count()
+    }
+}
+
+impl CleanupItem {
+    /// The file name of the binary.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Orphan(name) => name,
+            Self::Missing { binary, .. } => binary,
+        }
+    }
+}
+
+/// Where the binary `name` is in cargo's bin directory, if that is known.
+pub fn orphan_path(name: &str) -> Option<PathBuf> {
+    paths::cargo_bin_dir().map(|d| d.join(name))
+}
+
+/// Deletes an orphaned executable from cargo's bin directory.
+pub fn remove_orphan(name: &str) -> Result<(), Error> {
+    if let Some(path) = orphan_path(name) {
+        fs::remove_file(path)?;
+    }
+
+    Ok(())
+}
+
+/// Rustup installs its proxies as hard links to itself, so anything sharing the `rustup` file is
+/// a proxy too, including ones added by newer versions of rustup.
+fn is_rustup_proxy(bin_dir: &Path, name: &str) -> bool {
+    let stem = name.strip_suffix(EXE_SUFFIX).unwrap_or(name);
+    if RUSTUP_PROXIES.contains(&stem) {
+        return true;
+    }
+
+    is_same_inode(
+        &bin_dir.join(name),
+        &bin_dir.join(format!("rustup{EXE_SUFFIX}")),
+    )
+}
+
+#[cfg(unix)]
+fn is_same_inode(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_same_inode(_a: &Path, _b: &Path) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Cleanup, CleanupItem, is_rustup_proxy};
+    use crate::depot::{Krate, Krates};
+    use pretty_assertions::assert_eq;
+    use std::path::Path;
+
+    fn krate(name: &str, binaries: &[&str]) -> Krate {
+        let mut k = Krate::default();
+        k.name = name.to_string();
+        k.binaries = binaries.iter().map(|b| b.to_string()).collect();
+        k
+    }
+
+    #[test]
+    fn find_orphans_and_missing_binaries() {
+        let krates = Krates(vec![
+            krate("bat", &["bat"]),
+            krate("ripgrep", &["rg"]),
+            krate("wasm-bindgen-cli", &["wasm-bindgen", "wasm2es6js"]),
+        ]);
+        let files = ["rg", "wasm-bindgen", "old-tool", ".DS_Store", "another"]
+            .map(String::from)
+            .to_vec();
+        let cleanup = Cleanup::find(&krates, &files);
+
+        assert_eq!(
+            cleanup.0,
+            vec![
+                CleanupItem::Orphan("another".to_string()),
+                CleanupItem::Orphan("old-tool".to_string()),
+                CleanupItem::Missing {
+                    krate: "bat".to_string(),
+                    binary: "bat".to_string(),
+                },
+                CleanupItem::Missing {
+                    krate: "wasm-bindgen-cli".to_string(),
+                    binary: "wasm2es6js".to_string(),
+                },
+            ]
+        );
+        assert_eq!(cleanup.orphan_count(), 2);
+        assert_eq!(cleanup.missing_count(), 2);
+    }
+
+    #[test]
+    fn recognise_rustup_proxies() {
+        let bin_dir = Path::new("/nonexistent/bin");
+
+        assert!(is_rustup_proxy(bin_dir, "cargo"));
+        assert!(is_rustup_proxy(bin_dir, "rustfmt"));
+        assert!(is_rustup_proxy(bin_dir, "rust-analyzer"));
+        assert!(!is_rustup_proxy(bin_dir, "cargo-nextest"));
+    }
+}
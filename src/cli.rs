@@ -0,0 +1,684 @@
+use crate::app::App;
+use crate::ui::views::{View, drift_view::Drift, import_view::Import};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use depot_rs::backend::{CargoBackend, CargoCli};
+use depot_rs::check::{CheckReport, Severity};
+use depot_rs::depot::{Depot, Krate, fetch_metadata, install_krate, install_spec};
+use depot_rs::depotfile::Depotfile;
+use depot_rs::doctor::{DoctorReport, Status};
+use depot_rs::drift::DriftReport;
+use depot_rs::errors::Error;
+use depot_rs::hooks::Hooks;
+use depot_rs::journal::Journal;
+use depot_rs::operation::{OperationKind, Outcome};
+use depot_rs::plan::UpdatePlan;
+use depot_rs::policy::{Compliance, Policy};
+use depot_rs::records::InstallRecords;
+use depot_rs::schema::{self, KrateDetail, KrateList, KrateRecord};
+use depot_rs::tracking::InstallTracking;
+use futures::FutureExt;
+use futures::future::join_all;
+use std::fs;
+use std::path::PathBuf;
+use versions::SemVer;
+
+/// A TUI for managing your crates.
+///
+/// Runs the TUI when no command is given.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// List the installed crates.
+    List(FormatArgs),
+    /// List the installed crates that have a newer version.
+    Outdated(FormatArgs),
+    /// Show what is known about a crate.
+    Info(InfoArgs),
+    /// Update installed crates.
+    Update(UpdateArgs),
+    /// Uninstall crates.
+    Uninstall(UninstallArgs),
+    /// Fetch the latest information about the installed crates and drop stale install records.
+    Refresh,
+    /// Write a Depotfile listing the installed crates.
+    Export(ExportArgs),
+    /// Install the crates of a Depotfile that are missing.
+    Import(ImportArgs),
+    /// Compare the installed crates with a Depotfile. Exits with 1 when they differ.
+    Diff(DiffArgs),
+    /// Check the installed crates for updates, yanked versions, advisories and policy violations.
+    ///
+    /// Exits with 2 when there are errors at or above `--fail-on`, and with 1 when there are
+    /// only notes or warnings at or above it.
+    Check(CheckArgs),
+    /// Check that cargo and depot's environment are set up correctly. Exits with 1 on failures.
+    Doctor,
+    /// Work with the team policy.
+    #[command(subcommand)]
+    Policy(PolicyCommand),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PolicyCommand {
+    /// Check the installed crates against the policy. Exits with 1 on violations.
+    Check(PolicyCheckArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct UpdateArgs {
+    /// The crates to update.
+    #[arg(required_unless_present = "all", conflicts_with = "all")]
+    pub crates: Vec<String>,
+    /// Update every outdated crate.
+    #[arg(long)]
+    pub all: bool,
+    /// Print the update plan without changing anything.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct FormatArgs {
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+pub struct InfoArgs {
+    /// The crate to show. Doesn't have to be installed.
+    #[arg(value_name = "CRATE")]
+    pub krate: String,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+pub struct UninstallArgs {
+    /// The crates to uninstall.
+    #[arg(required = true)]
+    pub crates: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    /// Where to write the Depotfile. Prints it when not given.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct ImportArgs {
+    /// The Depotfile to read.
+    pub file: PathBuf,
+    /// Print the commands that would be run without installing anything.
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Review the missing crates in the TUI before installing them.
+    #[arg(long, conflicts_with = "dry_run")]
+    pub tui: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct DiffArgs {
+    /// The Depotfile to compare against.
+    pub manifest: PathBuf,
+    /// Show the report in the TUI, where the differences can be fixed.
+    #[arg(long)]
+    pub tui: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct PolicyCheckArgs {
+    /// The policy file. Defaults to the one set in the config.
+    #[arg(long)]
+    pub policy: Option<PathBuf>,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: OutputFormat,
+}
+
+#[derive(Debug, Args)]
+pub struct CheckArgs {
+    #[arg(long, value_enum, default_value_t)]
+    pub format: CheckFormat,
+    /// The least severe finding that fails the check.
+    #[arg(long, value_enum, default_value_t = FailOn::Error)]
+    pub fail_on: FailOn,
+    /// The policy file. Defaults to the one set in the config, if any.
+    #[arg(long)]
+    pub policy: Option<PathBuf>,
+    /// Where to write the report. Prints it when not given.
+    #[arg(short, long)]
+    pub output: Option<PathBuf>,
+}
+
+/// The values of `--fail-on`, one for each [`Severity`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum FailOn {
+    Note,
+    Warning,
+    Error,
+}
+
+impl From<FailOn> for Severity {
+    fn from(fail_on: FailOn) -> Self {
+        match fail_on {
+            FailOn::Note => Severity::Note,
+            FailOn::Warning => Severity::Warning,
+            FailOn::Error => Severity::Error,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum CheckFormat {
+    #[default]
+    Text,
+    Junit,
+    Sarif,
+}
+
+/// How results are printed. See [`schema`] for the JSON.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    #[default]
+    Text,
+    /// A single JSON document.
+    Json,
+    /// One JSON object per line, printed as soon as it is known.
+    Ndjson,
+}
+
+impl Command {
+    pub async fn run(self) -> Result<(), Error> {
+        match self {
+            Command::List(args) => list(args).await,
+            Command::Outdated(args) => outdated(args).await,
+            Command::Info(args) => info(args).await,
+            Command::Update(args) => update(args).await,
+            Command::Uninstall(args) => uninstall(args).await,
+            Command::Refresh => refresh().await,
+            Command::Export(args) => export(args).await,
+            Command::Import(args) => import(args).await,
+            Command::Diff(args) => diff(args).await,
+            Command::Check(args) => check(args).await,
+            Command::Doctor => doctor().await,
+            Command::Policy(PolicyCommand::Check(args)) => check_policy(args).await,
+        }
+    }
+}
+
+async fn export(args: ExportArgs) -> Result<(), Error> {
+    let depot = Depot::get(&CargoCli).await?;
+    let depotfile = Depotfile::export(&depot.store, &InstallTracking::load()?).to_toml()?;
+    match args.output {
+        Some(path) => fs::write(path, depotfile)?,
+        None => print!("{depotfile}"),
+    }
+
+    Ok(())
+}
+
+async fn import(args: ImportArgs) -> Result<(), Error> {
+    let depotfile = Depotfile::load(&args.file)?;
+    if args.tui {
+        let mut app = App::new().await?;
+        app.state.import = depotfile;
+        app.view = View::Import(Import);
+        return run_tui(app).await;
+    }
+
+    let depot = Depot::get(&CargoCli).await?;
+    let missing = depotfile.missing(&depot.store);
+    if missing.is_empty() {
+        println!("Every crate in {} is installed.", args.file.display());
+        return Ok(());
+    }
+
+    let journal = Journal::open();
+    let hooks = Hooks::load()?;
+    let mut failed = vec![];
+    for (name, spec) in missing {
+        if args.dry_run {
+            println!("cargo {}", spec.install_args(name).join(" "));
+            continue;
+        }
+
+        println!("Installing {name}...");
+        let target = spec.version.as_deref().and_then(SemVer::new);
+        let install = install_spec(&CargoCli, name, spec);
+        let outcome = journal
+            .run(
+                &CargoCli,
+                &hooks,
+                OperationKind::Install,
+                name,
+                target.as_ref(),
+                install,
+            )
+            .await;
+        match with_warnings(outcome) {
+            Ok(method) => println!("Installed {name} ({method})"),
+            Err(e) => {
+                eprintln!("Failed to install {name}: {e}");
+                failed.push(name);
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(Error::CommandFailed(format!(
+            "failed to install {}",
+            failed.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+async fn list(args: FormatArgs) -> Result<(), Error> {
+    let depot = Depot::get(&CargoCli).await?;
+    let tracking = InstallTracking::load()?;
+    let records = depot
+        .store
+        .0
+        .iter()
+        .map(|k| KrateRecord::new(k, tracking.source(&k.name)));
+
+    match args.format {
+        OutputFormat::Text => {
+            for krate in &depot.store.0 {
+                println!(
+                    "{:<24}{:<12}{}",
+                    krate.name,
+                    krate.version.to_string(),
+                    krate.binaries.join(", ")
+                );
+            }
+        }
+        OutputFormat::Json => {
+            let crates: Vec<KrateRecord> = records.collect();
+            println!("{}", schema::to_json(&KrateList::new(&crates))?);
+        }
+        OutputFormat::Ndjson => {
+            for record in records {
+                println!("{}", schema::to_ndjson_line(&record)?);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn outdated(args: FormatArgs) -> Result<(), Error> {
+    let mut depot = Depot::get(&CargoCli).await?;
+    let tracking = InstallTracking::load()?;
+    let names: Vec<String> = depot.store.0.iter().map(|k| k.name.clone()).collect();
+
+    // Crates are checked one by one, so that NDJSON consumers see each one as soon as it is known.
+    let mut outdated = vec![];
+    for name in names {
+        depot.sync(vec![fetch_metadata(&CargoCli, &name, None).await])?;
+        let Some(krate) = depot.store.0.iter().find(|k| k.name == name) else {
+            continue;
+        };
+        if krate.is_latest() {
+            continue;
+        }
+
+        let record = KrateRecord::new(krate, tracking.source(&name));
+        match args.format {
+            OutputFormat::Text => println!(
+                "{:<24}{} -> {} ({})",
+                krate.name,
+                krate.version,
+                krate.latest_version(),
+                krate.semver_class()
+            ),
+            OutputFormat::Json => outdated.push(record),
+            OutputFormat::Ndjson => println!("{}", schema::to_ndjson_line(&record)?),
+        }
+    }
+
+    match args.format {
+        OutputFormat::Text if depot.outdated_krate_count()? == 0 => {
+            println!("All crates are up-to-date!")
+        }
+        OutputFormat::Json => println!("{}", schema::to_json(&KrateList::new(&outdated))?),
+        _ => {}
+    }
+
+    Ok(())
+}
+
+async fn info(args: InfoArgs) -> Result<(), Error> {
+    let metadata = CargoCli.info(&args.krate, None).await?;
+    let mut depot = Depot::get(&CargoCli).await?;
+    let krate = if depot.store.0.iter().any(|k| k.name == args.krate) {
+        depot.sync(vec![metadata])?;
+        depot
+            .store
+            .0
+            .into_iter()
+            .find(|k| k.name == args.krate)
+            .unwrap_or_default()
+    } else {
+        Krate::from_metadata(metadata)
+    };
+    if let Some(e) = krate.parse_error() {
+        return Err(Error::KrateInfo(e.to_string()));
+    }
+
+    if args.format != OutputFormat::Text {
+        let record = KrateRecord::new(&krate, InstallTracking::load()?.source(&krate.name));
+        let output = match args.format {
+            OutputFormat::Json => schema::to_json(&KrateDetail::new(&record))?,
+            _ => schema::to_ndjson_line(&record)?,
+        };
+        println!("{output}");
+        return Ok(());
+    }
+
+    let installed = !krate.binaries.is_empty();
+    let mut fields = vec![
+        ("name", krate.name.clone()),
+        ("description", krate.description()),
+        ("latest", krate.latest_version().to_string()),
+        ("license", krate.license()),
+        ("rust-version", krate.rust_version_str()),
+        ("documentation", krate.documentation()),
+        ("homepage", krate.homepage()),
+        ("repository", krate.repository()),
+    ];
+    if installed {
+        fields.extend([
+            ("installed", krate.version.to_string()),
+            ("binaries", krate.binaries.join(", ")),
+            ("install method", krate.install_method_str()),
+            ("disk usage", krate.size_str()),
+            ("installed at", krate.installed_at_str()),
+            ("last update", krate.updated_at_str()),
+            ("last used", krate.last_used_str()),
+        ]);
+    }
+    for (field, value) in fields {
+        if !value.is_empty() {
+            println!("{field:<16}{value}");
+        }
+    }
+
+    Ok(())
+}
+
+async fn update(args: UpdateArgs) -> Result<(), Error> {
+    let mut depot = Depot::get(&CargoCli).await?;
+    let names: Vec<String> = if args.all {
+        depot.store.0.iter().map(|k| k.name.clone()).collect()
+    } else {
+        args.crates
+    };
+
+    for name in &names {
+        if !depot.store.0.iter().any(|k| &k.name == name) {
+            return Err(Error::Unexpected(format!("{name} is not installed")));
+        }
+    }
+
+    let info = join_all(names.iter().map(|n| fetch_metadata(&CargoCli, n, None))).await;
+    depot.sync(info)?;
+    // Crates that can't be looked up are left as they are.
+    for krate in depot.store.0.iter().filter(|k| names.contains(&k.name)) {
+        if let Some(e) = krate.fetch_error() {
+            eprintln!("Warning: could not look up {}: {e}", krate.name);
+        }
+    }
+
+    let krates: Vec<_> = depot
+        .store
+        .0
+        .into_iter()
+        .filter(|k| names.contains(&k.name))
+        .filter(|k| !k.is_latest())
+        .collect();
+
+    if args.dry_run {
+        print!("{}", UpdatePlan::get(&krates).await?);
+        return Ok(());
+    }
+    if krates.is_empty() {
+        println!("All crates are up-to-date!");
+        return Ok(());
+    }
+
+    let journal = Journal::open();
+    let hooks = Hooks::load()?;
+    let mut failed = vec![];
+    for krate in &krates {
+        let latest = krate.latest_version();
+        println!("Updating {} {} -> {latest}...", krate.name, krate.version);
+        let update = install_krate(&CargoCli, &krate.name);
+        let outcome = journal
+            .run(
+                &CargoCli,
+                &hooks,
+                OperationKind::Update,
+                &krate.name,
+                Some(&latest),
+                update,
+            )
+            .await;
+        match with_warnings(outcome) {
+            Ok(method) => println!("Updated {} ({method})", krate.name),
+            Err(e) => {
+                eprintln!("Failed to update {}: {e}", krate.name);
+                failed.push(krate.name.as_str());
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(Error::CommandFailed(format!(
+            "failed to update {}",
+            failed.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+async fn uninstall(args: UninstallArgs) -> Result<(), Error> {
+    let depot = Depot::get(&CargoCli).await?;
+    let mut krates = vec![];
+    for name in &args.crates {
+        match depot.store.0.iter().find(|k| &k.name == name) {
+            Some(krate) => krates.push(krate),
+            None => return Err(Error::Unexpected(format!("{name} is not installed"))),
+        }
+    }
+
+    let journal = Journal::open();
+    let hooks = Hooks::load()?;
+    let mut failed = vec![];
+    for krate in krates {
+        let uninstall = CargoCli.uninstall(&krate.name).map(Outcome::from);
+        let outcome = journal
+            .run(
+                &CargoCli,
+                &hooks,
+                OperationKind::Uninstall,
+                &krate.name,
+                None,
+                uninstall,
+            )
+            .await;
+        match with_warnings(outcome) {
+            Ok(()) => println!("Uninstalled {}", krate.name),
+            Err(e) => {
+                eprintln!("Failed to uninstall {}: {e}", krate.name);
+                failed.push(krate.name.as_str());
+            }
+        }
+    }
+
+    if !failed.is_empty() {
+        return Err(Error::CommandFailed(format!(
+            "failed to uninstall {}",
+            failed.join(", ")
+        )));
+    }
+
+    Ok(())
+}
+
+/// Prints the warnings of an operation and gives its result.
+fn with_warnings<T>(outcome: Outcome<T>) -> Result<T, Error> {
+    for warning in &outcome.warnings {
+        eprintln!("Warning: {warning}");
+    }
+
+    outcome.result
+}
+
+async fn refresh() -> Result<(), Error> {
+    let mut depot = Depot::get(&CargoCli).await?;
+    depot.fetch_info(&CargoCli).await?;
+    println!(
+        "Fetched the latest information about {} crates. {} are outdated.",
+        depot.crate_count(),
+        depot.outdated_krate_count()?
+    );
+    let unparseable = depot.unparseable_krate_count();
+    if unparseable != 0 {
+        println!("{unparseable} crates could not be read from `cargo info`.");
+    }
+    let unfetched = depot.unfetched_krate_count();
+    if unfetched != 0 {
+        println!("{unfetched} crates could not be looked up with `cargo info`.");
+    }
+
+    let installed: Vec<&str> = depot.store.0.iter().map(|k| k.name.as_str()).collect();
+    let mut records = InstallRecords::load()?;
+    let stale = records.prune(&installed);
+    if !stale.is_empty() {
+        records.save()?;
+        println!(
+            "Dropped the records of {}, which are no longer installed.",
+            stale.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+async fn diff(args: DiffArgs) -> Result<(), Error> {
+    let manifest = Depotfile::load(&args.manifest)?;
+    if args.tui {
+        let mut app = App::new().await?;
+        app.state.drift = DriftReport::get(&manifest, &Depot::get(&CargoCli).await?.store)?;
+        app.view = View::Drift(Drift);
+        return run_tui(app).await;
+    }
+
+    let report = DriftReport::get(&manifest, &Depot::get(&CargoCli).await?.store)?;
+    print!("{report}");
+    if !report.is_empty() {
+        // Lets provisioning scripts tell that the machine has drifted.
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+async fn check(args: CheckArgs) -> Result<(), Error> {
+    let policy = match args.policy {
+        Some(path) => Some(Policy::load(&path)?),
+        None => Policy::load_configured()?,
+    };
+    let report = CheckReport::get(
+        &CargoCli,
+        &mut Depot::get(&CargoCli).await?,
+        policy.as_ref(),
+    )
+    .await?;
+
+    let output = match args.format {
+        CheckFormat::Text => report.to_string(),
+        CheckFormat::Junit => report.to_junit(args.fail_on.into())?,
+        CheckFormat::Sarif => report.to_sarif()?,
+    };
+    match args.output {
+        Some(path) => fs::write(path, output)?,
+        None => print!("{output}"),
+    }
+    // The text report has them already, the others may be going to a file.
+    if args.format != CheckFormat::Text {
+        for note in &report.notes {
+            eprintln!("Note: {note}.");
+        }
+        for failure in &report.failures {
+            eprintln!("Failed: {failure}");
+        }
+    }
+
+    match report.exit_code(args.fail_on.into()) {
+        0 => Ok(()),
+        code => std::process::exit(code),
+    }
+}
+
+async fn doctor() -> Result<(), Error> {
+    let report = DoctorReport::run(&CargoCli).await;
+    print!("{report}");
+    if report.status() == Status::Fail {
+        // A failed check is a finding, not a failure of the command, which exits with 3.
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+async fn check_policy(args: PolicyCheckArgs) -> Result<(), Error> {
+    let policy = match args.policy {
+        Some(path) => Policy::load(&path)?,
+        None => Policy::load_configured()?.ok_or_else(|| {
+            Error::Unexpected("no policy given and none set in the config".to_string())
+        })?,
+    };
+    let violations = policy.check(&Depot::get(&CargoCli).await?.store);
+
+    match args.format {
+        OutputFormat::Text if violations.is_empty() => println!("No violations."),
+        OutputFormat::Text => {
+            for violation in &violations {
+                println!("{violation}");
+            }
+        }
+        OutputFormat::Json => println!("{}", schema::to_json(&Compliance::new(&violations))?),
+        OutputFormat::Ndjson => {
+            for violation in &violations {
+                println!("{}", schema::to_ndjson_line(violation)?);
+            }
+        }
+    }
+    if !violations.is_empty() {
+        // Lets CI tell violations apart from a policy that could not be checked, which exits
+        // with 3.
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Runs the TUI starting at the view chosen by a command.
+async fn run_tui(app: App) -> Result<(), Error> {
+    let terminal = ratatui::init();
+    let result = app.run(terminal).await;
+    ratatui::restore();
+
+    result
+}
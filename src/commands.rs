@@ -1,43 +1,359 @@
+use crate::config::{Config, ProcessConfig};
 use crate::errors::Error;
-use apply::Apply;
-use std::process::Command;
+use std::cell::RefCell;
+use std::path::Path;
+use std::process::Output;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
 
-/// Lists out all of the installed crates.
-pub fn list_crates() -> Result<String, Error> {
-    let stdout = Command::new("cargo")
-        .args(["install", "--list"])
-        .output()?
-        .stdout
-        .apply(String::from_utf8)?;
+/// Limits shared by every process depot starts, read from the config on first use.
+struct Limits {
+    config: ProcessConfig,
+    permits: Semaphore,
+    /// Installs have their own permits, so that a long build doesn't hold up quick commands.
+    install_permits: Semaphore,
+}
+
+static LIMITS: OnceLock<Limits> = OnceLock::new();
+
+fn limits() -> &'static Limits {
+    LIMITS.get_or_init(|| {
+        let config = Config::load().map(|c| c.process).unwrap_or_default();
+        Limits {
+            permits: Semaphore::new(config.max_concurrent.max(1)),
+            install_permits: Semaphore::new(config.max_concurrent_installs.max(1)),
+            config,
+        }
+    })
+}
+
+/// A command run inside [`capture`], with everything it printed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Invocation {
+    /// The command line, e.g. `cargo install ripgrep --locked`.
+    pub command: String,
+    /// `None` when the command was killed, e.g. because it timed out.
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+tokio::task_local! {
+    static CAPTURED: RefCell<Vec<Invocation>>;
+}
+
+/// Runs `f` and collects every command it ran, in order.
+pub(crate) async fn capture<F: Future>(f: F) -> (F::Output, Vec<Invocation>) {
+    CAPTURED
+        .scope(RefCell::new(vec![]), async {
+            let output = f.await;
+            (output, CAPTURED.with(|c| c.take()))
+        })
+        .await
+}
+
+tokio::task_local! {
+    static ON_START: RefCell<Option<Box<dyn FnOnce() + Send>>>;
+}
+
+/// Runs `f`, calling `on_start` once it starts changing crates, see [`started`].
+pub(crate) async fn on_start<F: Future>(
+    on_start: impl FnOnce() + Send + 'static,
+    f: F,
+) -> F::Output {
+    ON_START
+        .scope(RefCell::new(Some(Box::new(on_start))), f)
+        .await
+}
 
-    Ok(stdout)
+/// Calls the callback given to [`on_start`], if any and only the first time. Called when a hook
+/// starts, and when an install or uninstall got one of the permits.
+pub(crate) fn started() {
+    if let Ok(Some(on_start)) = ON_START.try_with(|s| s.borrow_mut().take()) {
+        on_start();
+    }
+}
+
+/// Adds a command to the ones being captured, if any.
+fn record(command: String, output: Option<&Output>) {
+    let _ = CAPTURED.try_with(|c| {
+        c.borrow_mut().push(Invocation {
+            command,
+            exit_code: output.and_then(|o| o.status.code()),
+            stdout: output.map_or_else(String::new, |o| {
+                String::from_utf8_lossy(&o.stdout).into_owned()
+            }),
+            stderr: output.map_or_else(String::new, |o| {
+                String::from_utf8_lossy(&o.stderr).into_owned()
+            }),
+        })
+    });
+}
+
+/// What a command does, which decides how long it may run and which permits it takes.
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Quick,
+    /// Uninstalls are quick, but they start an operation, see [`started`].
+    Uninstall,
+    /// Installs can build a crate from source, which takes much longer than anything else.
+    Install,
+}
+
+impl Kind {
+    fn timeout(self, config: &ProcessConfig) -> Duration {
+        Duration::from_secs(match self {
+            Self::Quick | Self::Uninstall => config.timeout_secs,
+            Self::Install => config.install_timeout_secs,
+        })
+    }
+
+    fn permits(self, limits: &Limits) -> &Semaphore {
+        match self {
+            Self::Quick | Self::Uninstall => &limits.permits,
+            Self::Install => &limits.install_permits,
+        }
+    }
+}
+
+/// Runs `program` once one of the permits of its `kind` is free and collects its output.
+///
+/// The process is killed when it outlives the timeout of its `kind`, and when the returned future
+/// is dropped, e.g. because the task awaiting it was aborted or the app quit.
+async fn run(program: &str, args: &[String], kind: Kind) -> Result<Output, Error> {
+    let limits = limits();
+    let _permit = kind
+        .permits(limits)
+        .acquire()
+        .await
+        .map_err(|e| Error::Unexpected(e.to_string()))?;
+    if matches!(kind, Kind::Install | Kind::Uninstall) {
+        started();
+    }
+
+    let mut command = Command::new(program);
+    command.args(args);
+    let description = format!("{program} {}", args.join(" "));
+
+    output(command, description, kind.timeout(&limits.config)).await
+}
+
+/// Runs `command`, recording it as `description`, and kills it when it outlives `timeout` or the
+/// returned future is dropped.
+async fn output(
+    mut command: Command,
+    description: String,
+    timeout: Duration,
+) -> Result<Output, Error> {
+    let mut unfinished = Unfinished(Some(description));
+    let output = command.kill_on_drop(true).output();
+    match tokio::time::timeout(timeout, output).await {
+        Ok(output) => {
+            let description = unfinished.0.take().unwrap_or_default();
+            let output = output?;
+            record(description, Some(&output));
+            Ok(output)
+        }
+        Err(_) => {
+            let description = unfinished.0.take().unwrap_or_default();
+            record(description.clone(), None);
+            Err(Error::Timeout(description, timeout.as_secs()))
+        }
+    }
+}
+
+/// Records a command as killed when the future running it is dropped before it finished, e.g.
+/// because its operation was cancelled.
+struct Unfinished(Option<String>);
+
+impl Drop for Unfinished {
+    fn drop(&mut self) {
+        if let Some(description) = self.0.take() {
+            record(description, None);
+        }
+    }
+}
+
+/// Runs `cargo` with `args` and fails unless it exits successfully.
+async fn cargo(args: &[String], kind: Kind) -> Result<String, Error> {
+    let output = run("cargo", args, kind).await?;
+
+    if !output.status.success() {
+        return Err(Error::CommandFailed(format!("cargo {}", args.join(" "))));
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// The error of a cargo command that exited unsuccessfully, with the last line it printed on
+/// stderr, or `otherwise` when it printed nothing.
+fn failure(args: &[String], output: &Output, otherwise: &str) -> Error {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let reason = stderr
+        .lines()
+        .rfind(|l| !l.trim().is_empty())
+        .unwrap_or(otherwise);
+
+    Error::CommandFailed(format!("cargo {}: {}", args.join(" "), reason.trim()))
+}
+
+fn to_args<const N: usize>(args: [&str; N]) -> Vec<String> {
+    args.map(String::from).to_vec()
+}
+
+/// Lists out all of the installed crates.
+pub(crate) async fn list_crates() -> Result<String, Error> {
+    cargo(&to_args(["install", "--list"]), Kind::Quick).await
 }
 
 /// Searches for a specific crate on crates.io.
 /// Gives the latest version and a short description.
-pub fn search_crate(c: &str) -> Result<String, Error> {
-    let stdout = Command::new("cargo")
-        .arg("info")
-        .arg(c)
-        .output()?
-        .stdout
-        .apply(String::from_utf8)?;
+///
+/// Fails with what cargo printed last when it could not describe the crate, e.g. because the
+/// registry could not be reached or there is no such crate.
+pub(crate) async fn search_crate(c: &str) -> Result<String, Error> {
+    let args = to_args(["info", c]);
+    let output = run("cargo", &args, Kind::Quick).await?;
+    if !output.status.success() {
+        return Err(failure(&args, &output, "no output"));
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Like [`search_crate`], but describes the given version, including whether it was yanked.
+pub(crate) async fn search_crate_version(c: &str, version: &str) -> Result<String, Error> {
+    search_crate(&format!("{c}@{version}")).await
+}
+
+/// Searches the registry for crates matching `query`.
+/// Uses the registry behind `index` when given.
+pub(crate) async fn search_registry(
+    query: &str,
+    index: Option<&str>,
+    limit: u32,
+) -> Result<String, Error> {
+    let mut args = to_args(["search", query, "--limit", &limit.to_string()]);
+    if let Some(index) = index {
+        args.extend(to_args(["--index", index]));
+    }
+
+    cargo(&args, Kind::Quick).await
+}
+
+/// The arguments passed to `cargo` to install a crate from source.
+pub(crate) fn install_args(c: &str) -> Vec<String> {
+    vec!["install".to_string(), c.to_string(), "--locked".to_string()]
+}
+
+/// The arguments passed to `cargo` to install a prebuilt binary of a crate.
+pub(crate) fn binstall_args(c: &str) -> Vec<String> {
+    to_args([
+        "binstall",
+        c,
+        "--no-confirm",
+        "--locked",
+        "--disable-strategies",
+        "compile",
+    ])
+}
+
+/// Gives the version of cargo, e.g. `cargo 1.86.0 (adf9b6ad1 2025-02-28)`.
+pub(crate) async fn cargo_version() -> Result<String, Error> {
+    let output = run("cargo", &to_args(["--version"]), Kind::Quick).await?;
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Gives the version of the active Rust toolchain, e.g. `rustc 1.86.0 (05f9846f8 2025-03-31)`.
+pub(crate) async fn rustc_version() -> Result<String, Error> {
+    let output = run("rustc", &to_args(["--version"]), Kind::Quick).await?;
 
-    Ok(stdout)
+    Ok(String::from_utf8(output.stdout)?)
 }
 
-pub async fn install_crate(c: &str) -> Result<(), Error> {
-    tokio::process::Command::new("cargo")
-        .arg("install")
-        .arg(c)
-        .arg("--locked")
-        .output()
-        .await?;
+pub(crate) async fn install_crate(c: &str) -> Result<(), Error> {
+    install_crate_with_args(install_args(c)).await
+}
+
+/// Runs `cargo` with `args`, which must be the arguments of a `cargo install` command.
+pub(crate) async fn install_crate_with_args(args: Vec<String>) -> Result<(), Error> {
+    cargo(&args, Kind::Install).await?;
 
     Ok(())
 }
 
-pub async fn uninstall_crate(c: &str) -> Result<(), Error> {
-    Command::new("cargo").arg("uninstall").arg(c).output()?;
+/// Checks whether `cargo binstall` is installed.
+pub(crate) async fn binstall_available() -> bool {
+    run("cargo", &to_args(["binstall", "-V"]), Kind::Quick)
+        .await
+        .is_ok_and(|o| o.status.success())
+}
+
+/// Installs a prebuilt binary of the crate with `cargo binstall`.
+/// Never falls back to compiling from source, so `false` means no prebuilt artifact was found.
+pub(crate) async fn binstall_crate(c: &str) -> Result<bool, Error> {
+    let output = run("cargo", &binstall_args(c), Kind::Install).await?;
+
+    Ok(output.status.success())
+}
+
+/// Checks whether `cargo audit` is installed.
+pub(crate) async fn cargo_audit_available() -> bool {
+    run("cargo", &to_args(["audit", "--version"]), Kind::Quick)
+        .await
+        .is_ok_and(|o| o.status.success())
+}
+
+/// Audits the dependencies embedded in a binary with `cargo audit bin` and gives its JSON report.
+/// `cargo audit` also exits with an error when it finds vulnerabilities, so it only failed when it
+/// printed no report.
+pub(crate) async fn audit_binary(path: &Path) -> Result<String, Error> {
+    let path = path.to_string_lossy();
+    let args = to_args(["audit", "--json", "bin", &path]);
+    let output = run("cargo", &args, Kind::Quick).await?;
+
+    if !output.status.success() && output.stdout.trim_ascii().is_empty() {
+        return Err(failure(&args, &output, "no report"));
+    }
+
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+pub(crate) async fn uninstall_crate(c: &str) -> Result<(), Error> {
+    cargo(&to_args(["uninstall", c]), Kind::Uninstall).await?;
+
+    Ok(())
+}
+
+/// Runs a hook from the config with the shell, with `env` set on top of depot's environment.
+/// `name` tells which hook failed, e.g. `pre-install`.
+///
+/// Hooks don't take one of the shared permits, since they don't run cargo and a hook waiting
+/// for one would hold up the operation it belongs to.
+pub(crate) async fn run_hook(
+    name: &str,
+    script: &str,
+    env: &[(&str, String)],
+    timeout: Duration,
+) -> Result<(), Error> {
+    started();
+    let (shell, flag) = if cfg!(windows) {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
+    let mut command = Command::new(shell);
+    command
+        .args([flag, script])
+        .envs(env.iter().map(|(k, v)| (*k, v)));
+
+    let output = output(command, script.to_string(), timeout).await?;
+    if !output.status.success() {
+        return Err(Error::CommandFailed(format!("{name} hook `{script}`")));
+    }
+
     Ok(())
 }
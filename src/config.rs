@@ -0,0 +1,303 @@
+//! The config file, `depot/config.toml` in the platform's config directory unless
+//! `DEPOT_CONFIG` is set. Every table and value is optional.
+
+use crate::errors::Error;
+use crate::installer::InstallStrategy;
+use crate::operation::OperationKind;
+use crate::paths;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// User configuration, read from the config file described in the [module docs](self).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct Config {
+    /// The `[install]` table.
+    pub install: InstallConfig,
+    /// The `[search]` table.
+    pub search: SearchConfig,
+    /// The `[usage]` table.
+    pub usage: UsageConfig,
+    /// The `[policy]` table.
+    pub policy: PolicyConfig,
+    /// The `[process]` table.
+    pub process: ProcessConfig,
+    /// The `[hooks]` table.
+    pub hooks: HooksConfig,
+}
+
+/// How crates are installed.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct InstallConfig {
+    /// The strategy used for every crate unless overridden in `crates`.
+    pub strategy: InstallStrategy,
+    /// Per-crate overrides.
+    pub crates: HashMap<String, InstallStrategy>,
+}
+
+/// Where and how the install view searches for crates.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct SearchConfig {
+    /// Index URL of the registry to search, passed to `cargo search --index`.
+    /// Uses cargo's default registry when unset.
+    pub index: Option<String>,
+    /// Maximum number of results.
+    pub limit: u32,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            index: None,
+            limit: 20,
+        }
+    }
+}
+
+/// How depot tells when a crate was last used.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct UsageConfig {
+    /// Whether bash, zsh and fish history is read to tell when binaries were last run.
+    pub shell_history: bool,
+    /// Crates that have not been used for this many days are considered forgotten.
+    pub forgotten_after_days: u64,
+}
+
+impl Default for UsageConfig {
+    fn default() -> Self {
+        Self {
+            shell_history: false,
+            forgotten_after_days: 90,
+        }
+    }
+}
+
+/// The policy checked by `depot policy check` and the start view.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct PolicyConfig {
+    /// The team's policy file, e.g. one in a checked-out repository. `~` is the home directory.
+    pub path: Option<PathBuf>,
+}
+
+/// Limits on the cargo processes depot starts.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct ProcessConfig {
+    /// Seconds before a command other than an install is killed.
+    pub timeout_secs: u64,
+    /// Seconds before `cargo install` or `cargo binstall` is killed.
+    pub install_timeout_secs: u64,
+    /// How many processes other than installs may run at once, across everything depot is
+    /// doing.
+    pub max_concurrent: usize,
+    /// How many installs may run at once. Installs don't take from `max_concurrent`, so listing
+    /// and fetching info go on while crates build.
+    pub max_concurrent_installs: usize,
+}
+
+impl Default for ProcessConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: 120,
+            install_timeout_secs: 60 * 60,
+            max_concurrent: 4,
+            max_concurrent_installs: 2,
+        }
+    }
+}
+
+/// Shell commands run before and after every install, update and uninstall, see
+/// [`crate::hooks`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct HooksConfig {
+    /// Run before a crate is installed.
+    pub pre_install: Vec<String>,
+    /// Run after a crate was installed.
+    pub post_install: Vec<String>,
+    /// Run before a crate is updated.
+    pub pre_update: Vec<String>,
+    /// Run after a crate was updated.
+    pub post_update: Vec<String>,
+    /// Run before a crate is uninstalled.
+    pub pre_uninstall: Vec<String>,
+    /// Run after a crate was uninstalled.
+    pub post_uninstall: Vec<String>,
+    /// Seconds before a hook is killed.
+    pub timeout_secs: u64,
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            pre_install: vec![],
+            post_install: vec![],
+            pre_update: vec![],
+            post_update: vec![],
+            pre_uninstall: vec![],
+            post_uninstall: vec![],
+            timeout_secs: 60,
+        }
+    }
+}
+
+impl HooksConfig {
+    /// The commands run before an operation of `kind`.
+    pub fn pre(&self, kind: OperationKind) -> &[String] {
+        match kind {
+            OperationKind::Install => &self.pre_install,
+            OperationKind::Update => &self.pre_update,
+            OperationKind::Uninstall => &self.pre_uninstall,
+        }
+    }
+
+    /// The commands run after an operation of `kind` succeeded.
+    pub fn post(&self, kind: OperationKind) -> &[String] {
+        match kind {
+            OperationKind::Install => &self.post_install,
+            OperationKind::Update => &self.post_update,
+            OperationKind::Uninstall => &self.post_uninstall,
+        }
+    }
+
+    /// Whether no hook is set at all.
+    pub fn is_empty(&self) -> bool {
+        [
+            OperationKind::Install,
+            OperationKind::Update,
+            OperationKind::Uninstall,
+        ]
+        .into_iter()
+        .all(|kind| self.pre(kind).is_empty() && self.post(kind).is_empty())
+    }
+}
+
+impl InstallConfig {
+    /// The strategy for `krate`, its override if it has one.
+    pub fn strategy_for(&self, krate: &str) -> InstallStrategy {
+        self.crates.get(krate).copied().unwrap_or(self.strategy)
+    }
+}
+
+impl Config {
+    /// Loads the config file, falling back to the defaults when there is none.
+    pub fn load() -> Result<Self, Error> {
+        let Some(path) = paths::config_file() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    fn parse(s: &str) -> Result<Self, Error> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Config;
+    use crate::installer::InstallStrategy;
+    use crate::operation::OperationKind;
+
+    #[test]
+    fn parse_empty_config() {
+        assert_eq!(Config::parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn parse_install_strategy() {
+        let s = r#"
+[install]
+strategy = "prefer-prebuilt"
+
+[install.crates]
+ripgrep = "source-only"
+"#;
+        let config = Config::parse(s).unwrap();
+
+        assert_eq!(
+            config.install.strategy_for("bat"),
+            InstallStrategy::PreferPrebuilt
+        );
+        assert_eq!(
+            config.install.strategy_for("ripgrep"),
+            InstallStrategy::SourceOnly
+        );
+    }
+
+    #[test]
+    fn parse_search_config() {
+        let config = Config::parse("[search]\nindex = \"sparse+http://localhost:8000/\"").unwrap();
+
+        assert_eq!(
+            config.search.index.as_deref(),
+            Some("sparse+http://localhost:8000/")
+        );
+        assert_eq!(config.search.limit, 20);
+    }
+
+    #[test]
+    fn parse_usage_config() {
+        let config = Config::parse("[usage]\nshell_history = true").unwrap();
+
+        assert!(config.usage.shell_history);
+        assert_eq!(config.usage.forgotten_after_days, 90);
+    }
+
+    #[test]
+    fn parse_policy_config() {
+        let config = Config::parse("[policy]\npath = \"~/work/tools/policy.toml\"").unwrap();
+
+        assert_eq!(
+            config.policy.path.as_deref(),
+            Some(std::path::Path::new("~/work/tools/policy.toml"))
+        );
+    }
+
+    #[test]
+    fn parse_process_config() {
+        let config = Config::parse("[process]\nmax_concurrent = 1").unwrap();
+
+        assert_eq!(config.process.max_concurrent, 1);
+        assert_eq!(config.process.max_concurrent_installs, 2);
+        assert_eq!(config.process.timeout_secs, 120);
+    }
+
+    #[test]
+    fn parse_hooks_config() {
+        let s = r#"
+[hooks]
+post_install = ["mkdir -p ~/.zfunc"]
+post_update = ["mkdir -p ~/.zfunc", "notify-send \"$DEPOT_CRATE\""]
+"#;
+        let config = Config::parse(s).unwrap();
+
+        assert_eq!(config.hooks.post(OperationKind::Update).len(), 2);
+        assert!(config.hooks.pre(OperationKind::Install).is_empty());
+        assert!(!config.hooks.is_empty());
+        assert_eq!(config.hooks.timeout_secs, 60);
+        assert!(Config::default().hooks.is_empty());
+    }
+
+    #[test]
+    fn parse_invalid_strategy() {
+        assert!(Config::parse("[install]\nstrategy = \"fastest\"").is_err());
+    }
+}
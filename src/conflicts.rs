@@ -0,0 +1,211 @@
+//! Binaries that are shadowed by another executable on the `PATH`, or that more than one crate
+//! installs.
+
+use crate::depot::Krates;
+use crate::paths;
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A problem with how an installed binary is resolved from `$PATH`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BinaryConflict {
+    /// Another executable with the same name comes first in `$PATH`.
+    Shadowed {
+        /// The crate that installed the binary.
+        krate: String,
+        /// The binary's file name.
+        binary: String,
+        /// The executable that is run instead.
+        by: PathBuf,
+    },
+    /// More than one installed crate claims the binary.
+    Duplicate {
+        /// The binary's file name.
+        binary: String,
+        /// The crates that claim it.
+        krates: Vec<String>,
+    },
+}
+
+impl BinaryConflict {
+    /// Whether the conflict concerns a binary of `krate`.
+    pub fn involves(&self, krate: &str) -> bool {
+        match self {
+            Self::Shadowed { krate: k, .. } => k == krate,
+            Self::Duplicate { krates, .. } => krates.iter().any(|k| k == krate),
+        }
+    }
+}
+
+impl Display for BinaryConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Shadowed { binary, by, .. } => {
+                write!(f, "`{binary}` is shadowed by {}", by.display())
+            }
+            Self::Duplicate { binary, krates } => {
+                write!(f, "`{binary}` is claimed by {}", krates.join(", "))
+            }
+        }
+    }
+}
+
+/// The conflicts of every installed binary.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Conflicts(pub Vec<BinaryConflict>);
+
+impl Conflicts {
+    /// Checks the binaries of the installed crates against `$PATH`.
+    pub fn get(krates: &Krates) -> Self {
+        let Some(bin_dir) = paths::cargo_bin_dir() else {
+            return Self::default();
+        };
+        let path: Vec<PathBuf> = env::var_os("PATH")
+            .map(|p| env::split_paths(&p).collect())
+            .unwrap_or_default();
+
+        Self::find(krates, |binary| {
+            let first = first_in_path(binary, &path)?;
+            (!is_same_file(&first, &bin_dir.join(binary))).then_some(first)
+        })
+    }
+
+    /// Finds conflicts, with `shadowed_by` returning the executable that `$PATH` resolves to
+    /// instead of the installed binary, if any.
+    fn find(krates: &Krates, shadowed_by: impl Fn(&str) -> Option<PathBuf>) -> Self {
+        let mut conflicts = vec![];
+        let mut owners: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+        for krate in &krates.0 {
+            for binary in &krate.binaries {
+                owners.entry(binary).or_default().push(krate.name.clone());
+                if let Some(by) = shadowed_by(binary) {
+                    conflicts.push(BinaryConflict::Shadowed {
+                        krate: krate.name.clone(),
+                        binary: binary.clone(),
+                        by,
+                    });
+                }
+            }
+        }
+
+        for (binary, krates) in owners.into_iter().filter(|(_, k)| k.len() > 1) {
+            conflicts.push(BinaryConflict::Duplicate {
+                binary: binary.to_string(),
+                krates,
+            });
+        }
+
+        Self(conflicts)
+    }
+
+    /// The conflicts that involve `krate`.
+    pub fn for_krate(&self, krate: &str) -> Vec<&BinaryConflict> {
+        self.0.iter().filter(|c| c.involves(krate)).collect()
+    }
+}
+
+/// Resolves `name` the way a shell would, returning the first executable found in `path`.
+fn first_in_path(name: &str, path: &[PathBuf]) -> Option<PathBuf> {
+    path.iter()
+        .map(|dir| dir.join(name))
+        .find(|p| is_executable(p))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::metadata(path).is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Compares paths after resolving symlinks, so that a symlinked cargo home is not reported as
+/// shadowing itself.
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BinaryConflict, Conflicts, first_in_path};
+    use crate::depot::{Krate, Krates};
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn krate(name: &str, binaries: &[&str]) -> Krate {
+        let mut k = Krate::default();
+        k.name = name.to_string();
+        k.binaries = binaries.iter().map(|b| b.to_string()).collect();
+        k
+    }
+
+    #[test]
+    fn find_shadowed_and_duplicate_binaries() {
+        let krates = Krates(vec![
+            krate("bat", &["bat"]),
+            krate("ripgrep", &["rg"]),
+            krate("ripgrep-fork", &["rg"]),
+        ]);
+        let conflicts = Conflicts::find(&krates, |binary| {
+            (binary == "bat").then(|| PathBuf::from("/usr/bin/bat"))
+        });
+
+        assert_eq!(
+            conflicts.0,
+            vec![
+                BinaryConflict::Shadowed {
+                    krate: "bat".to_string(),
+                    binary: "bat".to_string(),
+                    by: PathBuf::from("/usr/bin/bat"),
+                },
+                BinaryConflict::Duplicate {
+                    binary: "rg".to_string(),
+                    krates: vec!["ripgrep".to_string(), "ripgrep-fork".to_string()],
+                },
+            ]
+        );
+        assert_eq!(conflicts.for_krate("ripgrep-fork").len(), 1);
+        assert!(conflicts.for_krate("fd-find").is_empty());
+        assert_eq!(
+            conflicts.0[0].to_string(),
+            "`bat` is shadowed by /usr/bin/bat"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn resolve_first_executable_in_path() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = std::env::temp_dir().join(format!("depot-path-{}", std::process::id()));
+        let (system, cargo) = (root.join("usr-bin"), root.join("cargo-bin"));
+        for dir in [&system, &cargo] {
+            fs::create_dir_all(dir).unwrap();
+            let bin = dir.join("rg");
+            fs::write(&bin, "").unwrap();
+            fs::set_permissions(&bin, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        // Not executable, so it is skipped.
+        fs::write(system.join("bat"), "").unwrap();
+
+        let path = vec![system.clone(), cargo.clone()];
+        let first = first_in_path("rg", &path);
+        let missing = first_in_path("bat", &path);
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(first, Some(system.join("rg")));
+        assert_eq!(missing, None);
+    }
+}
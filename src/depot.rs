@@ -1,120 +1,154 @@
-use crate::commands::{install_crate, list_crates, search_crate, uninstall_crate};
-use crate::errors::{ChannelError, Error};
-use crate::parser::{alphanumeric1_with_hyphen, ws, ws2};
-use nom::bytes::complete::{tag, take_until};
+//! The installed crates and what is known about them.
+
+use crate::backend::{CargoBackend, InstallRequest};
+use crate::config::{Config, UsageConfig};
+use crate::conflicts::Conflicts;
+use crate::depotfile::ToolSpec;
+use crate::errors::Error;
+use crate::installer::InstallMethod;
+use crate::operation::Outcome;
+use crate::parser::{alphanumeric1_with_hyphen, package_name, target_name, ws, ws2};
+use crate::paths;
+use crate::records::{InstallRecord, InstallRecords};
+use crate::tracking::{InstallTracking, TrackedInstall};
+use crate::usage::{Usage, format_age};
+use futures::future::join_all;
+use nom::bytes::complete::{take_until, take_while1};
 use nom::character::complete::char;
-use nom::character::complete::multispace0;
 use nom::character::complete::{multispace1, newline, space1};
 use nom::combinator::{map, opt};
 use nom::multi::separated_list0;
-use nom::sequence::preceded;
+use nom::sequence::delimited;
 use nom::{IResult, Parser, multi::separated_list1};
-use ratatui::widgets::ListState;
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 use std::fmt::Display;
-use throbber_widgets_tui::ThrobberState;
+use std::fs;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use versions::SemVer;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum DepotMessage {
-    FetchKrateInfo(Vec<KrateMetadata>),
-    UpdateKrate { krate: String },
-    UninstallKrate,
-    DepotError(ChannelError),
+/// The installed crates, see [`Depot::get`].
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Depot {
+    /// Every installed crate.
+    pub store: Krates,
+    /// Binaries that are shadowed in `$PATH` or claimed by several crates.
+    pub conflicts: Conflicts,
 }
 
-impl DepotMessage {
-    pub fn handle(self, state: &mut DepotState) -> Result<(), Error> {
-        match self {
-            DepotMessage::FetchKrateInfo(r) => state.sync(r)?,
-            DepotMessage::UpdateKrate { krate } => state.update_krate(&krate)?,
-            DepotMessage::UninstallKrate => {}
-            DepotMessage::DepotError(e) => return Err(Error::HandleKrate(e)),
-        }
-
-        Ok(())
+impl Depot {
+    /// How many crates are installed.
+    pub fn crate_count(&self) -> i64 {
+        self.store.0.len() as i64
     }
-}
-
-#[derive(Debug)]
-pub struct DepotState {
-    pub depot: Depot,
-    pub list_state: ListState,
-    pub update_list_state: ListState,
-    pub throbber_state: ThrobberState,
-    update_queue: HashSet<String>,
-}
 
-impl Default for DepotState {
-    fn default() -> Self {
-        let depot = Depot::get().expect("failed to initialize `DepotState`");
-        let list_state = ListState::default();
-        let update_list_state = ListState::default();
-        let throbber_state = throbber_widgets_tui::ThrobberState::default();
-        let update_queue: HashSet<String> = HashSet::new();
-
-        Self {
-            depot,
-            list_state,
-            update_list_state,
-            throbber_state,
-            update_queue,
-        }
-    }
-}
+    /// Obtain the list of installed crates.
+    pub async fn get(backend: &dyn CargoBackend) -> Result<Self, Error> {
+        let records = InstallRecords::load()?;
+        let config = Config::load()?;
 
-impl DepotState {
-    pub fn is_all_synced(&self) -> bool {
-        self.depot.store.0.iter().all(|k| k.is_metadata_synced())
+        Self::get_with(backend, &records, &config.usage).await
     }
 
-    pub fn update_krate(&mut self, name: &str) -> Result<(), Error> {
-        if let Some(k) = self.depot.store.0.iter_mut().find(|k| k.name == name) {
-            k.update_version()?;
-            self.update_queue.remove(name);
+    /// Obtain the list of installed crates, with the given records and usage settings instead of
+    /// the ones on disk.
+    pub async fn get_with(
+        backend: &dyn CargoBackend,
+        records: &InstallRecords,
+        usage: &UsageConfig,
+    ) -> Result<Self, Error> {
+        let mut store = backend.list().await?;
+        let tracking = backend.tracking().await?;
+        let usage = Usage::get(&store, usage);
+        for krate in &mut store.0 {
+            krate.last_used = usage.last_used(&krate.binaries);
+            krate.install_method = records.get(&krate.name).map(|r| r.method);
+            krate.read_dates(records.get(&krate.name));
+            krate.tracked = tracking.get(&krate.name).cloned();
+            krate.measure();
         }
+        let conflicts = Conflicts::get(&store);
 
-        Ok(())
+        Ok(Self { store, conflicts })
     }
 
-    pub fn get_update_items(&self) -> HashSet<String> {
-        self.update_queue.clone()
+    /// Fetches the latest information about every installed crate with `cargo info`.
+    /// The crates are looked up concurrently, as far as the process limit allows. A crate that
+    /// can't be looked up doesn't fail the others, see [`fetch_metadata`].
+    pub async fn fetch_info(&mut self, backend: &dyn CargoBackend) -> Result<(), Error> {
+        let info = join_all(
+            self.store
+                .0
+                .iter()
+                .map(|k| fetch_metadata(backend, &k.name, None)),
+        )
+        .await;
+
+        self.sync(info)
     }
 
-    pub fn append_to_update_queue(&mut self, krate: &str) {
-        self.update_queue.insert(krate.to_string());
+    /// Checks the binaries again after crates were added or removed.
+    pub fn refresh_conflicts(&mut self) {
+        self.conflicts = Conflicts::get(&self.store);
     }
 
-    fn sync(&mut self, info: Vec<KrateMetadata>) -> Result<(), Error> {
-        for krate in &mut self.depot.store.0 {
-            if let Some(ki) = info.iter().find(|&i| krate.name == i.name) {
-                krate.metadata = ki.clone();
-            } else {
-                return Err(Error::Unexpected("unmatched name".to_string()));
+    /// Brings `name` in line with cargo's tracking data after it was installed, updated or
+    /// uninstalled, keeping the metadata that was already fetched for it.
+    ///
+    /// A crate missing from the tracking data is only dropped when it was `uninstalled` or the
+    /// tracking data was found, since cargo may have written it somewhere depot does not look.
+    pub fn reconcile(
+        &mut self,
+        name: &str,
+        uninstalled: bool,
+        tracking: &InstallTracking,
+        records: &InstallRecords,
+    ) {
+        let store = &mut self.store.0;
+        let ix = store.iter().position(|k| k.name == name);
+        match (tracking.get(name), tracking.version(name)) {
+            (Some(tracked), Some(version)) => {
+                let ix = ix.unwrap_or_else(|| {
+                    let ix = store.partition_point(|k| k.name.as_str() < name);
+                    let krate = Krate {
+                        name: name.to_string(),
+                        ..Default::default()
+                    };
+                    store.insert(ix, krate);
+                    ix
+                });
+                let krate = &mut store[ix];
+                krate.version = version;
+                krate.binaries = tracked.bins.iter().cloned().collect();
+                krate.tracked = Some(tracked.clone());
+                krate.install_method = records.get(name).map(|r| r.method);
+                krate.read_dates(records.get(name));
+                krate.measure();
+            }
+            _ if uninstalled || tracking.found() => {
+                if let Some(ix) = ix {
+                    store.remove(ix);
+                }
             }
+            _ => {}
         }
-        Ok(())
-    }
-}
 
-#[derive(Debug, Default)]
-pub struct Depot {
-    pub store: Krates,
-}
-
-impl Depot {
-    pub fn crate_count(&self) -> i64 {
-        self.store.0.len() as i64
+        self.refresh_conflicts();
     }
 
-    /// Obtain the list of installed crates.
-    pub fn get() -> Result<Self, Error> {
-        let output = list_crates()?;
-        let store = Krates::parse(&output)?.1;
+    /// Attaches fetched metadata to the matching crates.
+    pub fn sync(&mut self, info: Vec<KrateMetadata>) -> Result<(), Error> {
+        for ki in info {
+            match self.store.0.iter_mut().find(|k| k.name == ki.name) {
+                Some(krate) => krate.metadata = ki,
+                None => return Err(Error::Unexpected("unmatched name".to_string())),
+            }
+        }
 
-        Ok(Self { store })
+        Ok(())
     }
 
+    /// The crates whose latest version is not the installed one.
     pub fn get_outdated_krates(&self) -> Result<Krates, Error> {
         let k = self
             .store
@@ -127,16 +161,45 @@ impl Depot {
         Ok(Krates(k))
     }
 
+    /// Combined size of every installed binary in bytes.
+    pub fn disk_usage(&self) -> u64 {
+        self.store.0.iter().map(|k| k.size).sum()
+    }
+
+    /// Crates whose metadata could not be read.
+    pub fn unparseable_krate_count(&self) -> usize {
+        self.store
+            .0
+            .iter()
+            .filter(|k| k.parse_error().is_some())
+            .count()
+    }
+
+    /// Crates that `cargo info` failed on.
+    pub fn unfetched_krate_count(&self) -> usize {
+        self.store
+            .0
+            .iter()
+            .filter(|k| k.fetch_error().is_some())
+            .count()
+    }
+
     /// Shorthand for getting the number of outdated krates.
     pub fn outdated_krate_count(&self) -> Result<usize, Error> {
         Ok(self.get_outdated_krates()?.0.len())
     }
 }
 
-#[derive(Debug, Default, PartialEq, Eq)]
+/// A list of crates.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
 pub struct Krates(pub Vec<Krate>);
 
 impl Krates {
+    /// Reads the output of `cargo install --list`.
+    pub fn from_cargo_list(s: &str) -> Result<Self, Error> {
+        Ok(Self::parse(s).map_err(Error::parser)?.1)
+    }
+
     fn parse(s: &str) -> IResult<&str, Krates> {
         let (s, krates) = separated_list0(newline, Krate::parse).parse(s)?;
         let k = Krates(krates);
@@ -145,15 +208,48 @@ impl Krates {
     }
 }
 
+/// An installed crate, and what is known about it.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct Krate {
+    /// The crate's name.
     pub name: String,
+    /// The installed version, or the latest one for a crate that is not installed.
     pub version: SemVer,
+    /// The file names of the installed binaries. Empty when the crate is not installed.
     pub binaries: Vec<String>,
+    /// Only known for crates that were installed by depot.
+    pub install_method: Option<InstallMethod>,
+    /// How cargo installed the crate, if it is tracked in `.crates2.json`.
+    pub tracked: Option<TrackedInstall>,
+    /// Combined size of the installed binaries in bytes.
+    pub size: u64,
+    /// Estimate of when one of the binaries was last run, from the `[usage]` config.
+    pub last_used: Option<SystemTime>,
+    /// When the crate was first installed, see [`Krate::read_dates`].
+    pub installed_at: Option<SystemTime>,
+    /// When the crate was last updated. `None` if it has not been updated since it was installed.
+    pub updated_at: Option<SystemTime>,
     metadata: KrateMetadata,
 }
 
 impl Krate {
+    /// A crate that is not installed, described by its metadata.
+    pub fn from_metadata(metadata: KrateMetadata) -> Self {
+        Self {
+            name: metadata.name.clone(),
+            version: metadata.info.latest_version.clone().unwrap_or_default(),
+            metadata,
+            ..Default::default()
+        }
+    }
+
+    /// Attaches metadata fetched with [`CargoBackend::info`].
+    pub fn set_metadata(&mut self, metadata: KrateMetadata) {
+        self.metadata = metadata;
+    }
+
+    /// The description, or `not available`.
     pub fn description(&self) -> String {
         if let Some(description) = &self.metadata.info.description {
             description.clone()
@@ -162,6 +258,7 @@ impl Krate {
         }
     }
 
+    /// The keywords as hashtags, e.g. `#cli #search`. Empty when there are none.
     pub fn tags_str(&self) -> String {
         if let Some(tags) = &self.metadata.info.tags
             && !tags.0.is_empty()
@@ -172,6 +269,7 @@ impl Krate {
         }
     }
 
+    /// The latest version, or the installed one when it is not known.
     pub fn latest_version(&self) -> SemVer {
         if let Some(latest_version) = &self.metadata.info.latest_version {
             latest_version.clone()
@@ -180,6 +278,7 @@ impl Krate {
         }
     }
 
+    /// The license, or `not found`.
     pub fn license(&self) -> String {
         if let Some(license) = &self.metadata.info.license {
             license.clone()
@@ -188,6 +287,7 @@ impl Krate {
         }
     }
 
+    /// The minimum supported Rust version, or `unknown`.
     pub fn rust_version_str(&self) -> String {
         if let Some(rv) = &self.metadata.info.rust_version {
             rv.to_string()
@@ -196,6 +296,7 @@ impl Krate {
         }
     }
 
+    /// The documentation URL. Empty when there is none.
     pub fn documentation(&self) -> String {
         if let Some(documentation) = &self.metadata.info.documentation {
             documentation.clone()
@@ -204,6 +305,7 @@ impl Krate {
         }
     }
 
+    /// The homepage URL. Empty when there is none.
     pub fn homepage(&self) -> String {
         if let Some(hp) = &self.metadata.info.homepage {
             hp.clone()
@@ -212,6 +314,7 @@ impl Krate {
         }
     }
 
+    /// The repository URL. Empty when there is none.
     pub fn repository(&self) -> String {
         if let Some(repository) = &self.metadata.info.repository {
             repository.clone()
@@ -220,40 +323,148 @@ impl Krate {
         }
     }
 
-    pub fn is_latest(&self) -> bool {
-        if let Some(latest_version) = &self.metadata.info.latest_version {
-            latest_version == &self.version
+    /// What `cargo info` said about the crate, with the fields that are not known left empty.
+    pub fn info(&self) -> &KrateInfo {
+        &self.metadata.info
+    }
+
+    /// The minimum supported Rust version, if it is known.
+    pub fn rust_version(&self) -> Option<&SemVer> {
+        self.metadata.info.rust_version.as_ref()
+    }
+
+    /// The kind of version bump from the installed version to the latest one.
+    pub fn semver_class(&self) -> SemverClass {
+        SemverClass::between(&self.version, &self.latest_version())
+    }
+
+    /// Why the output of `cargo info` could not be read, if it could not.
+    pub fn parse_error(&self) -> Option<&str> {
+        self.metadata.info.parse_error.as_deref()
+    }
+
+    /// Why `cargo info` failed on the crate, if it did.
+    pub fn fetch_error(&self) -> Option<&str> {
+        self.metadata.info.fetch_error.as_deref()
+    }
+
+    /// The features the crate declares.
+    pub fn features(&self) -> &Features {
+        &self.metadata.info.features
+    }
+
+    /// The features enabled in the current install, when cargo tracks how it was installed.
+    pub fn enabled_features(&self) -> Option<BTreeSet<String>> {
+        let tracked = self.tracked.as_ref()?;
+        let enabled = self.features().resolve(
+            tracked.features.iter().cloned(),
+            tracked.no_default_features,
+            tracked.all_features,
+        );
+
+        Some(enabled)
+    }
+
+    /// How the crate was installed, or `unknown`.
+    pub fn install_method_str(&self) -> String {
+        if let Some(method) = &self.install_method {
+            method.to_string()
         } else {
-            true
+            "unknown".to_string()
         }
     }
 
-    pub fn is_metadata_synced(&self) -> bool {
-        self.metadata.info.synced
+    /// The size of the binaries, e.g. `4.2 MiB`.
+    pub fn size_str(&self) -> String {
+        format_size(self.size)
+    }
+
+    /// Adds up the size of the crate's binaries in cargo's bin directory.
+    /// Binaries that are missing count as empty.
+    pub fn measure(&mut self) {
+        let Some(bin_dir) = paths::cargo_bin_dir() else {
+            return;
+        };
+        self.size = self
+            .binaries
+            .iter()
+            .filter_map(|b| fs::metadata(bin_dir.join(b)).ok())
+            .map(|m| m.len())
+            .sum();
     }
 
-    pub fn update_version(&mut self) -> Result<(), Error> {
-        let name = &self.name.clone();
-        let s = list_crates()?;
-        let v = parse_ver(&s, name)?.1;
-        self.version = v;
+    /// Takes the install and update dates from depot's records. Crates depot has no record of
+    /// fall back to the modification time of their binaries, which is when cargo last wrote them.
+    pub fn read_dates(&mut self, record: Option<&InstallRecord>) {
+        if let Some(installed_at) = record.and_then(|r| r.installed_at) {
+            self.installed_at = Some(UNIX_EPOCH + Duration::from_secs(installed_at));
+            self.updated_at = record
+                .and_then(|r| r.updated_at)
+                .map(|t| UNIX_EPOCH + Duration::from_secs(t));
+            return;
+        }
 
-        Ok(())
+        let modified = modified_times(self.binaries.iter());
+        self.installed_at = modified.iter().min().copied();
+        self.updated_at = record
+            .and_then(|r| r.updated_at)
+            .map(|t| UNIX_EPOCH + Duration::from_secs(t))
+            .or_else(|| modified.iter().max().copied());
     }
 
-    pub async fn update(&self) -> Result<(), Error> {
-        install_crate(&self.name).await?;
-        Ok(())
+    /// When the installed version was put in place.
+    pub fn last_changed(&self) -> Option<SystemTime> {
+        self.updated_at.or(self.installed_at)
     }
 
-    pub async fn uninstall(&self) -> Result<(), Error> {
-        uninstall_crate(&self.name).await
+    /// The install date, or `unknown`.
+    pub fn installed_at_str(&self) -> String {
+        match self.installed_at {
+            Some(t) => format_date(t),
+            None => "unknown".to_string(),
+        }
+    }
+
+    /// The update date and how long ago it was, or `never`.
+    pub fn updated_at_str(&self) -> String {
+        match self.updated_at {
+            Some(t) => format!("{} ({})", format_date(t), format_age(t, SystemTime::now())),
+            None => "never".to_string(),
+        }
+    }
+
+    /// How long ago the crate was last used, or `unknown`.
+    pub fn last_used_str(&self) -> String {
+        match self.last_used {
+            Some(t) => format_age(t, SystemTime::now()),
+            None => "unknown".to_string(),
+        }
+    }
+
+    /// Whether the crate is known to not have been used within `window` of `now`.
+    pub fn is_forgotten(&self, window: Duration, now: SystemTime) -> bool {
+        self.last_used
+            .is_some_and(|t| now.duration_since(t).unwrap_or_default() > window)
+    }
+
+    /// Whether the installed version is the latest one. True when the latest one is not known.
+    pub fn is_latest(&self) -> bool {
+        if let Some(latest_version) = &self.metadata.info.latest_version {
+            latest_version == &self.version
+        } else {
+            true
+        }
+    }
+
+    /// Whether `cargo info` has been run for the crate.
+    pub fn is_metadata_synced(&self) -> bool {
+        self.metadata.info.synced
     }
 
     /// Retrieves information about the crate.
     /// Does not contain latest information from crates.io.
     fn parse(s: &str) -> IResult<&str, Krate> {
-        let (s, name) = map(alphanumeric1_with_hyphen, String::from).parse(s)?;
+        let (s, name) = map(package_name, String::from).parse(s)?;
         let (s, _) = multispace1(s)?;
         let (s, _) = char('v')(s)?;
         let (s, version) = SemVer::parse(s)?;
@@ -272,17 +483,226 @@ impl Krate {
     }
 }
 
-fn parse_ver<'a>(s: &'a str, n: &'a str) -> IResult<&'a str, SemVer> {
-    let (s, _) = take_until(n)(s)?;
-    let (s, _) = multispace0(s)?;
-    let (s, _) = tag(n)(s)?;
-    let (s, _) = multispace1(s)?;
-    let (s, _) = char('v')(s)?;
-    let (s, v) = SemVer::parse(s)?;
+/// Looks up a crate with `cargo info`, describing `version` when given. A crate that can't be
+/// looked up, e.g. one that was installed from git or while offline, gets the error in its
+/// metadata instead, see [`Krate::fetch_error`].
+pub async fn fetch_metadata(
+    backend: &dyn CargoBackend,
+    name: &str,
+    version: Option<&SemVer>,
+) -> KrateMetadata {
+    backend
+        .info(name, version)
+        .await
+        .unwrap_or_else(|e| KrateMetadata::unavailable(name, e.to_string()))
+}
+
+/// Installs the latest version of a crate with the configured strategy and records how it went.
+pub async fn install_krate(backend: &dyn CargoBackend, name: &str) -> Outcome<InstallMethod> {
+    let strategy = match Config::load() {
+        Ok(config) => config.install.strategy_for(name),
+        Err(e) => return Err(e).into(),
+    };
+
+    install(backend, name, &InstallRequest::Latest(strategy)).await
+}
 
-    Ok((s, v))
+/// Installs `name` the way `spec` describes and records how it went.
+pub async fn install_spec(
+    backend: &dyn CargoBackend,
+    name: &str,
+    spec: &ToolSpec,
+) -> Outcome<InstallMethod> {
+    install(backend, name, &InstallRequest::Spec(spec.clone())).await
 }
 
+/// Installs `name` and records how it went. Only cargo can fail the install: when what was
+/// installed before can't be read or the record can't be written, the install keeps its result,
+/// with a warning.
+async fn install(
+    backend: &dyn CargoBackend,
+    name: &str,
+    request: &InstallRequest,
+) -> Outcome<InstallMethod> {
+    let mut warnings = vec![];
+    let previous = previous_install(backend, name).await.unwrap_or_else(|e| {
+        warnings.push(format!("could not read the previous install: {e}"));
+        None
+    });
+    let started = Instant::now();
+    let result = backend.install(name, request).await;
+    if let Ok(method) = &result
+        && let Err(e) = record_install(name, *method, previous, started)
+    {
+        warnings.push(format!("could not record the install: {e}"));
+    }
+
+    Outcome { result, warnings }
+}
+
+/// A crate as it was before an install replaced it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct PreviousInstall {
+    /// When its binaries were first written, in seconds since the Unix epoch. Read before cargo
+    /// writes them again, for crates that were installed before depot managed them.
+    written_at: Option<u64>,
+}
+
+/// Whether `name` is installed, and when its binaries were written.
+async fn previous_install(
+    backend: &dyn CargoBackend,
+    name: &str,
+) -> Result<Option<PreviousInstall>, Error> {
+    let tracking = backend.tracking().await?;
+    let Some(tracked) = tracking.get(name) else {
+        return Ok(None);
+    };
+    let written_at = modified_times(tracked.bins.iter())
+        .into_iter()
+        .min()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+
+    Ok(Some(PreviousInstall { written_at }))
+}
+
+/// Records how and when `name` was installed. Updates keep the original install date.
+fn record_install(
+    name: &str,
+    method: InstallMethod,
+    previous: Option<PreviousInstall>,
+    started: Instant,
+) -> Result<(), Error> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let record = install_record(
+        InstallRecords::load()?.get(name),
+        previous,
+        method,
+        started.elapsed().as_secs(),
+        now,
+    );
+
+    InstallRecords::record(name, record)
+}
+
+/// The record of an install that finished at `now`. A crate depot has no install date for keeps
+/// the time its binaries were first written, rather than taking the time of this update.
+fn install_record(
+    record: Option<&InstallRecord>,
+    previous: Option<PreviousInstall>,
+    method: InstallMethod,
+    build_secs: u64,
+    now: u64,
+) -> InstallRecord {
+    InstallRecord {
+        method,
+        build_secs: Some(build_secs),
+        installed_at: match previous {
+            Some(p) => record.and_then(|r| r.installed_at).or(p.written_at),
+            None => Some(now),
+        },
+        updated_at: previous.is_some().then_some(now),
+    }
+}
+
+/// The modification times of the `binaries` in cargo's bin directory that exist.
+fn modified_times<'a>(binaries: impl Iterator<Item = &'a String>) -> Vec<SystemTime> {
+    let Some(bin_dir) = paths::cargo_bin_dir() else {
+        return vec![];
+    };
+
+    binaries
+        .filter_map(|b| {
+            fs::metadata(bin_dir.join(b))
+                .and_then(|m| m.modified())
+                .ok()
+        })
+        .collect()
+}
+
+/// Formats a byte count with binary units, e.g. `4.2 MiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{size:.1} {}", UNITS[unit])
+}
+
+/// Formats the UTC date of `time`, e.g. `2025-03-31`.
+pub fn format_date(time: SystemTime) -> String {
+    let days = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+        / (24 * 60 * 60);
+    // Converts days since the epoch to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// The kind of change between two versions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SemverClass {
+    /// A semver-incompatible change. Follows cargo's rules, so bumping the minor version of a
+    /// `0.x` release is also considered major.
+    Major,
+    /// A new minor version of a `1.x` or later release.
+    Minor,
+    /// A new patch version.
+    Patch,
+    /// Only the pre-release or build metadata changed, or nothing at all.
+    Other,
+}
+
+impl SemverClass {
+    /// The kind of change from `from` to `to`.
+    pub fn between(from: &SemVer, to: &SemVer) -> Self {
+        if from.major != to.major || (from.major == 0 && from.minor != to.minor) {
+            SemverClass::Major
+        } else if from.minor != to.minor {
+            SemverClass::Minor
+        } else if from.patch != to.patch {
+            SemverClass::Patch
+        } else {
+            SemverClass::Other
+        }
+    }
+}
+
+impl Display for SemverClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SemverClass::Major => write!(f, "major"),
+            SemverClass::Minor => write!(f, "minor"),
+            SemverClass::Patch => write!(f, "patch"),
+            SemverClass::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// What the registry knows about a crate, as told by [`CargoBackend::info`].
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct KrateMetadata {
     name: String,
@@ -290,96 +710,327 @@ pub struct KrateMetadata {
 }
 
 impl KrateMetadata {
-    /// Get the info of the given crate.
-    pub fn get(name: &str) -> Result<Self, Error> {
-        let s = search_crate(name)?;
-        let info = KrateInfo::parse(&s)?.1;
-        let ki = Self {
+    /// The crate's name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The latest version, if `cargo info` told it.
+    pub fn latest_version(&self) -> Option<&SemVer> {
+        self.info.latest_version.as_ref()
+    }
+
+    /// Why `cargo info` failed on the crate, if it did.
+    pub fn fetch_error(&self) -> Option<&str> {
+        self.info.fetch_error.as_deref()
+    }
+
+    /// Reads the stdout of `cargo info` for the given crate.
+    /// Crates whose info cannot be parsed are marked as unparseable instead of failing.
+    pub fn parse(name: &str, s: &str) -> Self {
+        let info = KrateInfo::parse(s).unwrap_or_else(|e| KrateInfo::unparseable(e.to_string()));
+
+        Self {
             name: name.to_string(),
             info,
-        };
+        }
+    }
+
+    /// Metadata of a crate that `cargo info` failed on, with the error.
+    pub fn unavailable(name: &str, reason: String) -> Self {
+        Self {
+            name: name.to_string(),
+            info: KrateInfo {
+                fetch_error: Some(reason),
+                synced: true,
+                ..Default::default()
+            },
+        }
+    }
 
-        Ok(ki)
+    /// Metadata with only the latest version, as a registry would report it.
+    pub fn with_latest(name: &str, version: SemVer) -> Self {
+        Self {
+            name: name.to_string(),
+            info: KrateInfo {
+                latest_version: Some(version),
+                synced: true,
+                ..Default::default()
+            },
+        }
     }
 }
 
 /// Contains latest information about the crate from crates.io.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
-struct KrateInfo {
-    description: Option<String>,
+#[non_exhaustive]
+pub struct KrateInfo {
+    /// The crate's description.
+    pub description: Option<String>,
     tags: Option<Tags>,
-    latest_version: Option<SemVer>,
-    license: Option<String>,
-    // Some crates have "unknown" as their Rust version.
-    rust_version: Option<SemVer>,
+    /// The latest version, or the one that was asked for.
+    pub latest_version: Option<SemVer>,
+    /// The crate's license, e.g. `MIT OR Apache-2.0`.
+    pub license: Option<String>,
+    /// The minimum supported Rust version. Some crates have "unknown" as theirs.
+    pub rust_version: Option<SemVer>,
     // NOTE: Let's keep the urls as strings for now as it's easier to parse and display.
-    documentation: Option<String>,
-    homepage: Option<String>,
-    repository: Option<String>,
-    crates_io: Option<String>,
+    /// Where the crate's documentation is.
+    pub documentation: Option<String>,
+    /// The crate's homepage.
+    pub homepage: Option<String>,
+    /// Where the crate's source is.
+    pub repository: Option<String>,
+    /// The crate's page on crates.io.
+    pub crates_io: Option<String>,
+    features: Features,
+    /// Whether the listed version has been yanked.
+    pub yanked: bool,
+    /// Set when the output of `cargo info` could not be read.
+    parse_error: Option<String>,
+    /// Set when `cargo info` failed, e.g. because the registry could not be reached.
+    fetch_error: Option<String>,
     synced: bool,
 }
 
-impl KrateInfo {
-    fn parse(s: &str) -> IResult<&str, Self> {
-        let (s, _) = alphanumeric1_with_hyphen(s)?;
-        let (s, _) = multispace0(s)?;
-        let (s, tags) = opt(map(opt(Tags::parse), |t| t.unwrap_or_default())).parse(s)?;
-        let (s, _) = multispace0(s)?;
-        let (s, description) = opt(map(take_until("version"), String::from)).parse(s)?;
-        let (s, _) = ws(tag("version:")).parse(s)?;
-        let (s, latest_version) = opt(SemVer::parse).parse(s)?;
-        let (s, _) = newline(s)?;
-        let (s, _) = ws(tag("license:")).parse(s)?;
-        let (s, license) = opt(map(take_until("\n"), String::from)).parse(s)?;
-        let (s, _) = newline(s)?;
-        let (s, _) = ws(tag("rust-version:")).parse(s)?;
-        let (s, rust_version) = opt(SemVer::parse).parse(s)?;
-        // When the Rust version is "unknown".
-        let (s, _) = take_until("\n")(s)?;
-        let (s, _) = opt(ws(tag("documentation:"))).parse(s)?;
-        let (s, documentation) = opt(map(take_until("\n"), String::from)).parse(s)?;
-        let (s, homepage) = opt(map(
-            opt(preceded(ws(tag("homepage:")), take_until("\n"))),
-            |d| d.unwrap_or("not available").to_string(),
-        ))
-        .parse(s)?;
-        let (s, _) = multispace0(s)?;
-        let (s, repository) = opt(map(
-            opt(preceded(ws(tag("repository:")), take_until("\n"))),
-            |d| d.unwrap_or("not available").to_string(),
-        ))
-        .parse(s)?;
-        let (s, _) = multispace0(s)?;
-        let (s, _) = ws(tag("crates.io:")).parse(s)?;
-        let (s, crates_io) = opt(map(take_until("\n"), String::from)).parse(s)?;
-        let synced = true;
+/// Fields that `cargo info` prints after the description.
+/// The description ends at the first line that starts with one of them.
+const KRATE_INFO_FIELDS: [&str; 9] = [
+    "version",
+    "license",
+    "rust-version",
+    "documentation",
+    "homepage",
+    "repository",
+    "crates.io",
+    "features",
+    "dependencies",
+];
 
-        let description = if let Some(d) = description {
-            Some(d.trim_end().to_string())
-        } else {
-            description
+impl KrateInfo {
+    /// Parses the stdout of `cargo info`.
+    ///
+    /// The output is read as a header with the name and tags, a description, and `key: value`
+    /// fields. Fields such as `features:` are followed by an indented section. Every field is
+    /// optional and unknown fields are skipped, so that output from different cargo releases can
+    /// be read as long as the header is there.
+    fn parse(s: &str) -> Result<Self, Error> {
+        let mut lines = s
+            .lines()
+            .filter(|l| !l.trim().is_empty() && !is_diagnostic(l))
+            .peekable();
+
+        let header = lines
+            .next()
+            .ok_or_else(|| Error::KrateInfo("no output".to_string()))?;
+        let (rest, _) = package_name(header.trim())
+            .map_err(|_| Error::KrateInfo(format!("unexpected header: {header}")))?;
+        let tags = match Tags::parse(rest.trim()) {
+            Ok((_, tags)) => tags,
+            Err(_) => Tags::default(),
         };
 
-        let k = Self {
-            description,
-            tags,
-            latest_version,
-            license,
-            rust_version,
-            documentation,
-            homepage,
-            repository,
-            crates_io,
-            synced,
+        let mut description = vec![];
+        while let Some(line) = lines.next_if(|l| !parse_field(l).is_some_and(is_known_field)) {
+            description.push(line.trim());
+        }
+
+        let mut info = Self {
+            tags: Some(tags),
+            description: (!description.is_empty()).then(|| description.join(" ")),
+            synced: true,
+            ..Default::default()
         };
 
-        Ok((s, k))
+        while let Some(line) = lines.next() {
+            let Some((key, value)) = parse_field(line) else {
+                continue;
+            };
+            let value = Some(value.to_string()).filter(|v| !v.is_empty());
+            match key {
+                "version" => {
+                    let (version, yanked) = parse_version_field(value.as_deref().unwrap_or(""));
+                    info.latest_version = version;
+                    info.yanked = yanked;
+                }
+                "license" => info.license = value,
+                "rust-version" => info.rust_version = value.as_deref().and_then(parse_rust_version),
+                "documentation" => info.documentation = value,
+                "homepage" => info.homepage = value,
+                "repository" => info.repository = value,
+                "crates.io" => info.crates_io = value,
+                "features" => {
+                    let mut section = vec![];
+                    while let Some(line) = lines.next_if(is_section_line) {
+                        section.push(line);
+                    }
+                    info.features = Features::parse(&section.join("\n"))
+                        .map(|(_, f)| f)
+                        .unwrap_or_default();
+                }
+                _ => {
+                    // Skips the section of fields that are not used, e.g. `dependencies:`.
+                    while lines.next_if(is_section_line).is_some() {}
+                }
+            }
+        }
+
+        Ok(info)
+    }
+
+    /// Info for a crate whose `cargo info` output could not be read.
+    fn unparseable(reason: String) -> Self {
+        Self {
+            parse_error: Some(reason),
+            synced: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// Lines such as `warning: ...` and `note: ...` that cargo may print around the output.
+fn is_diagnostic(line: &str) -> bool {
+    ["warning:", "note:", "error:", "help:"]
+        .iter()
+        .any(|p| line.starts_with(p))
+}
+
+/// An indented line that belongs to the field above it, e.g. a feature.
+fn is_section_line(line: &&str) -> bool {
+    line.starts_with(char::is_whitespace) && parse_field(line).is_none()
+}
+
+/// > license: MIT
+fn parse_field(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.trim().split_once(':')?;
+    let is_key = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.');
+    if !is_key || !(value.is_empty() || value.starts_with(' ')) {
+        return None;
+    }
+
+    Some((key, value.trim()))
+}
+
+fn is_known_field((key, _): (&str, &str)) -> bool {
+    KRATE_INFO_FIELDS.contains(&key)
+}
+
+/// > 0.3.18 (latest 0.3.55)
+/// > 0.3.1 (yanked)
+///
+/// Returns the latest version and whether the listed version has been yanked.
+fn parse_version_field(s: &str) -> (Option<SemVer>, bool) {
+    let (version, notes) = s.split_once(' ').unwrap_or((s, ""));
+    let latest = notes
+        .split_once("latest ")
+        .and_then(|(_, l)| l.split([' ', ')']).next())
+        .and_then(SemVer::new);
+    let yanked = notes.contains("yanked");
+
+    (latest.or_else(|| SemVer::new(version)), yanked)
+}
+
+/// Reads `rust-version`, which is often written without a patch version, e.g. `1.85`.
+fn parse_rust_version(s: &str) -> Option<SemVer> {
+    match s.matches('.').count() {
+        1 => SemVer::new(format!("{s}.0")),
+        _ => SemVer::new(s),
+    }
+}
+
+/// The features a crate declares, as listed by `cargo info`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Features(pub Vec<Feature>);
+
+/// A feature a crate declares.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Feature {
+    /// The feature's name.
+    pub name: String,
+    /// Features and optional dependencies turned on by this feature.
+    pub enables: Vec<String>,
+    /// Marked with `+` by `cargo info`, meaning it is on by default.
+    pub default: bool,
+}
+
+impl Features {
+    /// >  +default = [std]
+    /// >   std     = []
+    fn parse(s: &str) -> IResult<&str, Self> {
+        let (s, features) = separated_list0(newline, Feature::parse).parse(s)?;
+
+        Ok((s, Features(features)))
+    }
+
+    /// The features that are turned on when `requested` are, following what each feature enables.
+    /// `default` is added unless `no_default_features` is set.
+    pub fn resolve(
+        &self,
+        requested: impl IntoIterator<Item = String>,
+        no_default_features: bool,
+        all_features: bool,
+    ) -> BTreeSet<String> {
+        if all_features {
+            return self.0.iter().map(|f| f.name.clone()).collect();
+        }
+
+        let mut pending: Vec<String> = requested.into_iter().collect();
+        if !no_default_features {
+            pending.push("default".to_string());
+        }
+
+        let mut enabled = BTreeSet::new();
+        while let Some(name) = pending.pop() {
+            let Some(feature) = self.0.iter().find(|f| f.name == name) else {
+                continue;
+            };
+            if enabled.insert(name) {
+                pending.extend(feature.enables.iter().cloned());
+            }
+        }
+
+        enabled
+    }
+}
+
+impl Feature {
+    fn parse(s: &str) -> IResult<&str, Self> {
+        let (s, default) = map(ws(opt(char('+'))), |c| c.is_some()).parse(s)?;
+        let (s, name) = map(
+            take_while1(|c: char| c.is_alphanumeric() || "-_.+".contains(c)),
+            String::from,
+        )
+        .parse(s)?;
+        let (s, _) = ws2(char('=')).parse(s)?;
+        let (s, enables) = delimited(
+            char('['),
+            separated_list0(
+                ws2(char(',')),
+                map(take_while1(|c: char| c != ',' && c != ']'), |e: &str| {
+                    e.trim().to_string()
+                }),
+            ),
+            char(']'),
+        )
+        .parse(s)?;
+
+        Ok((
+            s,
+            Self {
+                name,
+                enables,
+                default,
+            },
+        ))
     }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
-pub struct Tags(Vec<String>);
+pub(crate) struct Tags(Vec<String>);
 
 impl Display for Tags {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -406,18 +1057,77 @@ impl Tags {
 /// > \tdepot-rs
 fn parse_binary(s: &str) -> IResult<&str, String> {
     let (s, _) = space1(s)?;
-    let (s, b) = map(alphanumeric1_with_hyphen, String::from).parse(s)?;
+    let (s, b) = map(target_name, String::from).parse(s)?;
 
     Ok((s, b))
 }
 
 #[cfg(test)]
 mod parser_tests {
-    use super::{Krate, KrateInfo, Krates, Tags, parse_binary};
-    use crate::{depot::parse_ver, parser::alphanumeric1_with_hyphen};
+    use super::{
+        Depot, Feature, Features, Krate, KrateInfo, KrateMetadata, Krates, PreviousInstall,
+        SemverClass, Tags, format_date, format_size, install_record, parse_binary,
+    };
+    use crate::backend::CargoBackend;
+    use crate::backend::fake::FakeBackend;
+    use crate::installer::InstallMethod;
+    use crate::parser::alphanumeric1_with_hyphen;
+    use crate::records::{InstallRecord, InstallRecords};
+    use crate::tracking::{InstallTracking, TrackedInstall};
     use pretty_assertions::assert_eq;
+    use std::time::{Duration, UNIX_EPOCH};
     use versions::SemVer;
 
+    #[tokio::test]
+    async fn failed_lookups_keep_the_others() {
+        let backend = FakeBackend::default()
+            .with_installed("bat", "0.24.0")
+            .with_installed("ripgrep", "14.1.0")
+            .with_latest("ripgrep", "14.1.1")
+            .failing("bat");
+        let mut depot = Depot {
+            store: backend.list().await.unwrap(),
+            ..Default::default()
+        };
+
+        depot.fetch_info(&backend).await.unwrap();
+
+        assert_eq!(depot.unfetched_krate_count(), 1);
+        assert_eq!(depot.unparseable_krate_count(), 0);
+        assert_eq!(
+            depot.store.0[0].fetch_error(),
+            Some("command failed: cargo info bat")
+        );
+        assert_eq!(depot.outdated_krate_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn install_dates() {
+        let method = InstallMethod::Source;
+        let before_depot = Some(PreviousInstall {
+            written_at: Some(100),
+        });
+
+        // A new install.
+        let record = install_record(None, None, method, 5, 300);
+        assert_eq!(record.installed_at, Some(300));
+        assert_eq!(record.updated_at, None);
+
+        // An update of a crate installed before depot keeps when its binaries were written.
+        let record = install_record(None, before_depot, method, 5, 300);
+        assert_eq!(record.installed_at, Some(100));
+        assert_eq!(record.updated_at, Some(300));
+
+        // A recorded install date wins over the binaries.
+        let recorded = InstallRecord {
+            installed_at: Some(50),
+            ..record
+        };
+        let record = install_record(Some(&recorded), before_depot, method, 5, 400);
+        assert_eq!(record.installed_at, Some(50));
+        assert_eq!(record.updated_at, Some(400));
+    }
+
     #[test]
     fn parse_krates() {
         let s1 = r#"depot-rs v0.1.0:
@@ -473,6 +1183,41 @@ foo v0.1.0:
         );
     }
 
+    #[test]
+    fn parse_krates_with_underscores_and_dots() {
+        let s = r#"cargo_toml v0.21.0:
+    cargo_toml
+wasm-bindgen-cli v0.2.100:
+    wasm-bindgen
+    wasm-bindgen-test-runner
+    wasm2es6js
+ripgrep v14.1.1:
+    rg.exe"#;
+        let krates = Krates::parse(s).unwrap();
+
+        assert_eq!(krates.0, "");
+        assert_eq!(
+            krates
+                .1
+                .0
+                .iter()
+                .map(|k| (k.name.as_str(), k.binaries.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("cargo_toml", vec!["cargo_toml".to_string()]),
+                (
+                    "wasm-bindgen-cli",
+                    vec![
+                        "wasm-bindgen".to_string(),
+                        "wasm-bindgen-test-runner".to_string(),
+                        "wasm2es6js".to_string()
+                    ]
+                ),
+                ("ripgrep", vec!["rg.exe".to_string()]),
+            ]
+        );
+    }
+
     #[test]
     fn parse_empty_krates() {
         let s = "";
@@ -512,16 +1257,6 @@ foo v0.1.0:
         assert!(Krate::parse(s4).is_err(),);
     }
 
-    #[test]
-    fn parse_krate_version() {
-        let s = " fooo foooo fooooo cargo-thesaurust v0.1.2:";
-
-        assert_eq!(
-            parse_ver(s, "cargo-thesaurust").unwrap().1,
-            SemVer::parse("0.1.2").unwrap().1
-        )
-    }
-
     #[test]
     fn parse_single_binary() {
         assert_eq!(parse_binary("\tdepot-rs").unwrap().1, "depot-rs")
@@ -553,7 +1288,7 @@ foo v0.1.0:
         "#;
 
         assert_eq!(
-            KrateInfo::parse(output).unwrap().1,
+            KrateInfo::parse(output).unwrap(),
             KrateInfo {
                 description: Some("A terminal-based dictionary app.".to_string()),
                 tags: Some(Tags(vec![
@@ -570,6 +1305,10 @@ foo v0.1.0:
                 homepage: Some("https://moreenh.me/pages/projects/cargo-thesaurust".to_string()),
                 repository: Some("https://github.com/quietpigeon/cargo-thesaurust".to_string()),
                 crates_io: Some("https://crates.io/crates/cargo-thesaurust/0.1.2".to_string()),
+                features: Features::default(),
+                yanked: false,
+                parse_error: None,
+                fetch_error: None,
                 synced: true
             }
         )
@@ -589,7 +1328,7 @@ foo v0.1.0:
         "#;
 
         assert_eq!(
-            KrateInfo::parse(output).unwrap().1,
+            KrateInfo::parse(output).unwrap(),
             KrateInfo {
                 tags: Some(Tags(vec![])),
                 description: Some("A terminal-based dictionary app.".to_string()),
@@ -600,11 +1339,233 @@ foo v0.1.0:
                 homepage: Some("https://moreenh.me/pages/projects/cargo-thesaurust".to_string()),
                 repository: Some("https://github.com/quietpigeon/cargo-thesaurust".to_string()),
                 crates_io: Some("https://crates.io/crates/cargo-thesaurust/0.1.2".to_string()),
+                features: Features::default(),
+                yanked: false,
+                parse_error: None,
+                fetch_error: None,
                 synced: true
             }
         )
     }
 
+    /// Reads a fixture from `fixtures/cargo_info`, named after the cargo release that printed it.
+    macro_rules! cargo_info_fixture {
+        ($name:literal) => {
+            include_str!(concat!("../fixtures/cargo_info/", $name, ".txt"))
+        };
+    }
+
+    #[test]
+    fn parse_krate_info_fixtures() {
+        let v = |s| SemVer::new(s);
+
+        let ripgrep = KrateInfo::parse(cargo_info_fixture!("1.95-ripgrep")).unwrap();
+        assert_eq!(
+            ripgrep.description.as_deref(),
+            Some(
+                "ripgrep is a line-oriented search tool that recursively searches the current \
+                 directory for a regex pattern while respecting gitignore rules. ripgrep has \
+                 first class support on Windows, macOS and Linux."
+            )
+        );
+        assert_eq!(ripgrep.latest_version, v("15.2.0"));
+        assert_eq!(ripgrep.rust_version, v("1.85.0"));
+        assert_eq!(ripgrep.license.as_deref(), Some("Unlicense OR MIT"));
+        assert_eq!(ripgrep.features.0.len(), 1);
+
+        let bat = KrateInfo::parse(cargo_info_fixture!("1.95-bat")).unwrap();
+        assert_eq!(bat.tags, Some(Tags(vec![])));
+        assert_eq!(bat.features.0.len(), 21);
+        assert!(bat.features.0[0].default);
+
+        let clap = KrateInfo::parse(cargo_info_fixture!("1.95-clap-older-version")).unwrap();
+        assert_eq!(clap.latest_version, v("4.6.7"));
+        assert!(!clap.yanked);
+
+        let time = KrateInfo::parse(cargo_info_fixture!("1.95-time-older-version")).unwrap();
+        assert_eq!(
+            time.description.as_deref(),
+            Some(
+                "Date and time library. Fully interoperable with the standard library. Mostly \
+                 compatible with #![no_std]."
+            )
+        );
+        assert_eq!(time.latest_version, v("0.3.55"));
+
+        // Run in a workspace whose lockfile pins the yanked 0.3.48. Cargo describes the
+        // locked version, but doesn't say that it was yanked.
+        let time = KrateInfo::parse(cargo_info_fixture!("1.86-time-yanked-in-lockfile")).unwrap();
+        assert_eq!(time.latest_version, v("0.3.55"));
+        assert_eq!(time.rust_version, v("1.88.0"));
+        assert_eq!(
+            time.crates_io.as_deref(),
+            Some("https://crates.io/crates/time/0.3.48")
+        );
+        assert!(!time.yanked);
+        assert_eq!(time.features.0.len(), 17);
+
+        let ripgrep =
+            KrateInfo::parse(cargo_info_fixture!("1.97-nightly-ripgrep-verbose")).unwrap();
+        assert_eq!(
+            ripgrep.crates_io.as_deref(),
+            Some("https://crates.io/crates/ripgrep/15.2.0")
+        );
+        assert_eq!(ripgrep.features.0.len(), 1);
+
+        let nextest = KrateInfo::parse(cargo_info_fixture!("1.97-nightly-cargo-nextest")).unwrap();
+        assert_eq!(nextest.homepage, None);
+        assert_eq!(nextest.rust_version, v("1.91.0"));
+        assert_eq!(nextest.features.0.len(), 5);
+    }
+
+    #[test]
+    fn parse_krate_info_with_diagnostics_and_missing_fields() {
+        // Cargo prints its diagnostics on stderr, but they end up in the output with `2>&1`.
+        let output = "warning: spurious network error (3 tries remaining)
+tool
+A tool.
+version: 0.3.1 (yanked)
+rust-version: 1.70
+note: to see how you depend on tool, run `cargo tree --invert tool@0.3.1`
+";
+        let info = KrateInfo::parse(output).unwrap();
+
+        assert_eq!(info.description.as_deref(), Some("A tool."));
+        assert_eq!(info.latest_version, SemVer::new("0.3.1"));
+        assert!(info.yanked);
+        assert_eq!(info.license, None);
+        assert_eq!(info.rust_version, SemVer::new("1.70.0"));
+    }
+
+    #[test]
+    fn parse_unparseable_krate_info() {
+        assert!(KrateInfo::parse("").is_err());
+        assert!(KrateInfo::parse("error: could not find `foo` in registry").is_err());
+        assert!(KrateInfo::parse("!!!").is_err());
+    }
+
+    #[test]
+    fn parse_krate_info_with_features() {
+        let output = r#"bat
+A cat(1) clone with wings.
+version: 0.25.0
+license: MIT OR Apache-2.0
+rust-version: unknown
+documentation: https://docs.rs/bat/0.25.0
+homepage: https://github.com/sharkdp/bat
+repository: https://github.com/sharkdp/bat
+crates.io: https://crates.io/crates/bat/0.25.0
+features:
+ +default     = [application, git]
+  application = [minimal-application]
+  git         = [git2]
+  git2        = [dep:git2]
+  minimal-application = [clap, paging]
+  regex-fancy = [syntect/regex-fancy]
+"#;
+        let info = KrateInfo::parse(output).unwrap();
+
+        assert_eq!(info.features.0.len(), 6);
+        assert_eq!(
+            info.features.0[0],
+            Feature {
+                name: "default".to_string(),
+                enables: vec!["application".to_string(), "git".to_string()],
+                default: true,
+            }
+        );
+        assert_eq!(
+            info.features.0[5],
+            Feature {
+                name: "regex-fancy".to_string(),
+                enables: vec!["syntect/regex-fancy".to_string()],
+                default: false,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_feature_without_dependencies() {
+        assert_eq!(
+            Feature::parse("  std = []").unwrap().1,
+            Feature {
+                name: "std".to_string(),
+                enables: vec![],
+                default: false,
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_enabled_features() {
+        let features = Features::parse(
+            " +default = [a]\n  a = [b, dep:foo]\n  b = []\n  c = [foo?/bar]\n  d = []",
+        )
+        .unwrap()
+        .1;
+        let names = |v: &[&str]| v.iter().map(|s| s.to_string()).collect();
+
+        assert_eq!(
+            features.resolve(vec![], false, false),
+            names(&["a", "b", "default"])
+        );
+        assert_eq!(
+            features.resolve(vec!["c".to_string()], true, false),
+            names(&["c"])
+        );
+        assert_eq!(
+            features.resolve(vec![], true, true),
+            names(&["a", "b", "c", "d", "default"])
+        );
+    }
+
+    #[test]
+    fn reconcile_with_tracking() {
+        let v = |s| SemVer::new(s).unwrap();
+        let tracked = |bin: &str| TrackedInstall {
+            bins: [bin.to_string()].into(),
+            ..Default::default()
+        };
+        let mut depot = Depot {
+            store: Krates::from_cargo_list("bat v0.24.0:\n    bat\nzoxide v0.9.4:\n    zoxide\n")
+                .unwrap(),
+            ..Default::default()
+        };
+        depot.store.0[0].set_metadata(KrateMetadata::with_latest("bat", v("0.25.0")));
+
+        let mut tracking = InstallTracking::default();
+        tracking.insert("bat", &v("0.25.0"), tracked("bat"));
+        tracking.insert("fd-find", &v("10.2.0"), tracked("fd"));
+        let mut records = InstallRecords::default();
+        records.set(
+            "fd-find",
+            InstallRecord {
+                method: InstallMethod::Prebuilt,
+                build_secs: None,
+                installed_at: Some(0),
+                updated_at: None,
+            },
+        );
+        // Without tracking data, only an uninstall drops a crate.
+        let untracked = InstallTracking::default();
+        depot.reconcile("zoxide", false, &untracked, &records);
+        assert_eq!(depot.crate_count(), 2);
+        depot.reconcile("bat", false, &tracking, &records);
+        depot.reconcile("fd-find", false, &tracking, &records);
+        depot.reconcile("zoxide", false, &tracking, &records);
+
+        let names: Vec<&str> = depot.store.0.iter().map(|k| k.name.as_str()).collect();
+        assert_eq!(names, vec!["bat", "fd-find"]);
+        assert_eq!(depot.store.0[0].version, v("0.25.0"));
+        assert!(depot.store.0[0].is_latest());
+        assert_eq!(depot.store.0[1].binaries, vec!["fd"]);
+        assert_eq!(
+            depot.store.0[1].install_method,
+            Some(InstallMethod::Prebuilt)
+        );
+        assert_eq!(depot.store.0[1].installed_at, Some(UNIX_EPOCH));
+    }
+
     #[test]
     fn parse_semver() {
         let v = SemVer::parse("1.12.0").unwrap().1;
@@ -620,6 +1581,122 @@ foo v0.1.0:
         assert!(SemVer::parse("1.1").is_err());
     }
 
+    #[test]
+    fn classify_version_change() {
+        let v = |s| SemVer::new(s).unwrap();
+
+        assert_eq!(
+            SemverClass::between(&v("1.2.3"), &v("2.0.0")),
+            SemverClass::Major
+        );
+        assert_eq!(
+            SemverClass::between(&v("0.2.3"), &v("0.3.0")),
+            SemverClass::Major
+        );
+        assert_eq!(
+            SemverClass::between(&v("1.2.3"), &v("1.3.0")),
+            SemverClass::Minor
+        );
+        assert_eq!(
+            SemverClass::between(&v("0.2.3"), &v("0.2.4")),
+            SemverClass::Patch
+        );
+        assert_eq!(
+            SemverClass::between(&v("1.2.3"), &v("1.2.3")),
+            SemverClass::Other
+        );
+    }
+
+    #[test]
+    fn format_dates() {
+        let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+
+        assert_eq!(format_date(at(0)), "1970-01-01");
+        assert_eq!(format_date(at(951_782_400)), "2000-02-29");
+        assert_eq!(format_date(at(1_743_465_599)), "2025-03-31");
+    }
+
+    #[test]
+    fn format_sizes() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024 + 300 * 1024), "5.3 MiB");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    }
+
+    mod props {
+        use super::super::{Krate, Krates};
+        use proptest::prelude::*;
+        use versions::SemVer;
+
+        fn package_name() -> impl Strategy<Value = String> {
+            "[a-zA-Z_][a-zA-Z0-9_-]{0,30}"
+        }
+
+        fn target_name() -> impl Strategy<Value = String> {
+            "[a-zA-Z0-9_][a-zA-Z0-9_.-]{0,30}"
+        }
+
+        fn version() -> impl Strategy<Value = String> {
+            (
+                0u32..1000,
+                0u32..1000,
+                0u32..1000,
+                "(-(alpha|beta|rc)\\.(0|[1-9][0-9]?))?",
+            )
+                .prop_map(|(major, minor, patch, pre)| format!("{major}.{minor}.{patch}{pre}"))
+        }
+
+        /// What cargo prints after the version, if anything.
+        fn source() -> impl Strategy<Value = String> {
+            prop_oneof![
+                Just("".to_string()),
+                "[a-z]{1,10}/[a-z]{1,10}#[0-9a-f]{8}"
+                    .prop_map(|repo| format!(" (https://github.com/{repo})")),
+                "[a-z ]{1,20}".prop_map(|dir| format!(" (path+file:///home/{dir})")),
+            ]
+        }
+
+        fn entry() -> impl Strategy<Value = (String, String, String, Vec<String>)> {
+            (
+                package_name(),
+                version(),
+                source(),
+                prop::collection::vec(target_name(), 1..5),
+            )
+        }
+
+        proptest! {
+            #[test]
+            fn krates_round_trip(entries in prop::collection::vec(entry(), 0..10)) {
+                let output = entries
+                    .iter()
+                    .map(|(name, version, source, binaries)| {
+                        let binaries: Vec<String> =
+                            binaries.iter().map(|b| format!("    {b}")).collect();
+                        format!("{name} v{version}{source}:\n{}", binaries.join("\n"))
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+
+                let (rest, krates) = Krates::parse(&output).unwrap();
+                let expected: Vec<Krate> = entries
+                    .into_iter()
+                    .map(|(name, version, _, binaries)| Krate {
+                        name,
+                        version: SemVer::new(&version).unwrap(),
+                        binaries,
+                        ..Default::default()
+                    })
+                    .collect();
+
+                prop_assert_eq!(rest, "");
+                prop_assert_eq!(krates, Krates(expected));
+            }
+        }
+    }
+
     #[test]
     fn parse_hyphenated() {
         assert_eq!(alphanumeric1_with_hyphen("depot-rs").unwrap().1, "depot-rs");
@@ -0,0 +1,259 @@
+//! The `depot.toml` manifest that `depot export` writes and `depot import` installs from.
+
+use crate::depot::Krates;
+use crate::errors::Error;
+use crate::tracking::{InstallTracking, KrateSource};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+/// A declarative list of the crates that should be installed, so that a toolset can be kept in a
+/// dotfiles repository and installed on another machine.
+///
+/// > [crates.ripgrep]
+/// > version = "14.1.1"
+/// > features = ["pcre2"]
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct Depotfile {
+    /// Each crate by name, with how it should be installed.
+    #[serde(default)]
+    pub crates: BTreeMap<String, ToolSpec>,
+}
+
+/// How a single crate should be installed. Mirrors the flags of `cargo install`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct ToolSpec {
+    /// A version requirement, where a bare version means exactly that version.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Index URL of the registry, when it is not crates.io.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    /// A git repository to install from instead of a registry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git: Option<String>,
+    /// The commit of `git` to install.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rev: Option<String>,
+    /// A local directory to install from instead of a registry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    /// Features to turn on.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub features: Vec<String>,
+    /// Whether every feature is turned on.
+    #[serde(skip_serializing_if = "is_false")]
+    pub all_features: bool,
+    /// Whether the default features are turned off.
+    #[serde(skip_serializing_if = "is_false")]
+    pub no_default_features: bool,
+    /// The build profile, when it is not `release`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
+}
+
+fn is_false(b: &bool) -> bool {
+    !b
+}
+
+impl Depotfile {
+    /// Describes the installed crates, using cargo's tracking for the sources and flags.
+    pub fn export(krates: &Krates, tracking: &InstallTracking) -> Self {
+        let crates = krates
+            .0
+            .iter()
+            .map(|krate| {
+                let tracked = tracking.get(&krate.name);
+                let mut spec = ToolSpec {
+                    version: tracked
+                        .and_then(|t| t.version_req.clone())
+                        .or_else(|| Some(krate.version.to_string())),
+                    ..Default::default()
+                };
+                if let Some(t) = tracked {
+                    spec.features = t.features.iter().cloned().collect();
+                    spec.all_features = t.all_features;
+                    spec.no_default_features = t.no_default_features;
+                    spec.profile = (!t.profile.is_empty() && t.profile != "release")
+                        .then(|| t.profile.clone());
+                }
+                match tracking.source(&krate.name) {
+                    Some(KrateSource::Registry { index }) => spec.index = index,
+                    Some(KrateSource::Git { url, rev }) => {
+                        // Git and path installs don't take a registry version requirement.
+                        spec.version = None;
+                        spec.git = Some(url);
+                        spec.rev = rev;
+                    }
+                    Some(KrateSource::Path(path)) => {
+                        spec.version = None;
+                        spec.path = Some(path);
+                    }
+                    None => {}
+                }
+
+                (krate.name.clone(), spec)
+            })
+            .collect();
+
+        Self { crates }
+    }
+
+    /// Reads the Depotfile at `path`.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    fn parse(s: &str) -> Result<Self, Error> {
+        Ok(toml::from_str(s)?)
+    }
+
+    /// The Depotfile as it is written to `depot.toml`.
+    pub fn to_toml(&self) -> Result<String, Error> {
+        Ok(toml::to_string(self)?)
+    }
+
+    /// The crates in the file that are not installed.
+    pub fn missing(&self, installed: &Krates) -> Vec<(&str, &ToolSpec)> {
+        self.crates
+            .iter()
+            .filter(|(name, _)| !installed.0.iter().any(|k| &k.name == *name))
+            .map(|(name, spec)| (name.as_str(), spec))
+            .collect()
+    }
+}
+
+impl ToolSpec {
+    /// A short description of where the crate comes from, e.g. `14.1.1 +pcre2`.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![];
+        if let Some(git) = &self.git {
+            parts.push(git.clone());
+        } else if let Some(path) = &self.path {
+            parts.push(path.clone());
+        } else {
+            parts.push(self.version.clone().unwrap_or_else(|| "latest".to_string()));
+        }
+        parts.extend(self.features.iter().map(|f| format!("+{f}")));
+
+        parts.join(" ")
+    }
+
+    /// The arguments passed to `cargo` to install the crate.
+    pub fn install_args(&self, name: &str) -> Vec<String> {
+        let mut args = vec!["install".to_string(), name.to_string()];
+        let mut flag = |f: &str, v: &Option<String>| {
+            if let Some(v) = v {
+                args.push(format!("--{f}"));
+                args.push(v.clone());
+            }
+        };
+        flag("version", &self.version);
+        flag("index", &self.index);
+        flag("git", &self.git);
+        flag("rev", &self.rev);
+        flag("path", &self.path);
+        flag("profile", &self.profile);
+        if !self.features.is_empty() {
+            args.push("--features".to_string());
+            args.push(self.features.join(","));
+        }
+        if self.all_features {
+            args.push("--all-features".to_string());
+        }
+        if self.no_default_features {
+            args.push("--no-default-features".to_string());
+        }
+        args.push("--locked".to_string());
+
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Depotfile, ToolSpec};
+    use crate::depot::{Krate, Krates};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_depotfile() {
+        let s = r#"
+[crates.ripgrep]
+version = "14.1.1"
+features = ["pcre2"]
+
+[crates.foo]
+git = "https://github.com/foo/foo"
+rev = "9f2b6c1"
+no-default-features = true
+"#;
+        let depotfile = Depotfile::parse(s).unwrap();
+
+        assert_eq!(
+            depotfile.crates["ripgrep"].install_args("ripgrep"),
+            vec![
+                "install",
+                "ripgrep",
+                "--version",
+                "14.1.1",
+                "--features",
+                "pcre2",
+                "--locked"
+            ]
+        );
+        assert_eq!(
+            depotfile.crates["foo"].install_args("foo"),
+            vec![
+                "install",
+                "foo",
+                "--git",
+                "https://github.com/foo/foo",
+                "--rev",
+                "9f2b6c1",
+                "--no-default-features",
+                "--locked"
+            ]
+        );
+    }
+
+    #[test]
+    fn depotfile_round_trip() {
+        let mut depotfile = Depotfile::default();
+        depotfile.crates.insert(
+            "bat".to_string(),
+            ToolSpec {
+                version: Some("0.25.0".to_string()),
+                all_features: true,
+                ..Default::default()
+            },
+        );
+        let s = depotfile.to_toml().unwrap();
+
+        assert_eq!(
+            s,
+            "[crates.bat]\nversion = \"0.25.0\"\nall-features = true\n"
+        );
+        assert_eq!(Depotfile::parse(&s).unwrap(), depotfile);
+    }
+
+    #[test]
+    fn find_missing_crates() {
+        let depotfile =
+            Depotfile::parse("[crates.bat]\n[crates.ripgrep]\nversion = \"14.1.1\"").unwrap();
+        let mut bat = Krate::default();
+        bat.name = "bat".to_string();
+
+        let missing: Vec<&str> = depotfile
+            .missing(&Krates(vec![bat]))
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        assert_eq!(missing, vec!["ripgrep"]);
+    }
+}
@@ -0,0 +1,370 @@
+//! `depot doctor`: checks that the toolchain, the registry and depot's own files are usable.
+
+use crate::backend::CargoBackend;
+use crate::commands::{cargo_version, rustc_version};
+use crate::config::{Config, SearchConfig};
+use crate::paths;
+use crate::policy::Policy;
+use std::env;
+use std::ffi::OsStr;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use versions::SemVer;
+
+/// The first cargo release with `cargo info`, which depot reads crate details from.
+const CARGO_INFO_SINCE: (u32, u32) = (1, 82);
+
+/// How a check went. Ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Status {
+    /// Nothing to do.
+    Pass,
+    /// Depot works, but some of it won't.
+    Warning,
+    /// Depot can't work until it is fixed.
+    Fail,
+}
+
+impl Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Status::Pass => write!(f, "ok"),
+            Status::Warning => write!(f, "warn"),
+            Status::Fail => write!(f, "fail"),
+        }
+    }
+}
+
+/// The result of a single check of the environment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Diagnosis {
+    /// What was checked, e.g. `cargo`.
+    pub name: &'static str,
+    /// How the check went.
+    pub status: Status,
+    /// What was found, e.g. the cargo version.
+    pub detail: String,
+    /// How to fix a warning or failure.
+    pub hint: Option<String>,
+}
+
+impl Diagnosis {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warning,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Checks of everything depot relies on, so that a broken setup is explained instead of making
+/// depot fail in confusing ways.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DoctorReport(pub Vec<Diagnosis>);
+
+impl DoctorReport {
+    /// Runs every check, reaching the registry through `backend`. That needs the network, so
+    /// this can take a while.
+    pub async fn run(backend: &dyn CargoBackend) -> Self {
+        let cargo = cargo_version()
+            .await
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .map(|v| v.trim().to_string());
+
+        Self(vec![
+            check_cargo(cargo.as_deref()),
+            check_rustc().await,
+            check_cargo_info(cargo.as_deref()),
+            check_path(),
+            check_cargo_home(),
+            check_config(),
+            check_registry(backend).await,
+            check_writable("bin directory", paths::cargo_bin_dir().as_deref(), true),
+            check_writable("data directory", paths::data_dir().as_deref(), false),
+        ])
+    }
+
+    /// The worst status of any check.
+    pub fn status(&self) -> Status {
+        self.0
+            .iter()
+            .map(|d| d.status)
+            .max()
+            .unwrap_or(Status::Pass)
+    }
+}
+
+impl Display for DoctorReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for diagnosis in &self.0 {
+            writeln!(
+                f,
+                "{:<7}{:<18}{}",
+                format!("[{}]", diagnosis.status),
+                diagnosis.name,
+                diagnosis.detail
+            )?;
+            if let Some(hint) = &diagnosis.hint {
+                writeln!(f, "{:<25}{hint}", "")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn check_cargo(version: Option<&str>) -> Diagnosis {
+    match version {
+        Some(v) => Diagnosis::pass("cargo", v),
+        None => Diagnosis::fail(
+            "cargo",
+            "cargo was not found",
+            "install Rust with rustup from https://rustup.rs",
+        ),
+    }
+}
+
+async fn check_rustc() -> Diagnosis {
+    match rustc_version().await {
+        Ok(v) if !v.trim().is_empty() => Diagnosis::pass("rustc", v.trim()),
+        _ => Diagnosis::fail(
+            "rustc",
+            "rustc was not found",
+            "run `rustup default stable` to install a toolchain",
+        ),
+    }
+}
+
+fn check_cargo_info(cargo: Option<&str>) -> Diagnosis {
+    let Some(version) = cargo.and_then(parse_cargo_version) else {
+        return Diagnosis::fail(
+            "cargo info",
+            "the cargo version is unknown",
+            "install cargo first",
+        );
+    };
+
+    if supports_cargo_info(&version) {
+        Diagnosis::pass("cargo info", "available")
+    } else {
+        Diagnosis::fail(
+            "cargo info",
+            format!(
+                "cargo {version} is older than {}.{}",
+                CARGO_INFO_SINCE.0, CARGO_INFO_SINCE.1
+            ),
+            "run `rustup update stable`",
+        )
+    }
+}
+
+fn check_path() -> Diagnosis {
+    let Some(bin_dir) = paths::cargo_bin_dir() else {
+        return Diagnosis::fail("PATH", "cargo's bin directory is unknown", "set CARGO_HOME");
+    };
+
+    if path_contains(env::var_os("PATH").as_deref(), &bin_dir) {
+        Diagnosis::pass("PATH", format!("{} is on PATH", bin_dir.display()))
+    } else {
+        Diagnosis::warn(
+            "PATH",
+            format!("{} is not on PATH", bin_dir.display()),
+            format!(
+                "add `export PATH=\"{}:$PATH\"` to your shell profile",
+                bin_dir.display()
+            ),
+        )
+    }
+}
+
+fn check_cargo_home() -> Diagnosis {
+    let Some(home) = paths::cargo_home() else {
+        return Diagnosis::fail("CARGO_HOME", "no home directory", "set CARGO_HOME");
+    };
+    if !home.is_dir() {
+        return Diagnosis::fail(
+            "CARGO_HOME",
+            format!("{} does not exist", home.display()),
+            "install Rust with rustup, or point CARGO_HOME at cargo's home directory",
+        );
+    }
+
+    let root = paths::cargo_bin_dir()
+        .and_then(|b| b.parent().map(Path::to_path_buf))
+        .unwrap_or(home.clone());
+    if !root.join(".crates2.json").exists() && !root.join(".crates.toml").exists() {
+        return Diagnosis::warn(
+            "CARGO_HOME",
+            format!("no install tracking in {}", root.display()),
+            "nothing was installed with `cargo install` yet, or CARGO_INSTALL_ROOT is wrong",
+        );
+    }
+
+    Diagnosis::pass("CARGO_HOME", home.display().to_string())
+}
+
+fn check_config() -> Diagnosis {
+    let Some(path) = paths::config_file() else {
+        return Diagnosis::pass("config", "no config directory, using the defaults");
+    };
+    if !path.exists() {
+        return Diagnosis::pass(
+            "config",
+            format!("{} not found, using the defaults", path.display()),
+        );
+    }
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            return Diagnosis::fail(
+                "config",
+                format!("{}: {e}", path.display()),
+                "fix the file or remove it to use the defaults",
+            );
+        }
+    };
+    if let Some(policy) = &config.policy.path
+        && let Err(e) = Policy::load(policy)
+    {
+        return Diagnosis::fail(
+            "config",
+            format!("policy {}: {e}", policy.display()),
+            "check `policy.path` and the policy file",
+        );
+    }
+
+    Diagnosis::pass("config", path.display().to_string())
+}
+
+async fn check_registry(backend: &dyn CargoBackend) -> Diagnosis {
+    let config = SearchConfig {
+        limit: 1,
+        ..Config::load().map(|c| c.search).unwrap_or_default()
+    };
+    let registry = config.index.as_deref().unwrap_or("crates.io");
+    match backend.search("depot-rs", &config).await {
+        Ok(_) => Diagnosis::pass("registry", format!("{registry} is reachable")),
+        Err(_) => Diagnosis::fail(
+            "registry",
+            format!("{registry} could not be reached"),
+            "check your network, proxy settings and `search.index`",
+        ),
+    }
+}
+
+/// Checks that depot can write to `dir` by creating a file in it. A missing directory is fine
+/// when it will be created on first use.
+fn check_writable(name: &'static str, dir: Option<&Path>, required: bool) -> Diagnosis {
+    let Some(dir) = dir else {
+        return Diagnosis::fail(name, "unknown location", "set HOME");
+    };
+    let existing = dir.ancestors().find(|d| d.exists()).unwrap_or(dir);
+
+    let probe = existing.join(format!(".depot-doctor-{}", std::process::id()));
+    let writable = fs::write(&probe, "").and_then(|_| fs::remove_file(&probe));
+    match writable {
+        Ok(_) => Diagnosis::pass(name, format!("{} is writable", dir.display())),
+        Err(e) => {
+            let detail = format!("{} is not writable: {e}", existing.display());
+            let hint = format!("fix the permissions of {}", existing.display());
+            if required {
+                Diagnosis::fail(name, detail, hint)
+            } else {
+                Diagnosis::warn(name, detail, hint)
+            }
+        }
+    }
+}
+
+/// > cargo 1.86.0 (adf9b6ad1 2025-02-28)
+fn parse_cargo_version(s: &str) -> Option<SemVer> {
+    s.split_whitespace().nth(1).and_then(SemVer::new)
+}
+
+fn supports_cargo_info(version: &SemVer) -> bool {
+    (version.major, version.minor) >= CARGO_INFO_SINCE
+}
+
+fn path_contains(path: Option<&OsStr>, dir: &Path) -> bool {
+    let Some(path) = path else {
+        return false;
+    };
+    let dir = fs::canonicalize(dir).unwrap_or(dir.to_path_buf());
+
+    env::split_paths(path).any(|p| fs::canonicalize(&p).unwrap_or(p) == dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Diagnosis, DoctorReport, Status, parse_cargo_version, path_contains, supports_cargo_info,
+    };
+    use pretty_assertions::assert_eq;
+    use std::ffi::OsStr;
+    use std::path::Path;
+    use versions::SemVer;
+
+    #[test]
+    fn check_cargo_info_support() {
+        let old = parse_cargo_version("cargo 1.81.0 (2dbb1af80 2024-08-20)").unwrap();
+        let new = parse_cargo_version("cargo 1.86.0 (adf9b6ad1 2025-02-28)").unwrap();
+
+        assert!(!supports_cargo_info(&old));
+        assert!(supports_cargo_info(&new));
+        assert!(supports_cargo_info(&SemVer::new("2.0.0").unwrap()));
+        assert_eq!(parse_cargo_version("cargo"), None);
+    }
+
+    #[test]
+    fn find_dir_on_path() {
+        let path = OsStr::new("/usr/bin:/nonexistent/.cargo/bin");
+
+        assert!(path_contains(
+            Some(path),
+            Path::new("/nonexistent/.cargo/bin")
+        ));
+        assert!(!path_contains(Some(path), Path::new("/opt/bin")));
+        assert!(!path_contains(None, Path::new("/usr/bin")));
+    }
+
+    #[test]
+    fn report_worst_status() {
+        let report = DoctorReport(vec![
+            Diagnosis::pass("cargo", "cargo 1.86.0"),
+            Diagnosis::warn("PATH", "~/.cargo/bin is not on PATH", "add it"),
+        ]);
+
+        assert_eq!(report.status(), Status::Warning);
+        assert_eq!(
+            report.to_string(),
+            "[ok]   cargo             cargo 1.86.0
+[warn] PATH              ~/.cargo/bin is not on PATH
+                         add it
+"
+        );
+    }
+}
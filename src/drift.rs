@@ -0,0 +1,380 @@
+//! How the installed crates differ from a [`Depotfile`].
+
+use crate::depot::Krates;
+use crate::depotfile::{Depotfile, ToolSpec};
+use crate::errors::Error;
+use crate::tracking::InstallTracking;
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use versions::SemVer;
+
+/// How the installed crates differ from a Depotfile.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DriftReport(pub Vec<Drift>);
+
+/// A crate that differs from the Depotfile.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Drift {
+    /// The crate's name.
+    pub name: String,
+    /// How it differs.
+    pub kind: DriftKind,
+    /// What differs, e.g. `14.1.0 installed, 14.1.1 required`.
+    pub detail: String,
+    /// How the crate should be installed according to the Depotfile. `None` for extra crates.
+    pub spec: Option<ToolSpec>,
+}
+
+/// The groups of the report, in the order they are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[non_exhaustive]
+pub enum DriftKind {
+    /// In the Depotfile, but not installed.
+    Missing,
+    /// Installed, but not in the Depotfile.
+    Extra,
+    /// Installed in a version the Depotfile doesn't allow.
+    Version,
+    /// Installed with different features, source or profile.
+    Options,
+}
+
+impl DriftKind {
+    /// Every group, in the order they are shown.
+    pub const ALL: [DriftKind; 4] = [Self::Missing, Self::Extra, Self::Version, Self::Options];
+}
+
+impl Display for DriftKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing => write!(f, "Missing locally"),
+            Self::Extra => write!(f, "Not in the manifest"),
+            Self::Version => write!(f, "Version mismatches"),
+            Self::Options => write!(f, "Option mismatches"),
+        }
+    }
+}
+
+impl DriftReport {
+    /// Compares the installed crates with `manifest`.
+    pub fn get(manifest: &Depotfile, krates: &Krates) -> Result<Self, Error> {
+        let installed = Depotfile::export(krates, &InstallTracking::load()?);
+
+        Ok(Self::between(manifest, krates, &installed))
+    }
+
+    /// `installed` describes `krates` the way they were installed.
+    fn between(manifest: &Depotfile, krates: &Krates, installed: &Depotfile) -> Self {
+        let mut drift = vec![];
+        for (name, spec) in &manifest.crates {
+            let Some(krate) = krates.0.iter().find(|k| &k.name == name) else {
+                drift.push(Drift {
+                    name: name.clone(),
+                    kind: DriftKind::Missing,
+                    detail: spec.summary(),
+                    spec: Some(spec.clone()),
+                });
+                continue;
+            };
+
+            if let Some(req) = &spec.version
+                && spec.git.is_none()
+                && spec.path.is_none()
+                && !satisfies(req, &krate.version)
+            {
+                drift.push(Drift {
+                    name: name.clone(),
+                    kind: DriftKind::Version,
+                    detail: format!("{} installed, {req} required", krate.version),
+                    spec: Some(spec.clone()),
+                });
+            }
+
+            let differences = installed
+                .crates
+                .get(name)
+                .map(|i| option_differences(spec, i))
+                .unwrap_or_default();
+            if !differences.is_empty() {
+                drift.push(Drift {
+                    name: name.clone(),
+                    kind: DriftKind::Options,
+                    detail: differences.join("; "),
+                    spec: Some(spec.clone()),
+                });
+            }
+        }
+
+        for krate in &krates.0 {
+            if !manifest.crates.contains_key(&krate.name) {
+                drift.push(Drift {
+                    name: krate.name.clone(),
+                    kind: DriftKind::Extra,
+                    detail: krate.version.to_string(),
+                    spec: None,
+                });
+            }
+        }
+        drift.sort_by(|a, b| (a.kind, &a.name).cmp(&(b.kind, &b.name)));
+
+        Self(drift)
+    }
+
+    /// Whether the installed crates match the Depotfile.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The crates that differ in the way `kind` describes.
+    pub fn group(&self, kind: DriftKind) -> impl Iterator<Item = &Drift> {
+        self.0.iter().filter(move |d| d.kind == kind)
+    }
+}
+
+impl Display for DriftReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "No drift.");
+        }
+
+        for kind in DriftKind::ALL {
+            let group: Vec<&Drift> = self.group(kind).collect();
+            if group.is_empty() {
+                continue;
+            }
+            writeln!(f, "{kind} ({})", group.len())?;
+            for drift in group {
+                writeln!(f, "  {:<24}{}", drift.name, drift.detail)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks `version` against a requirement as given to `cargo install --version`, where a bare
+/// version means exactly that version.
+fn satisfies(req: &str, version: &SemVer) -> bool {
+    let req = req.trim();
+    if let Some(exact) = SemVer::new(req) {
+        return &exact == version;
+    }
+
+    req.split(',')
+        .all(|r| matches_comparator(r.trim(), version).unwrap_or(false))
+}
+
+/// Checks a single comparator such as `^1.2` or `>=0.9.0` with cargo's semantics, where a
+/// comparator without an operator is a caret requirement. `None` if it can't be read.
+fn matches_comparator(comparator: &str, version: &SemVer) -> Option<bool> {
+    let (op, rest) = ["<=", ">=", "<", ">", "=", "~", "^"]
+        .iter()
+        .find_map(|op| comparator.strip_prefix(op).map(|rest| (*op, rest.trim())))
+        .unwrap_or(("^", comparator));
+
+    let mut parts = vec![];
+    for part in rest.split('.') {
+        match part {
+            "*" | "x" | "X" => break,
+            _ => parts.push(part.parse::<u32>().ok()?),
+        }
+    }
+    if parts.is_empty() {
+        return Some(true);
+    }
+    if parts.len() > 3 {
+        return None;
+    }
+
+    let v = (version.major, version.minor, version.patch);
+    let lower = (
+        parts[0],
+        parts.get(1).copied().unwrap_or(0),
+        parts.get(2).copied().unwrap_or(0),
+    );
+    // The first version that no longer matches a partial or caret requirement.
+    let upper = match (op, parts.as_slice()) {
+        ("^", [0, 0, p]) => (0, 0, p + 1),
+        ("^", [0, m, ..]) => (0, m + 1, 0),
+        ("^" | "~" | "=", [major]) => (major + 1, 0, 0),
+        ("^", [major, ..]) => (major + 1, 0, 0),
+        ("~" | "=", [major, minor, ..]) => (*major, minor + 1, 0),
+        _ => lower,
+    };
+
+    Some(match op {
+        "=" if parts.len() == 3 => v == lower,
+        "^" | "~" | "=" => v >= lower && v < upper,
+        ">" => v > lower,
+        ">=" => v >= lower,
+        "<" => v < lower,
+        _ => v <= lower,
+    })
+}
+
+/// Describes how the install flags in `installed` differ from the ones `wanted`.
+fn option_differences(wanted: &ToolSpec, installed: &ToolSpec) -> Vec<String> {
+    let mut differences = vec![];
+
+    let source = |s: &ToolSpec| {
+        if let Some(git) = &s.git {
+            format!("git {git}")
+        } else if let Some(path) = &s.path {
+            format!("path {path}")
+        } else if let Some(index) = &s.index {
+            format!("registry {index}")
+        } else {
+            "crates.io".to_string()
+        }
+    };
+    let (want, have) = (source(wanted), source(installed));
+    if want != have {
+        differences.push(format!("source is {have}, wants {want}"));
+    } else if wanted.rev.is_some() && wanted.rev != installed.rev {
+        differences.push(format!(
+            "revision is {}, wants {}",
+            installed.rev.as_deref().unwrap_or("unknown"),
+            wanted.rev.as_deref().unwrap_or_default()
+        ));
+    }
+
+    let want: BTreeSet<&String> = wanted.features.iter().collect();
+    let have: BTreeSet<&String> = installed.features.iter().collect();
+    let mut features: Vec<String> = want.difference(&have).map(|f| format!("+{f}")).collect();
+    features.extend(have.difference(&want).map(|f| format!("-{f}")));
+    if !features.is_empty() {
+        differences.push(format!("features {}", features.join(" ")));
+    }
+    if wanted.all_features != installed.all_features {
+        differences.push(format!("all-features should be {}", wanted.all_features));
+    }
+    if wanted.no_default_features != installed.no_default_features {
+        differences.push(format!(
+            "no-default-features should be {}",
+            wanted.no_default_features
+        ));
+    }
+
+    let profile = |s: &ToolSpec| s.profile.clone().unwrap_or_else(|| "release".to_string());
+    if profile(wanted) != profile(installed) {
+        differences.push(format!(
+            "profile is {}, wants {}",
+            profile(installed),
+            profile(wanted)
+        ));
+    }
+
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DriftKind, DriftReport, satisfies};
+    use crate::depot::{Krate, Krates};
+    use crate::depotfile::{Depotfile, ToolSpec};
+    use pretty_assertions::assert_eq;
+    use versions::SemVer;
+
+    fn krate(name: &str, version: &str) -> Krate {
+        let mut k = Krate::default();
+        k.name = name.to_string();
+        k.version = SemVer::new(version).unwrap();
+        k
+    }
+
+    fn spec(version: &str) -> ToolSpec {
+        ToolSpec {
+            version: Some(version.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn check_version_requirements() {
+        let v = SemVer::new("14.1.1").unwrap();
+
+        assert!(satisfies("14.1.1", &v));
+        assert!(!satisfies("14.1.0", &v));
+        assert!(satisfies("^14", &v));
+        assert!(satisfies(">=14, <15", &v));
+        assert!(!satisfies("~14.0", &v));
+        assert!(satisfies("~14.1", &v));
+        assert!(satisfies("=14.1", &v));
+        assert!(satisfies("14.*", &v));
+        assert!(satisfies("*", &v));
+        assert!(!satisfies("<14.1.1", &v));
+        assert!(satisfies("^0.2", &SemVer::new("0.2.9").unwrap()));
+        assert!(!satisfies("^0.2", &SemVer::new("0.3.0").unwrap()));
+        assert!(!satisfies("^0.0.3", &SemVer::new("0.0.4").unwrap()));
+        assert!(!satisfies("not a version", &v));
+    }
+
+    #[test]
+    fn report_drift() {
+        let mut manifest = Depotfile::default();
+        manifest.crates.insert("bat".to_string(), spec("0.25.0"));
+        manifest
+            .crates
+            .insert("fd-find".to_string(), spec("10.2.0"));
+        manifest.crates.insert(
+            "ripgrep".to_string(),
+            ToolSpec {
+                features: vec!["pcre2".to_string()],
+                ..spec("^14")
+            },
+        );
+        let krates = Krates(vec![
+            krate("bat", "0.24.0"),
+            krate("ripgrep", "14.1.1"),
+            krate("tokei", "12.1.2"),
+        ]);
+        let mut installed = Depotfile::default();
+        installed.crates.insert("bat".to_string(), spec("0.24.0"));
+        installed.crates.insert(
+            "ripgrep".to_string(),
+            ToolSpec {
+                profile: Some("dev".to_string()),
+                ..spec("14.1.1")
+            },
+        );
+        installed.crates.insert("tokei".to_string(), spec("12.1.2"));
+
+        let report = DriftReport::between(&manifest, &krates, &installed);
+        let summary: Vec<(&str, DriftKind, &str)> = report
+            .0
+            .iter()
+            .map(|d| (d.name.as_str(), d.kind, d.detail.as_str()))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("fd-find", DriftKind::Missing, "10.2.0"),
+                ("tokei", DriftKind::Extra, "12.1.2"),
+                (
+                    "bat",
+                    DriftKind::Version,
+                    "0.24.0 installed, 0.25.0 required"
+                ),
+                (
+                    "ripgrep",
+                    DriftKind::Options,
+                    "features +pcre2; profile is dev, wants release"
+                ),
+            ]
+        );
+        assert_eq!(
+            report.to_string(),
+            "Missing locally (1)
+  fd-find                 10.2.0
+Not in the manifest (1)
+  tokei                   12.1.2
+Version mismatches (1)
+  bat                     0.24.0 installed, 0.25.0 required
+Option mismatches (1)
+  ripgrep                 features +pcre2; profile is dev, wants release
+"
+        );
+    }
+}
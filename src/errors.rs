@@ -1,43 +1,63 @@
-use std::{fmt, string::FromUtf8Error};
+//! The error type of every fallible function in the crate.
+
+use std::string::FromUtf8Error;
 use thiserror::Error;
 
+/// Everything that can go wrong in depot.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum Error {
+    /// Reading a file or running a process failed.
     #[error("failed to run command")]
     Cli(#[from] std::io::Error),
+    /// A command printed something that is not UTF-8.
     #[error("failed to run command")]
     FromUtf8(#[from] FromUtf8Error),
+    /// The output of a cargo command could not be parsed.
     #[error("failed to parse cargo command stdout: {0}")]
-    Parser(nom::Err<nom::error::Error<String>>),
+    Parser(String),
+    /// The output of `cargo info` could not be parsed.
+    #[error("failed to parse cargo info output: {0}")]
+    KrateInfo(String),
+    /// Formatting text failed.
     #[error("failed to create text")]
     DisplayFmt(#[from] std::fmt::Error),
+    /// Something that should not happen, described by the message.
     #[error("unexpected error occured for: {0}")]
     Unexpected(String),
-    #[error("operation failed: {0}")]
-    HandleKrate(ChannelError),
+    /// The TUI's event channel closed.
     #[error("failed to receive event")]
     ReceiveEvent,
+    /// A command exited unsuccessfully. Holds the command.
+    #[error("command failed: {0}")]
+    CommandFailed(String),
+    /// An operation was cancelled before it finished.
+    #[error("cancelled")]
+    Cancelled,
+    /// A command was killed after running for the given number of seconds.
+    #[error("command timed out after {1}s: {0}")]
+    Timeout(String, u64),
+    /// A TOML file could not be read.
+    #[error("failed to read toml: {0}")]
+    TomlDe(#[from] toml::de::Error),
+    /// A TOML file could not be written.
+    #[error("failed to write toml: {0}")]
+    TomlSer(#[from] toml::ser::Error),
+    /// JSON could not be read or written.
+    #[error("failed to read json: {0}")]
+    Json(#[from] serde_json::Error),
 }
 
-impl From<nom::Err<nom::error::Error<&str>>> for Error {
-    fn from(err: nom::Err<nom::error::Error<&str>>) -> Self {
-        Self::Parser(err.map_input(|input| input.into()))
-    }
-}
-
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum ChannelError {
-    UpdateKrate,
-    UninstallKrate,
-    KrateInfo,
-}
-
-impl fmt::Display for ChannelError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ChannelError::UpdateKrate => write!(f, "failed to update krate"),
-            ChannelError::UninstallKrate => write!(f, "failed to uninstall krate"),
-            ChannelError::KrateInfo => write!(f, "failed to fetch krate"),
-        }
+impl Error {
+    /// Describes where nom gave up, without leaking nom's types.
+    pub(crate) fn parser(err: nom::Err<nom::error::Error<&str>>) -> Self {
+        Self::Parser(match err {
+            nom::Err::Incomplete(_) => "unexpected end of output".into(),
+            nom::Err::Error(e) | nom::Err::Failure(e) => format!(
+                "{} at {:?}",
+                e.code.description(),
+                e.input.lines().next().unwrap_or_default()
+            ),
+        })
     }
 }
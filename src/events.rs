@@ -1,6 +1,7 @@
-use crate::{depot::DepotMessage, errors::Error};
+use crate::state::DepotMessage;
 use crossterm::event::Event as CrosstermEvent;
 use crossterm::event::EventStream;
+use depot_rs::errors::Error;
 use futures::{FutureExt, StreamExt};
 use std::time::Duration;
 use tokio::sync::mpsc;
@@ -0,0 +1,190 @@
+//! Commands from the `[hooks]` table of the config, run before and after every install, update
+//! and uninstall, e.g. to regenerate shell completions when a tool changes.
+//!
+//! Hooks run with `sh -c` (`cmd /C` on Windows) and get these environment variables:
+//!
+//! - `DEPOT_OPERATION`: `install`, `update` or `uninstall`.
+//! - `DEPOT_CRATE`: the crate's name.
+//! - `DEPOT_OLD_VERSION`: the version installed before, empty for a new crate.
+//! - `DEPOT_NEW_VERSION`: the version installed after, empty after an uninstall. Before an
+//!   operation, the version it is expected to install, if known.
+//! - `DEPOT_BINARIES`: the paths of the crate's binaries, separated like `PATH`. Before an
+//!   install or update these are the ones already installed, after an uninstall the ones that
+//!   were removed.
+//!
+//! A failing pre-hook aborts the operation before cargo runs. Post-hooks only run after an
+//! operation succeeded, and one that fails is reported as a warning. Hooks are run by
+//! [`Journal::run`](crate::journal::Journal::run), which keeps their output in the operation's
+//! log.
+
+use crate::commands::run_hook;
+use crate::config::{Config, HooksConfig};
+use crate::errors::Error;
+use crate::operation::OperationKind;
+use crate::paths;
+use crate::tracking::InstallTracking;
+use std::env;
+use std::time::Duration;
+use versions::SemVer;
+
+/// The hooks of the config, ready to run.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Hooks {
+    config: HooksConfig,
+}
+
+impl Hooks {
+    /// The hooks set in the config.
+    pub fn load() -> Result<Self, Error> {
+        Ok(Self::new(Config::load()?.hooks))
+    }
+
+    /// The hooks of `config`.
+    pub fn new(config: HooksConfig) -> Self {
+        Self { config }
+    }
+
+    /// Runs the pre-hooks of `kind`, stopping at the first one that fails.
+    pub async fn pre(&self, kind: OperationKind, env: &HookEnv) -> Result<(), Error> {
+        for script in self.config.pre(kind) {
+            run_hook(&format!("pre-{kind}"), script, &env.0, self.timeout()).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs every post-hook of `kind`, giving a warning for each one that fails.
+    pub async fn post(&self, kind: OperationKind, env: &HookEnv) -> Vec<String> {
+        let mut warnings = vec![];
+        for script in self.config.post(kind) {
+            if let Err(e) = run_hook(&format!("post-{kind}"), script, &env.0, self.timeout()).await
+            {
+                warnings.push(e.to_string());
+            }
+        }
+
+        warnings
+    }
+
+    fn timeout(&self) -> Duration {
+        Duration::from_secs(self.config.timeout_secs)
+    }
+}
+
+/// The environment variables described in the [module docs](self).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookEnv(Vec<(&'static str, String)>);
+
+impl HookEnv {
+    /// The environment for an operation of `kind` on `krate`, with the binaries `tracking` has
+    /// for it. Tracking data that could not be read leaves the binaries empty.
+    pub fn new(
+        kind: OperationKind,
+        krate: &str,
+        from: Option<&SemVer>,
+        to: Option<&SemVer>,
+        tracking: Option<&InstallTracking>,
+    ) -> Self {
+        let version = |v: Option<&SemVer>| v.map(|v| v.to_string()).unwrap_or_default();
+        let bin_dir = paths::cargo_bin_dir().unwrap_or_default();
+        let binaries: Vec<_> = tracking
+            .and_then(|t| t.get(krate))
+            .map(|t| t.bins.iter().map(|b| bin_dir.join(b)).collect())
+            .unwrap_or_default();
+        // Only fails for a path with the separator in it, which cargo would not install.
+        let binaries = env::join_paths(binaries).unwrap_or_default();
+
+        Self(vec![
+            ("DEPOT_OPERATION", kind.to_string()),
+            ("DEPOT_CRATE", krate.to_string()),
+            ("DEPOT_OLD_VERSION", version(from)),
+            ("DEPOT_NEW_VERSION", version(to)),
+            ("DEPOT_BINARIES", binaries.to_string_lossy().into_owned()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HookEnv, Hooks};
+    use crate::config::HooksConfig;
+    use crate::operation::OperationKind;
+    use crate::tracking::{InstallTracking, TrackedInstall};
+    use pretty_assertions::assert_eq;
+    use versions::SemVer;
+
+    fn env() -> HookEnv {
+        let version = SemVer::new("0.24.0").unwrap();
+        let mut tracking = InstallTracking::default();
+        let install = TrackedInstall {
+            bins: ["bat".to_string()].into(),
+            ..Default::default()
+        };
+        tracking.insert("bat", &version, install);
+
+        HookEnv::new(
+            OperationKind::Uninstall,
+            "bat",
+            Some(&version),
+            None,
+            Some(&tracking),
+        )
+    }
+
+    #[tokio::test]
+    async fn failing_pre_hook_stops() {
+        let hooks = Hooks::new(HooksConfig {
+            pre_uninstall: vec!["exit 1".to_string(), "exit 0".to_string()],
+            ..Default::default()
+        });
+
+        let result = hooks.pre(OperationKind::Uninstall, &env()).await;
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "command failed: pre-uninstall hook `exit 1`"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn post_hooks_get_versions_and_warn() {
+        let out = std::env::temp_dir().join(format!("depot-hooks-{}", std::process::id()));
+        let _ = std::fs::remove_file(&out);
+        let hooks = Hooks::new(HooksConfig {
+            post_uninstall: vec![
+                "exit 3".to_string(),
+                format!(
+                    "echo \"$DEPOT_OPERATION $DEPOT_CRATE $DEPOT_OLD_VERSION-$DEPOT_NEW_VERSION $(basename \"$DEPOT_BINARIES\")\" > {}",
+                    out.display()
+                ),
+            ],
+            ..Default::default()
+        });
+
+        let warnings = hooks.post(OperationKind::Uninstall, &env()).await;
+
+        assert_eq!(
+            warnings,
+            vec!["command failed: post-uninstall hook `exit 3`"]
+        );
+        assert_eq!(
+            std::fs::read_to_string(&out).unwrap(),
+            "uninstall bat 0.24.0- bat\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn hooks_time_out() {
+        let hooks = Hooks::new(HooksConfig {
+            pre_install: vec!["sleep 5".to_string()],
+            timeout_secs: 0,
+            ..Default::default()
+        });
+
+        let result = hooks.pre(OperationKind::Install, &env()).await;
+
+        assert!(matches!(result, Err(crate::errors::Error::Timeout(_, 0))));
+    }
+}
@@ -0,0 +1,210 @@
+//! How a crate is installed: built from source with `cargo install`, or downloaded with
+//! `cargo binstall`.
+
+use crate::commands::{binstall_available, binstall_crate, install_crate};
+use crate::errors::Error;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+
+/// Decides how a crate should be installed.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum InstallStrategy {
+    /// Use `cargo binstall` when it is available and fall back to `cargo install`.
+    PreferPrebuilt,
+    /// Always compile with `cargo install`.
+    #[default]
+    SourceOnly,
+    /// Only use `cargo binstall`, failing when there is no prebuilt artifact.
+    PrebuiltOnly,
+}
+
+/// How a crate ended up being installed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum InstallMethod {
+    /// Built with `cargo install`.
+    Source,
+    /// Downloaded with `cargo binstall`.
+    Prebuilt,
+}
+
+impl Display for InstallMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InstallMethod::Source => write!(f, "built from source"),
+            InstallMethod::Prebuilt => write!(f, "prebuilt binary"),
+        }
+    }
+}
+
+/// The commands used to install a crate.
+pub(crate) trait Installer {
+    /// Whether prebuilt binaries can be installed at all.
+    async fn prebuilt_available(&self) -> bool;
+
+    /// Installs a prebuilt binary. Returns `false` when there is no artifact for the crate.
+    async fn install_prebuilt(&self, name: &str) -> Result<bool, Error>;
+
+    /// Compiles and installs the crate from source.
+    async fn install_source(&self, name: &str) -> Result<(), Error>;
+}
+
+/// Installs crates with `cargo binstall` and `cargo install`.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct CargoInstaller;
+
+impl Installer for CargoInstaller {
+    async fn prebuilt_available(&self) -> bool {
+        binstall_available().await
+    }
+
+    async fn install_prebuilt(&self, name: &str) -> Result<bool, Error> {
+        binstall_crate(name).await
+    }
+
+    async fn install_source(&self, name: &str) -> Result<(), Error> {
+        install_crate(name).await
+    }
+}
+
+/// Installs `name` with `installer` according to `strategy`.
+pub(crate) async fn install<I: Installer>(
+    installer: &I,
+    name: &str,
+    strategy: InstallStrategy,
+) -> Result<InstallMethod, Error> {
+    match strategy {
+        InstallStrategy::SourceOnly => {}
+        InstallStrategy::PreferPrebuilt => {
+            if installer.prebuilt_available().await && installer.install_prebuilt(name).await? {
+                return Ok(InstallMethod::Prebuilt);
+            }
+        }
+        InstallStrategy::PrebuiltOnly => {
+            if !installer.prebuilt_available().await {
+                return Err(Error::CommandFailed(
+                    "cargo binstall is not installed".to_string(),
+                ));
+            }
+            if installer.install_prebuilt(name).await? {
+                return Ok(InstallMethod::Prebuilt);
+            }
+            return Err(Error::CommandFailed(format!(
+                "no prebuilt binary found for {name}"
+            )));
+        }
+    }
+
+    installer.install_source(name).await?;
+    Ok(InstallMethod::Source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InstallMethod, InstallStrategy, Installer, install};
+    use crate::errors::Error;
+    use std::sync::Mutex;
+
+    /// An [`Installer`] that records the calls made to it instead of running cargo.
+    #[derive(Debug, Default)]
+    struct FakeInstaller {
+        binstall: bool,
+        artifacts: Vec<String>,
+        calls: Mutex<Vec<String>>,
+    }
+
+    impl FakeInstaller {
+        fn calls(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    impl Installer for FakeInstaller {
+        async fn prebuilt_available(&self) -> bool {
+            self.binstall
+        }
+
+        async fn install_prebuilt(&self, name: &str) -> Result<bool, Error> {
+            self.calls.lock().unwrap().push(format!("binstall {name}"));
+            Ok(self.artifacts.iter().any(|a| a == name))
+        }
+
+        async fn install_source(&self, name: &str) -> Result<(), Error> {
+            self.calls.lock().unwrap().push(format!("install {name}"));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn source_only_never_uses_binstall() {
+        let fake = FakeInstaller {
+            binstall: true,
+            artifacts: vec!["bat".to_string()],
+            ..Default::default()
+        };
+        let method = install(&fake, "bat", InstallStrategy::SourceOnly)
+            .await
+            .unwrap();
+
+        assert_eq!(method, InstallMethod::Source);
+        assert_eq!(fake.calls(), vec!["install bat"]);
+    }
+
+    #[tokio::test]
+    async fn prefer_prebuilt_uses_artifact() {
+        let fake = FakeInstaller {
+            binstall: true,
+            artifacts: vec!["bat".to_string()],
+            ..Default::default()
+        };
+        let method = install(&fake, "bat", InstallStrategy::PreferPrebuilt)
+            .await
+            .unwrap();
+
+        assert_eq!(method, InstallMethod::Prebuilt);
+        assert_eq!(fake.calls(), vec!["binstall bat"]);
+    }
+
+    #[tokio::test]
+    async fn prefer_prebuilt_falls_back_to_source() {
+        let fake = FakeInstaller {
+            binstall: true,
+            ..Default::default()
+        };
+        let method = install(&fake, "bat", InstallStrategy::PreferPrebuilt)
+            .await
+            .unwrap();
+
+        assert_eq!(method, InstallMethod::Source);
+        assert_eq!(fake.calls(), vec!["binstall bat", "install bat"]);
+    }
+
+    #[tokio::test]
+    async fn prefer_prebuilt_without_binstall() {
+        let fake = FakeInstaller::default();
+        let method = install(&fake, "bat", InstallStrategy::PreferPrebuilt)
+            .await
+            .unwrap();
+
+        assert_eq!(method, InstallMethod::Source);
+        assert_eq!(fake.calls(), vec!["install bat"]);
+    }
+
+    #[tokio::test]
+    async fn prebuilt_only_fails_without_artifact() {
+        let fake = FakeInstaller {
+            binstall: true,
+            ..Default::default()
+        };
+
+        assert!(
+            install(&fake, "bat", InstallStrategy::PrebuiltOnly)
+                .await
+                .is_err()
+        );
+        assert_eq!(fake.calls(), vec!["binstall bat"]);
+    }
+}
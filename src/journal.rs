@@ -0,0 +1,597 @@
+//! The journal of every install, update and uninstall, kept as JSON lines with a log of each
+//! operation's output.
+
+use crate::backend::CargoBackend;
+use crate::commands::{Invocation, capture};
+use crate::depot::format_date;
+use crate::errors::Error;
+use crate::hooks::{HookEnv, Hooks};
+use crate::operation::{OperationKind, Outcome};
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use std::fmt::Display;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
+use std::pin::{Pin, pin};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use versions::SemVer;
+
+const JOURNAL_FILE: &str = "journal.jsonl";
+/// Where the output of the commands run by each operation is kept, next to the journal.
+const LOGS_DIR: &str = "logs";
+
+/// What an operation did to a crate, as far as the journal is concerned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum JournalAction {
+    /// A crate that was not installed before.
+    Install,
+    /// A newer version of an installed crate, or the same one again.
+    Update,
+    /// An install or update that went back to an older version.
+    Rollback,
+    /// A crate that was removed.
+    Uninstall,
+}
+
+impl JournalAction {
+    fn of(kind: OperationKind, from: Option<&SemVer>, to: Option<&SemVer>) -> Self {
+        match (kind, from, to) {
+            (OperationKind::Uninstall, _, _) => Self::Uninstall,
+            (_, Some(from), Some(to)) if to < from => Self::Rollback,
+            (_, Some(_), _) => Self::Update,
+            (_, None, _) => Self::Install,
+        }
+    }
+}
+
+impl Display for JournalAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Install => write!(f, "install"),
+            Self::Update => write!(f, "update"),
+            Self::Rollback => write!(f, "rollback"),
+            Self::Uninstall => write!(f, "uninstall"),
+        }
+    }
+}
+
+/// One line of the journal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct JournalEntry {
+    /// When the operation started, in seconds since the Unix epoch.
+    pub at: u64,
+    /// What the operation did.
+    pub action: JournalAction,
+    /// The crate it was done to.
+    #[serde(rename = "crate")]
+    pub krate: String,
+    /// The version installed before, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    /// The version installed after, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<String>,
+    /// The last cargo command that was run, e.g. `cargo install ripgrep --locked`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    /// How long the operation took, hooks included.
+    pub duration_secs: u64,
+    /// The exit code of the last cargo command. `None` when it was killed or nothing was run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Why the operation failed. `None` when it succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// What went wrong without failing the operation, e.g. a post-hook.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// The output of every command and hook that was run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log: Option<PathBuf>,
+}
+
+impl JournalEntry {
+    /// Whether the operation succeeded.
+    pub fn succeeded(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Whether the operation was cancelled before it finished.
+    pub fn cancelled(&self) -> bool {
+        self.error.as_deref() == Some(&Error::Cancelled.to_string())
+    }
+
+    /// When the operation started in UTC, e.g. `2025-03-31 14:05`.
+    pub fn at_str(&self) -> String {
+        let at = UNIX_EPOCH + Duration::from_secs(self.at);
+        let minutes = self.at / 60 % (24 * 60);
+        format!(
+            "{} {:02}:{:02}",
+            format_date(at),
+            minutes / 60,
+            minutes % 60
+        )
+    }
+
+    /// The versions involved, e.g. `14.1.0 -> 14.1.1`.
+    pub fn versions_str(&self) -> String {
+        match (&self.from, &self.to) {
+            (Some(from), Some(to)) if from != to => format!("{from} -> {to}"),
+            (Some(v), _) | (None, Some(v)) => v.clone(),
+            (None, None) => String::new(),
+        }
+    }
+}
+
+/// The append-only record of every install, update, rollback and uninstall, kept as JSON lines in
+/// `<data dir>/journal.jsonl`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Journal {
+    /// Nothing is recorded without a directory.
+    dir: Option<PathBuf>,
+}
+
+impl Journal {
+    /// The journal in depot's data directory.
+    pub fn open() -> Self {
+        Self::new(paths::data_dir())
+    }
+
+    /// The journal in `dir`.
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self { dir }
+    }
+
+    /// Every entry, oldest first. Lines that can't be read, e.g. one cut short by a crash, are
+    /// skipped.
+    pub fn entries(&self) -> Result<Vec<JournalEntry>, Error> {
+        let Some(path) = self.path() else {
+            return Ok(vec![]);
+        };
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+
+        Ok(fs::read_to_string(path)?
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Adds `entry` at the end of the journal.
+    pub fn append(&self, entry: &JournalEntry) -> Result<(), Error> {
+        let Some(path) = self.path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+
+        Ok(())
+    }
+
+    /// Runs `operation` on `krate` between its [hooks](crate::hooks) and appends it to the
+    /// journal, along with the log of the commands and hooks it ran. The versions before and
+    /// after are read from cargo's tracking data. `target` is the version the operation is
+    /// expected to install, if known, which the pre-hooks are told.
+    ///
+    /// Journaling is best-effort: when the tracking data can't be read or the journal can't be
+    /// written, the operation still runs and keeps its result, with a warning. The warnings of
+    /// the operation itself are journaled and given back first.
+    pub async fn run<T>(
+        &self,
+        backend: &dyn CargoBackend,
+        hooks: &Hooks,
+        kind: OperationKind,
+        krate: &str,
+        target: Option<&SemVer>,
+        operation: impl Future<Output = Outcome<T>>,
+    ) -> Outcome<T> {
+        let never = std::future::pending();
+        self.run_cancellable(backend, hooks, kind, krate, target, operation, never)
+            .await
+    }
+
+    /// Like [`Journal::run`], but stops the pre-hooks or the operation when `cancelled`
+    /// completes first, killing whatever they run. The operation then fails with
+    /// [`Error::Cancelled`] and is journaled with the log of what ran until then.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn run_cancellable<T>(
+        &self,
+        backend: &dyn CargoBackend,
+        hooks: &Hooks,
+        kind: OperationKind,
+        krate: &str,
+        target: Option<&SemVer>,
+        operation: impl Future<Output = Outcome<T>>,
+        cancelled: impl Future<Output = ()>,
+    ) -> Outcome<T> {
+        let mut cancelled = pin!(cancelled);
+        let at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let before = backend.tracking().await.ok();
+        let from = before.as_ref().and_then(|t| t.version(krate));
+        let started = Instant::now();
+
+        let env = HookEnv::new(kind, krate, from.as_ref(), target, before.as_ref());
+        let pre = unless_cancelled(cancelled.as_mut(), hooks.pre(kind, &env));
+        let (pre, mut invocations) = capture(pre).await;
+        // The hooks are in the log, but the entry tells about the commands cargo ran.
+        let (outcome, ran) = match pre.unwrap_or(Err(Error::Cancelled)) {
+            Ok(()) => {
+                let operation = unless_cancelled(cancelled, operation);
+                let (outcome, ran) = capture(operation).await;
+                (outcome.unwrap_or_else(|| Err(Error::Cancelled).into()), ran)
+            }
+            Err(e) => (Err(e).into(), vec![]),
+        };
+        let Outcome {
+            result,
+            mut warnings,
+        } = outcome;
+        let last = ran.last().cloned();
+        invocations.extend(ran);
+
+        let after = backend.tracking().await.ok();
+        let to = after.as_ref().and_then(|t| t.version(krate));
+        if result.is_ok() {
+            let binaries = match kind {
+                OperationKind::Uninstall => before.as_ref(),
+                _ => after.as_ref(),
+            };
+            let env = HookEnv::new(kind, krate, from.as_ref(), to.as_ref(), binaries);
+            let (failed, ran) = capture(hooks.post(kind, &env)).await;
+            warnings.extend(failed);
+            invocations.extend(ran);
+        }
+        let duration_secs = started.elapsed().as_secs();
+
+        let log = self.write_log(at, krate, &invocations).unwrap_or_else(|e| {
+            warnings.push(format!("could not save the log: {e}"));
+            None
+        });
+        let entry = JournalEntry {
+            at,
+            action: JournalAction::of(kind, from.as_ref(), to.as_ref()),
+            krate: krate.to_string(),
+            from: from.map(|v| v.to_string()),
+            to: to.map(|v| v.to_string()),
+            command: last.as_ref().map(|i| i.command.clone()),
+            duration_secs,
+            exit_code: last.and_then(|i| i.exit_code),
+            error: result.as_ref().err().map(|e| e.to_string()),
+            warnings: warnings.clone(),
+            log,
+        };
+        if let Err(e) = self.append(&entry) {
+            warnings.push(format!("could not write the journal: {e}"));
+        }
+
+        Outcome { result, warnings }
+    }
+
+    /// Saves the output of `invocations` as `logs/<at>-<krate>.log`, if anything was run. Another
+    /// operation on the crate in the same second, e.g. a quick retry, gets a numbered suffix.
+    fn write_log(
+        &self,
+        at: u64,
+        krate: &str,
+        invocations: &[Invocation],
+    ) -> Result<Option<PathBuf>, Error> {
+        let Some(dir) = &self.dir else {
+            return Ok(None);
+        };
+        if invocations.is_empty() {
+            return Ok(None);
+        }
+
+        let mut log = String::new();
+        for invocation in invocations {
+            log.push_str(&format!("$ {}\n", invocation.command));
+            log.push_str(&invocation.stdout);
+            log.push_str(&invocation.stderr);
+            match invocation.exit_code {
+                Some(code) => log.push_str(&format!("exit code {code}\n\n")),
+                None => log.push_str("killed\n\n"),
+            }
+        }
+        let dir = dir.join(LOGS_DIR);
+        fs::create_dir_all(&dir)?;
+        let mut n = 0;
+        let (path, mut file) = loop {
+            let name = match n {
+                0 => format!("{at}-{krate}.log"),
+                n => format!("{at}-{krate}-{n}.log"),
+            };
+            let path = dir.join(name);
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(file) => break (path, file),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => n += 1,
+                Err(e) => return Err(e.into()),
+            }
+        };
+        file.write_all(log.as_bytes())?;
+
+        Ok(Some(path))
+    }
+
+    fn path(&self) -> Option<PathBuf> {
+        self.dir.as_ref().map(|d| d.join(JOURNAL_FILE))
+    }
+}
+
+/// Runs `f` unless `cancelled` completes first, which drops it and gives `None`.
+async fn unless_cancelled<T>(
+    cancelled: Pin<&mut impl Future<Output = ()>>,
+    f: impl Future<Output = T>,
+) -> Option<T> {
+    tokio::select! {
+        biased;
+        () = cancelled => None,
+        output = f => Some(output),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Journal, JournalAction, JournalEntry};
+    use crate::backend::CargoBackend;
+    use crate::backend::fake::FakeBackend;
+    use crate::commands::Invocation;
+    use crate::config::HooksConfig;
+    use crate::errors::Error;
+    use crate::hooks::Hooks;
+    use crate::operation::{OperationKind, Outcome};
+    use futures::FutureExt;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use std::time::Duration;
+    use versions::SemVer;
+
+    fn journal(name: &str) -> Journal {
+        let dir = std::env::temp_dir().join(format!("depot-journal-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        Journal::new(Some(dir))
+    }
+
+    #[test]
+    fn classify_actions() {
+        let v = |s| SemVer::new(s).unwrap();
+
+        assert_eq!(
+            JournalAction::of(OperationKind::Install, None, Some(&v("1.0.0"))),
+            JournalAction::Install
+        );
+        assert_eq!(
+            JournalAction::of(OperationKind::Update, Some(&v("1.0.0")), Some(&v("1.1.0"))),
+            JournalAction::Update
+        );
+        assert_eq!(
+            JournalAction::of(OperationKind::Install, Some(&v("1.1.0")), Some(&v("1.0.0"))),
+            JournalAction::Rollback
+        );
+        assert_eq!(
+            JournalAction::of(OperationKind::Uninstall, Some(&v("1.0.0")), None),
+            JournalAction::Uninstall
+        );
+    }
+
+    #[tokio::test]
+    async fn journal_records_operations() {
+        let journal = journal("records");
+        let backend = FakeBackend::default()
+            .with_installed("bat", "0.24.0")
+            .with_installed("ripgrep", "14.1.0")
+            .failing("ripgrep");
+
+        journal
+            .run(
+                &backend,
+                &Hooks::default(),
+                OperationKind::Uninstall,
+                "bat",
+                None,
+                backend.uninstall("bat").map(Outcome::from),
+            )
+            .await
+            .result
+            .unwrap();
+        let failed = journal
+            .run(
+                &backend,
+                &Hooks::default(),
+                OperationKind::Uninstall,
+                "ripgrep",
+                None,
+                backend.uninstall("ripgrep").map(Outcome::from),
+            )
+            .await;
+        assert!(failed.result.is_err());
+        assert!(failed.warnings.is_empty());
+
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].action, JournalAction::Uninstall);
+        assert_eq!(entries[0].krate, "bat");
+        assert_eq!(entries[0].from.as_deref(), Some("0.24.0"));
+        assert_eq!(entries[0].to, None);
+        assert!(entries[0].succeeded());
+        assert_eq!(entries[1].to.as_deref(), Some("14.1.0"));
+        assert!(!entries[1].succeeded());
+    }
+
+    #[tokio::test]
+    async fn journal_failures_keep_the_result() {
+        // A file where the data directory should be, so nothing can be written.
+        let dir = std::env::temp_dir().join(format!("depot-journal-file-{}", std::process::id()));
+        fs::write(&dir, "").unwrap();
+        let journal = Journal::new(Some(dir));
+        let backend = FakeBackend::default().with_installed("bat", "0.24.0");
+
+        let outcome = journal
+            .run(
+                &backend,
+                &Hooks::default(),
+                OperationKind::Uninstall,
+                "bat",
+                None,
+                backend.uninstall("bat").map(Outcome::from),
+            )
+            .await;
+
+        assert!(outcome.result.is_ok());
+        assert_eq!(outcome.warnings.len(), 1);
+        assert!(outcome.warnings[0].starts_with("could not write the journal"));
+        assert_eq!(backend.calls(), vec!["uninstall bat"]);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn journal_runs_hooks() {
+        let journal = journal("hooks");
+        let backend = FakeBackend::default()
+            .with_installed("bat", "0.24.0")
+            .with_installed("ripgrep", "14.1.0");
+        let hooks = Hooks::new(HooksConfig {
+            pre_uninstall: vec!["test \"$DEPOT_CRATE\" != ripgrep".to_string()],
+            post_uninstall: vec!["echo done; exit 1".to_string()],
+            ..Default::default()
+        });
+
+        let bat = journal
+            .run(
+                &backend,
+                &hooks,
+                OperationKind::Uninstall,
+                "bat",
+                None,
+                backend.uninstall("bat").map(Outcome::from),
+            )
+            .await;
+        let ripgrep = journal
+            .run(
+                &backend,
+                &hooks,
+                OperationKind::Uninstall,
+                "ripgrep",
+                None,
+                backend.uninstall("ripgrep").map(Outcome::from),
+            )
+            .await;
+
+        // A failing post-hook is a warning, a failing pre-hook stops the operation.
+        assert!(bat.result.is_ok());
+        assert_eq!(
+            bat.warnings,
+            vec!["command failed: post-uninstall hook `echo done; exit 1`"]
+        );
+        assert!(ripgrep.result.is_err());
+        assert_eq!(backend.calls(), vec!["uninstall bat"]);
+
+        let entries = journal.entries().unwrap();
+        assert!(entries[0].succeeded());
+        assert_eq!(entries[0].warnings, bat.warnings);
+        // Hooks are no cargo command, but their output is in the log.
+        assert_eq!(entries[0].command, None);
+        let log = fs::read_to_string(entries[0].log.as_ref().unwrap()).unwrap();
+        assert!(log.contains("$ echo done; exit 1\ndone\n"));
+        assert!(!entries[1].succeeded());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn journal_records_cancelled_operations() {
+        let journal = journal("cancelled");
+        let backend = FakeBackend::default().with_installed("bat", "0.24.0");
+        let hooks = Hooks::new(HooksConfig {
+            pre_uninstall: vec!["sleep 5".to_string()],
+            ..Default::default()
+        });
+
+        let outcome = journal
+            .run_cancellable(
+                &backend,
+                &hooks,
+                OperationKind::Uninstall,
+                "bat",
+                None,
+                backend.uninstall("bat").map(Outcome::from),
+                tokio::time::sleep(Duration::from_millis(100)),
+            )
+            .await;
+
+        assert!(matches!(outcome.result, Err(Error::Cancelled)));
+        assert!(backend.calls().is_empty());
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert!(entries[0].cancelled());
+        assert_eq!(entries[0].from.as_deref(), Some("0.24.0"));
+        assert_eq!(entries[0].to.as_deref(), Some("0.24.0"));
+        // The hook was killed, and the log says so.
+        let log = fs::read_to_string(entries[0].log.as_ref().unwrap()).unwrap();
+        assert_eq!(log, "$ sleep 5\nkilled\n\n");
+    }
+
+    #[test]
+    fn logs_are_not_overwritten() {
+        let journal = journal("logs");
+        let invocation = |exit_code| Invocation {
+            command: "cargo install ripgrep --locked".to_string(),
+            exit_code: Some(exit_code),
+            stdout: String::new(),
+            stderr: String::new(),
+        };
+
+        let failed = journal
+            .write_log(1_743_429_900, "ripgrep", &[invocation(101)])
+            .unwrap()
+            .unwrap();
+        let retried = journal
+            .write_log(1_743_429_900, "ripgrep", &[invocation(0)])
+            .unwrap()
+            .unwrap();
+
+        assert_ne!(failed, retried);
+        assert!(
+            fs::read_to_string(failed)
+                .unwrap()
+                .contains("exit code 101")
+        );
+        assert!(fs::read_to_string(retried).unwrap().contains("exit code 0"));
+    }
+
+    #[test]
+    fn journal_skips_unreadable_lines() {
+        let journal = journal("unreadable");
+        let entry = JournalEntry {
+            at: 1_743_429_900,
+            action: JournalAction::Update,
+            krate: "ripgrep".to_string(),
+            from: Some("14.1.0".to_string()),
+            to: Some("14.1.1".to_string()),
+            command: Some("cargo install ripgrep --locked".to_string()),
+            duration_secs: 42,
+            exit_code: Some(0),
+            error: None,
+            warnings: vec![],
+            log: None,
+        };
+        journal.append(&entry).unwrap();
+        let path = journal.path().unwrap();
+        let mut s = fs::read_to_string(&path).unwrap();
+        s.push_str("{\"at\":17434");
+        fs::write(&path, s).unwrap();
+
+        assert_eq!(journal.entries().unwrap(), vec![entry.clone()]);
+        assert_eq!(entry.at_str(), "2025-03-31 14:05");
+        assert_eq!(entry.versions_str(), "14.1.0 -> 14.1.1");
+    }
+}
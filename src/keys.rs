@@ -1,9 +1,17 @@
 use crate::app::App;
-use crate::errors::Error;
 use crate::ui::views::catalog_view::Catalog;
+use crate::ui::views::cleanup_view::Cleanup;
+use crate::ui::views::doctor_view::Doctor;
+use crate::ui::views::drift_view::Drift;
+use crate::ui::views::error_view::ErrorView;
+use crate::ui::views::history_view::History;
+use crate::ui::views::import_view::Import;
+use crate::ui::views::install_view::Install;
+use crate::ui::views::plan_view::Plan;
 use crate::ui::views::update_view::Update;
 use crate::ui::views::{View, start_view::Start};
 use crossterm::event::KeyEvent;
+use depot_rs::errors::Error;
 
 /// Handles the key events and updates the state of [`App`].
 pub async fn key_handler(app: &mut App, key: KeyEvent) -> Result<(), Error> {
@@ -11,6 +19,14 @@ pub async fn key_handler(app: &mut App, key: KeyEvent) -> Result<(), Error> {
         View::Start(_) => Start::select(app, &key).await?,
         View::Catalog(_) => Catalog::select(app, &key).await?,
         View::Update(_) => Update::select(app, &key).await?,
+        View::Plan(_) => Plan::select(app, &key).await?,
+        View::Install(_) => Install::select(app, &key).await?,
+        View::Cleanup(_) => Cleanup::select(app, &key).await?,
+        View::Import(_) => Import::select(app, &key).await?,
+        View::Drift(_) => Drift::select(app, &key).await?,
+        View::Doctor(_) => Doctor::select(app, &key).await?,
+        View::History(_) => History::select(app, &key).await?,
+        View::Error(_) => ErrorView::select(app, &key).await?,
     }
 
     Ok(())
@@ -0,0 +1,59 @@
+//! Listing, checking, installing and uninstalling the tools installed with `cargo install`.
+//!
+//! This is the library behind the `depot` TUI and CLI. Cargo is always run through a
+//! [`CargoBackend`], so that tools embedding depot can swap in [`backend::fake::FakeBackend`] in
+//! tests.
+//!
+//! ```no_run
+//! use depot_rs::{CargoCli, Depot};
+//!
+//! # async fn example() -> Result<(), depot_rs::Error> {
+//! let mut depot = Depot::get(&CargoCli).await?;
+//! depot.fetch_info(&CargoCli).await?;
+//! for krate in depot.get_outdated_krates()?.0 {
+//!     println!("{} {} -> {}", krate.name, krate.version, krate.latest_version());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Cargo runs as a tokio process. It is killed when it outlives the timeouts in the `[process]`
+//! table of the config, or when the future awaiting it is dropped, and at most
+//! `process.max_concurrent` processes run at once, plus `process.max_concurrent_installs`
+//! installs.
+//!
+//! The public items follow semver: they are only removed or changed in a breaking way in a new
+//! major version (or minor version while below 1.0). Structs and enums that may gain fields or
+//! variants are `#[non_exhaustive]`, so adding one is not a breaking change. The JSON documents
+//! in [`schema`] are versioned separately with [`schema::SCHEMA_VERSION`].
+
+#![warn(missing_docs)]
+
+pub mod backend;
+pub mod check;
+pub mod cleanup;
+mod commands;
+pub mod config;
+pub mod conflicts;
+pub mod depot;
+pub mod depotfile;
+pub mod doctor;
+pub mod drift;
+pub mod errors;
+pub mod hooks;
+pub mod installer;
+pub mod journal;
+pub mod operation;
+mod parser;
+mod paths;
+pub mod plan;
+pub mod policy;
+pub mod records;
+pub mod schema;
+pub mod search;
+pub mod tracking;
+mod usage;
+
+pub use backend::{CargoBackend, CargoCli, InstallRequest};
+pub use depot::{Depot, Krate, KrateInfo, KrateMetadata, Krates};
+pub use errors::Error;
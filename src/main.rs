@@ -1,17 +1,45 @@
-use crate::errors::Error;
+use crate::cli::Cli;
+use clap::Parser;
+use depot_rs::errors::Error;
+use std::io::IsTerminal;
+use std::process::ExitCode;
 mod app;
-mod commands;
-mod depot;
-mod errors;
+mod cli;
 mod events;
 mod keys;
-mod parser;
+mod state;
 mod ui;
 
+/// What depot exits with when a command fails. Commands that report findings, e.g. drift or
+/// policy violations, exit with 1 or 2, and `depot check` also exits with 3 when it could not be
+/// completed.
+const ERROR_EXIT_CODE: u8 = 3;
+
 #[tokio::main]
-async fn main() -> Result<(), Error> {
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("Error: {e}");
+            ExitCode::from(ERROR_EXIT_CODE)
+        }
+    }
+}
+
+async fn run() -> Result<(), Error> {
+    if let Some(command) = Cli::parse().command {
+        return command.run().await;
+    }
+    if !std::io::stdout().is_terminal() {
+        return Err(Error::Unexpected(
+            "stdout is not a terminal, run one of the commands in `depot --help` instead"
+                .to_string(),
+        ));
+    }
+
+    let app = app::App::new().await?;
     let terminal = ratatui::init();
-    let result = app::App::new().run(terminal).await;
+    let result = app.run(terminal).await;
     ratatui::restore();
 
     result
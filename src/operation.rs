@@ -0,0 +1,352 @@
+//! Installs, updates and uninstalls that are running or have finished.
+
+use crate::commands;
+use crate::errors::Error;
+use std::collections::HashSet;
+use std::fmt::Display;
+
+/// What an operation does to a crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OperationKind {
+    /// Installs a crate that is not installed.
+    Install,
+    /// Installs the latest version of an installed crate.
+    Update,
+    /// Removes a crate.
+    Uninstall,
+}
+
+impl OperationKind {
+    /// Describes the operation while it runs, e.g. `updating`.
+    pub fn progressive(&self) -> &'static str {
+        match self {
+            Self::Install => "installing",
+            Self::Update => "updating",
+            Self::Uninstall => "uninstalling",
+        }
+    }
+}
+
+impl Display for OperationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Install => write!(f, "install"),
+            Self::Update => write!(f, "update"),
+            Self::Uninstall => write!(f, "uninstall"),
+        }
+    }
+}
+
+/// Where an operation is in its lifecycle. It only ever moves from pending to running, and from
+/// running to succeeded or failed. It can also fail while pending, e.g. when the crate can't be
+/// found, and be cancelled until it finished.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum OperationState {
+    /// Queued, but waiting for one of the permits of `[process]`.
+    Pending,
+    /// Its hooks or cargo are running.
+    Running,
+    /// Finished without an error.
+    Succeeded,
+    /// Failed with the given error.
+    Failed(String),
+    /// Stopped before it finished, killing cargo if it was running.
+    Cancelled,
+}
+
+impl Display for OperationState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Pending => write!(f, "pending"),
+            Self::Running => write!(f, "running"),
+            Self::Succeeded => write!(f, "succeeded"),
+            Self::Failed(e) => write!(f, "failed: {e}"),
+            Self::Cancelled => write!(f, "cancelled"),
+        }
+    }
+}
+
+/// An install, update or uninstall started in this session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Operation {
+    /// Unique within the session.
+    pub id: u64,
+    /// What the operation does.
+    pub kind: OperationKind,
+    /// The crate it does it to.
+    pub krate: String,
+    /// Where the operation is in its lifecycle.
+    pub state: OperationState,
+    /// What went wrong around the operation without failing it, see [`Outcome`].
+    pub warnings: Vec<String>,
+}
+
+impl Operation {
+    /// Whether the operation has not finished yet.
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self.state,
+            OperationState::Pending | OperationState::Running
+        )
+    }
+}
+
+/// How an operation ended: the result of what it asked cargo to do, and warnings about what went
+/// wrong around it without failing it, e.g. the journal not being written.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Outcome<T> {
+    /// What cargo did.
+    pub result: Result<T, Error>,
+    /// What went wrong without failing the operation.
+    pub warnings: Vec<String>,
+}
+
+impl<T> Outcome<T> {
+    /// Changes the value of a successful outcome, keeping its warnings.
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Outcome<U> {
+        Outcome {
+            result: self.result.map(f),
+            warnings: self.warnings,
+        }
+    }
+}
+
+impl<T> From<Result<T, Error>> for Outcome<T> {
+    fn from(result: Result<T, Error>) -> Self {
+        Self {
+            result,
+            warnings: vec![],
+        }
+    }
+}
+
+/// Runs `operation`, calling `on_start` once it gets going: when its first hook starts, or when
+/// cargo got one of the permits of `[process]` to install or uninstall. Until then it waits
+/// behind other operations, and its state should stay [`OperationState::Pending`].
+pub async fn on_start<F: Future>(
+    on_start: impl FnOnce() + Send + 'static,
+    operation: F,
+) -> F::Output {
+    commands::on_start(on_start, operation).await
+}
+
+/// The installs, updates and uninstalls started in this session, oldest first.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Operations {
+    next_id: u64,
+    operations: Vec<Operation>,
+}
+
+impl Operations {
+    /// Adds a pending operation on `krate` and gives its id.
+    pub fn queue(&mut self, kind: OperationKind, krate: &str) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.operations.push(Operation {
+            id,
+            kind,
+            krate: krate.to_string(),
+            state: OperationState::Pending,
+            warnings: vec![],
+        });
+
+        id
+    }
+
+    /// Marks a pending operation as running.
+    pub fn start(&mut self, id: u64) -> Result<&Operation, Error> {
+        self.transition(id, OperationState::Running).map(|o| &*o)
+    }
+
+    /// Marks a running operation as succeeded, or a pending or running one as failed with the
+    /// error.
+    pub fn finish(
+        &mut self,
+        id: u64,
+        result: Result<(), String>,
+        warnings: Vec<String>,
+    ) -> Result<&Operation, Error> {
+        let state = match result {
+            Ok(()) => OperationState::Succeeded,
+            Err(e) => OperationState::Failed(e),
+        };
+        let operation = self.transition(id, state)?;
+        operation.warnings = warnings;
+
+        Ok(operation)
+    }
+
+    /// Marks a pending or running operation as cancelled.
+    pub fn cancel(&mut self, id: u64) -> Result<&Operation, Error> {
+        self.transition(id, OperationState::Cancelled).map(|o| &*o)
+    }
+
+    /// Moves an operation to `to`. A cancelled operation stays cancelled, since its task may
+    /// have reported that it started or finished before it was stopped.
+    fn transition(&mut self, id: u64, to: OperationState) -> Result<&mut Operation, Error> {
+        let operation = self
+            .operations
+            .iter_mut()
+            .find(|o| o.id == id)
+            .ok_or_else(|| Error::Unexpected(format!("unknown operation {id}")))?;
+        if operation.state == OperationState::Cancelled {
+            return Ok(operation);
+        }
+        let allowed = matches!(
+            (&operation.state, &to),
+            (
+                OperationState::Pending,
+                OperationState::Running | OperationState::Failed(_)
+            ) | (
+                OperationState::Running,
+                OperationState::Succeeded | OperationState::Failed(_)
+            ) | (
+                OperationState::Pending | OperationState::Running,
+                OperationState::Cancelled
+            )
+        );
+        if !allowed {
+            return Err(Error::Unexpected(format!(
+                "operation {id} cannot go from {} to {to}",
+                operation.state
+            )));
+        }
+        operation.state = to;
+
+        Ok(operation)
+    }
+
+    /// The operation with `id`.
+    pub fn get(&self, id: u64) -> Option<&Operation> {
+        self.operations.iter().find(|o| o.id == id)
+    }
+
+    /// The latest operation on `krate`.
+    pub fn last(&self, krate: &str) -> Option<&Operation> {
+        self.operations.iter().rev().find(|o| o.krate == krate)
+    }
+
+    /// Whether an operation on `krate` is pending or running.
+    pub fn is_active(&self, krate: &str) -> bool {
+        self.last(krate).is_some_and(|o| o.is_active())
+    }
+
+    /// The crates with a pending or running operation.
+    pub fn active_krates(&self) -> HashSet<String> {
+        self.operations
+            .iter()
+            .filter(|o| o.is_active())
+            .map(|o| o.krate.clone())
+            .collect()
+    }
+
+    /// The crates whose latest operation failed, with the operation.
+    pub fn failed(&self) -> impl Iterator<Item = &Operation> {
+        self.operations.iter().filter(|o| {
+            matches!(o.state, OperationState::Failed(_))
+                && self.last(&o.krate).is_some_and(|l| l.id == o.id)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{OperationKind, OperationState, Operations, on_start};
+    use crate::backend::CargoBackend;
+    use crate::backend::fake::FakeBackend;
+    use pretty_assertions::assert_eq;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn operation_lifecycle() {
+        let mut operations = Operations::default();
+        let id = operations.queue(OperationKind::Update, "ripgrep");
+        assert!(operations.is_active("ripgrep"));
+        assert_eq!(operations.get(id).unwrap().state, OperationState::Pending);
+
+        operations.start(id).unwrap();
+        assert_eq!(operations.get(id).unwrap().state, OperationState::Running);
+
+        let operation = operations
+            .finish(id, Err("no network".to_string()), vec![])
+            .unwrap();
+        assert_eq!(
+            operation.state,
+            OperationState::Failed("no network".to_string())
+        );
+        assert!(!operations.is_active("ripgrep"));
+        assert_eq!(operations.failed().count(), 1);
+
+        let retry = operations.queue(OperationKind::Update, "ripgrep");
+        assert_eq!(operations.failed().count(), 0);
+        assert_eq!(operations.active_krates().len(), 1);
+        operations.start(retry).unwrap();
+        operations
+            .finish(retry, Ok(()), vec!["no journal".to_string()])
+            .unwrap();
+        let last = operations.last("ripgrep").unwrap();
+        assert_eq!(last.state, OperationState::Succeeded);
+        assert_eq!(last.warnings, vec!["no journal"]);
+    }
+
+    #[test]
+    fn operations_reject_invalid_transitions() {
+        let mut operations = Operations::default();
+        let id = operations.queue(OperationKind::Install, "bat");
+
+        assert!(operations.finish(id, Ok(()), vec![]).is_err());
+        operations.start(id).unwrap();
+        assert!(operations.start(id).is_err());
+        operations.finish(id, Ok(()), vec![]).unwrap();
+        assert!(operations.finish(id, Ok(()), vec![]).is_err());
+        assert!(operations.start(42).is_err());
+
+        let missing = operations.queue(OperationKind::Install, "not-a-crate");
+        operations
+            .finish(missing, Err("could not find".to_string()), vec![])
+            .unwrap();
+        assert!(operations.start(missing).is_err());
+    }
+
+    #[tokio::test]
+    async fn operations_start_once_they_get_cargo() {
+        let backend = FakeBackend::default().with_installed("bat", "0.24.0");
+        let starts = Arc::new(AtomicUsize::new(0));
+        let counter = starts.clone();
+
+        let operation = async {
+            backend.tracking().await.unwrap();
+            assert_eq!(starts.load(Ordering::SeqCst), 0);
+            backend.uninstall("bat").await.unwrap();
+            backend.uninstall("bat").await
+        };
+        let count = move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+        };
+        assert!(on_start(count, operation).await.is_err());
+        assert_eq!(starts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn cancelled_operations_stay_cancelled() {
+        let mut operations = Operations::default();
+        let id = operations.queue(OperationKind::Install, "bat");
+        operations.start(id).unwrap();
+        operations.cancel(id).unwrap();
+        assert!(!operations.is_active("bat"));
+
+        // The task may still report before it is stopped.
+        operations.finish(id, Ok(()), vec![]).unwrap();
+        assert_eq!(operations.get(id).unwrap().state, OperationState::Cancelled);
+
+        let done = operations.queue(OperationKind::Install, "bat");
+        operations.start(done).unwrap();
+        operations.finish(done, Ok(()), vec![]).unwrap();
+        assert!(operations.cancel(done).is_err());
+    }
+}
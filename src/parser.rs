@@ -1,15 +1,37 @@
+use nom::bytes::complete::{take_while, take_while1};
 use nom::character::complete::{alphanumeric1, multispace0};
-use nom::character::complete::{char, space0};
+use nom::character::complete::{char, satisfy, space0};
 use nom::combinator::recognize;
 use nom::error::ParseError;
-use nom::sequence::delimited;
+use nom::sequence::{delimited, pair};
 use nom::{IResult, Parser, multi::separated_list1};
 
+/// Characters that cargo does not allow in target names.
+const FORBIDDEN_TARGET_CHARS: &str = "/\\:*?\"<>|";
+
 /// A parser that recognizes strings that contain `_` as a word.
 pub fn alphanumeric1_with_hyphen(s: &str) -> IResult<&str, &str> {
     recognize(separated_list1(char('-'), alphanumeric1)).parse(s)
 }
 
+/// A parser that recognizes package names as cargo accepts them: ASCII letters, digits, `-` and
+/// `_`, not starting with `-`.
+pub fn package_name(s: &str) -> IResult<&str, &str> {
+    recognize(pair(
+        satisfy(|c| c.is_ascii_alphanumeric() || c == '_'),
+        take_while(|c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_'),
+    ))
+    .parse(s)
+}
+
+/// A parser that recognizes target names, such as the name of a binary.
+///
+/// Cargo is more lenient with these than with package names: anything but whitespace and
+/// characters that are not allowed in file names, e.g. `rg.exe` or `wasm_bindgen-test-runner`.
+pub fn target_name(s: &str) -> IResult<&str, &str> {
+    take_while1(|c: char| !c.is_whitespace() && !FORBIDDEN_TARGET_CHARS.contains(c)).parse(s)
+}
+
 /// A combinator that takes a parser `inner` and produces a parser that also consumes both leading and
 /// trailing whitespace, returning the output of `inner`.
 pub fn ws<'a, O, E: ParseError<&'a str>, F>(inner: F) -> impl Parser<&'a str, Output = O, Error = E>
@@ -32,7 +54,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use super::{alphanumeric1_with_hyphen, ws2};
+    use super::{alphanumeric1_with_hyphen, package_name, target_name, ws2};
     use crate::parser::ws;
     use nom::{IResult, Parser, bytes::complete::tag};
 
@@ -57,6 +79,33 @@ mod tests {
         assert_eq!(alphanumeric1_with_hyphen("hello").unwrap().1, "hello");
     }
 
+    #[test]
+    fn parse_package_name() {
+        assert_eq!(
+            package_name("cargo_toml v0.1.0").unwrap(),
+            (" v0.1.0", "cargo_toml")
+        );
+        assert_eq!(
+            package_name("wasm-bindgen_cli").unwrap().1,
+            "wasm-bindgen_cli"
+        );
+        assert_eq!(package_name("_private").unwrap().1, "_private");
+        assert_eq!(package_name("x86-64").unwrap().1, "x86-64");
+        assert!(package_name("-leading").is_err());
+        assert!(package_name("").is_err());
+    }
+
+    #[test]
+    fn parse_target_name() {
+        assert_eq!(target_name("rg.exe\n").unwrap(), ("\n", "rg.exe"));
+        assert_eq!(
+            target_name("wasm_bindgen-test-runner").unwrap().1,
+            "wasm_bindgen-test-runner"
+        );
+        assert_eq!(target_name("foo/bar").unwrap().1, "foo");
+        assert!(target_name(" foo").is_err());
+    }
+
     #[test]
     fn parse_with_ws() {
         assert_eq!(ws_wrapper("foo", " \nfoo ").unwrap().1, "foo")
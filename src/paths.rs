@@ -0,0 +1,50 @@
+use std::env;
+use std::path::PathBuf;
+
+/// Location of depot's config file.
+///
+/// Can be overridden with `DEPOT_CONFIG`, otherwise defaults to `<config dir>/depot/config.toml`.
+pub fn config_file() -> Option<PathBuf> {
+    if let Some(p) = env::var_os("DEPOT_CONFIG") {
+        return Some(PathBuf::from(p));
+    }
+
+    dirs::config_dir().map(|d| d.join("depot").join("config.toml"))
+}
+
+/// Directory where depot keeps its own records.
+///
+/// Can be overridden with `DEPOT_DATA_DIR`, otherwise defaults to `<data dir>/depot`.
+pub fn data_dir() -> Option<PathBuf> {
+    if let Some(p) = env::var_os("DEPOT_DATA_DIR") {
+        return Some(PathBuf::from(p));
+    }
+
+    dirs::data_dir().map(|d| d.join("depot"))
+}
+
+/// Cargo's home directory, honouring `CARGO_HOME`.
+pub fn cargo_home() -> Option<PathBuf> {
+    if let Some(p) = env::var_os("CARGO_HOME") {
+        return Some(PathBuf::from(p));
+    }
+
+    dirs::home_dir().map(|d| d.join(".cargo"))
+}
+
+/// Directory `cargo install` installs into, with the binaries in `bin` and its tracking files
+/// at the top.
+///
+/// Honours `CARGO_INSTALL_ROOT`, otherwise defaults to the cargo home.
+pub fn install_root() -> Option<PathBuf> {
+    if let Some(p) = env::var_os("CARGO_INSTALL_ROOT") {
+        return Some(PathBuf::from(p));
+    }
+
+    cargo_home()
+}
+
+/// Directory `cargo install` puts binaries in, `<install root>/bin`.
+pub fn cargo_bin_dir() -> Option<PathBuf> {
+    install_root().map(|d| d.join("bin"))
+}
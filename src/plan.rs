@@ -0,0 +1,441 @@
+//! What updating the outdated crates would do, shown before anything is run.
+
+use crate::commands::{binstall_args, install_args, rustc_version};
+use crate::config::Config;
+use crate::depot::{Krate, SemverClass};
+use crate::errors::Error;
+use crate::installer::InstallStrategy;
+use crate::paths;
+use crate::records::InstallRecords;
+use serde::Deserialize;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+use versions::SemVer;
+
+/// A preview of what updating a set of crates will do.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UpdatePlan(pub Vec<PlanEntry>);
+
+/// What updating one crate would do.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PlanEntry {
+    /// The crate's name.
+    pub name: String,
+    /// The installed version.
+    pub from: SemVer,
+    /// The version it would be updated to.
+    pub to: SemVer,
+    /// The kind of change between the two.
+    pub class: SemverClass,
+    /// The cargo commands that will be run, in order. Later commands are fallbacks.
+    pub commands: Vec<String>,
+    /// How its binaries would change.
+    pub binaries: BinaryChanges,
+    /// Whether the new version can be built.
+    pub msrv: MsrvVerdict,
+    /// Taken from the last time depot installed the crate.
+    pub estimated_build: Option<Duration>,
+}
+
+/// How the binaries of a crate change after an update.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BinaryChanges {
+    /// Binaries the new version adds.
+    pub added: Vec<String>,
+    /// Binaries both versions have, which are overwritten.
+    pub replaced: Vec<String>,
+    /// Binaries the new version no longer has.
+    pub removed: Vec<String>,
+}
+
+/// Whether the active toolchain can build the new version.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MsrvVerdict {
+    /// The active toolchain is at least the crate's `rust-version`.
+    Compatible {
+        /// The crate's `rust-version`.
+        required: SemVer,
+    },
+    /// The active toolchain is older than the crate's `rust-version`.
+    Incompatible {
+        /// The crate's `rust-version`.
+        required: SemVer,
+        /// The version of the active toolchain.
+        installed: SemVer,
+    },
+    /// Either the crate does not declare a `rust-version` or rustc could not be found.
+    Unknown,
+}
+
+impl UpdatePlan {
+    /// Builds a plan for `krates` with the current config, records and toolchain.
+    pub async fn get(krates: &[Krate]) -> Result<Self, Error> {
+        let config = Config::load()?;
+        let records = InstallRecords::load()?;
+        let rustc = rustc_version()
+            .await
+            .ok()
+            .and_then(|s| parse_rustc_version(&s));
+        let entries = krates
+            .iter()
+            .map(|k| {
+                let next = published_binaries(&k.name, &k.latest_version());
+                PlanEntry::new(k, &config, &records, rustc.as_ref(), next.as_deref())
+            })
+            .collect();
+
+        Ok(Self(entries))
+    }
+}
+
+impl PlanEntry {
+    fn new(
+        krate: &Krate,
+        config: &Config,
+        records: &InstallRecords,
+        rustc: Option<&SemVer>,
+        next_binaries: Option<&[String]>,
+    ) -> Self {
+        let cargo = |args: Vec<String>| format!("cargo {}", args.join(" "));
+        let commands = match config.install.strategy_for(&krate.name) {
+            InstallStrategy::SourceOnly => vec![cargo(install_args(&krate.name))],
+            InstallStrategy::PreferPrebuilt => vec![
+                cargo(binstall_args(&krate.name)),
+                cargo(install_args(&krate.name)),
+            ],
+            InstallStrategy::PrebuiltOnly => vec![cargo(binstall_args(&krate.name))],
+        };
+        let msrv = MsrvVerdict::new(krate.rust_version(), rustc);
+        let estimated_build = records
+            .get(&krate.name)
+            .and_then(|r| r.build_secs)
+            .map(Duration::from_secs);
+
+        Self {
+            name: krate.name.clone(),
+            from: krate.version.clone(),
+            to: krate.latest_version(),
+            class: krate.semver_class(),
+            commands,
+            binaries: BinaryChanges::between(&krate.binaries, next_binaries),
+            msrv,
+            estimated_build,
+        }
+    }
+
+    /// The entry as label and value pairs, shared by the TUI and the command line.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![];
+        for (i, command) in self.commands.iter().enumerate() {
+            let label = if i == 0 { "command" } else { "fallback" };
+            fields.push((label, command.clone()));
+        }
+        fields.push(("binaries", self.binaries.to_string()));
+        fields.push(("msrv", self.msrv.to_string()));
+        let build = match self.estimated_build {
+            Some(d) => format!("~{} (last build)", format_duration(d)),
+            None => "unknown".to_string(),
+        };
+        fields.push(("build time", build));
+
+        fields
+    }
+}
+
+impl Display for UpdatePlan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0.is_empty() {
+            return writeln!(f, "Nothing to update.");
+        }
+
+        writeln!(f, "Update plan ({} crates)", self.0.len())?;
+        for entry in &self.0 {
+            writeln!(f)?;
+            writeln!(
+                f,
+                "{} {} -> {} ({})",
+                entry.name, entry.from, entry.to, entry.class
+            )?;
+            for (label, value) in entry.fields() {
+                writeln!(f, "  {:<12}{value}", format!("{label}:"))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl BinaryChanges {
+    /// Compares the installed binaries against the ones of the next version.
+    /// When the next version's binaries are unknown, every installed binary is assumed to be
+    /// replaced.
+    pub fn between(current: &[String], next: Option<&[String]>) -> Self {
+        let Some(next) = next else {
+            return Self {
+                replaced: current.to_vec(),
+                ..Default::default()
+            };
+        };
+
+        Self {
+            added: next
+                .iter()
+                .filter(|b| !current.contains(b))
+                .cloned()
+                .collect(),
+            replaced: next
+                .iter()
+                .filter(|b| current.contains(b))
+                .cloned()
+                .collect(),
+            removed: current
+                .iter()
+                .filter(|b| !next.contains(b))
+                .cloned()
+                .collect(),
+        }
+    }
+}
+
+impl Display for BinaryChanges {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = vec![];
+        if !self.replaced.is_empty() {
+            parts.push(format!("replaces {}", self.replaced.join(", ")));
+        }
+        if !self.added.is_empty() {
+            parts.push(format!("adds {}", self.added.join(", ")));
+        }
+        if !self.removed.is_empty() {
+            parts.push(format!("removes {}", self.removed.join(", ")));
+        }
+        if parts.is_empty() {
+            parts.push("none".to_string());
+        }
+
+        write!(f, "{}", parts.join("; "))
+    }
+}
+
+impl MsrvVerdict {
+    fn new(required: Option<&SemVer>, installed: Option<&SemVer>) -> Self {
+        match (required, installed) {
+            (Some(required), Some(installed)) if required > installed => Self::Incompatible {
+                required: required.clone(),
+                installed: installed.clone(),
+            },
+            (Some(required), Some(_)) => Self::Compatible {
+                required: required.clone(),
+            },
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl Display for MsrvVerdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MsrvVerdict::Compatible { required } => write!(f, "ok (requires {required})"),
+            MsrvVerdict::Incompatible {
+                required,
+                installed,
+            } => write!(f, "too old (requires {required}, have {installed})"),
+            MsrvVerdict::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// > rustc 1.86.0 (05f9846f8 2025-03-31)
+fn parse_rustc_version(s: &str) -> Option<SemVer> {
+    let v = s.strip_prefix("rustc ")?.split_whitespace().next()?;
+    // Drops pre-release suffixes such as `-nightly`, which would otherwise sort below the release.
+    SemVer::new(v.split('-').next()?)
+}
+
+/// Formats a duration for the plan, e.g. `2m 5s`.
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    if secs < 60 {
+        format!("{secs}s")
+    } else {
+        format!("{}m {}s", secs / 60, secs % 60)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PackageManifest {
+    package: Package,
+    #[serde(default)]
+    bin: Vec<BinTarget>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Package {
+    name: String,
+    autobins: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BinTarget {
+    name: String,
+}
+
+/// Finds the binaries of a published version in cargo's registry cache.
+///
+/// `cargo info` unpacks the crate it was asked about, so this is usually available once the
+/// metadata has been fetched.
+fn published_binaries(name: &str, version: &SemVer) -> Option<Vec<String>> {
+    let registry = paths::cargo_home()?.join("registry").join("src");
+    let dir_name = format!("{name}-{version}");
+    fs::read_dir(registry)
+        .ok()?
+        .flatten()
+        .map(|index| index.path().join(&dir_name))
+        .find(|dir| dir.is_dir())
+        .and_then(|dir| binaries_in_package(&dir))
+}
+
+fn binaries_in_package(dir: &Path) -> Option<Vec<String>> {
+    let manifest: PackageManifest =
+        toml::from_str(&fs::read_to_string(dir.join("Cargo.toml")).ok()?).ok()?;
+    let mut bins: Vec<String> = manifest.bin.into_iter().map(|b| b.name).collect();
+
+    if manifest.package.autobins != Some(false) {
+        if dir.join("src").join("main.rs").exists() && !bins.contains(&manifest.package.name) {
+            bins.push(manifest.package.name.clone());
+        }
+        if let Ok(entries) = fs::read_dir(dir.join("src").join("bin")) {
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let name = if path.is_dir() && path.join("main.rs").exists() {
+                    entry.file_name().to_string_lossy().to_string()
+                } else if path.extension().is_some_and(|e| e == "rs") {
+                    path.file_stem()?.to_string_lossy().to_string()
+                } else {
+                    continue;
+                };
+                if !bins.contains(&name) {
+                    bins.push(name);
+                }
+            }
+        }
+    }
+
+    Some(bins)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BinaryChanges, MsrvVerdict, PlanEntry, UpdatePlan, parse_rustc_version};
+    use crate::config::Config;
+    use crate::depot::{Krate, SemverClass};
+    use crate::installer::InstallStrategy;
+    use crate::records::{InstallRecord, InstallRecords};
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+    use versions::SemVer;
+
+    fn strings(v: &[&str]) -> Vec<String> {
+        v.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn parse_rustc() {
+        assert_eq!(
+            parse_rustc_version("rustc 1.86.0 (05f9846f8 2025-03-31)\n"),
+            SemVer::new("1.86.0")
+        );
+        assert_eq!(
+            parse_rustc_version("rustc 1.88.0-nightly (b8c54d635 2025-04-20)"),
+            SemVer::new("1.88.0")
+        );
+        assert_eq!(parse_rustc_version("error: no toolchain"), None);
+    }
+
+    #[test]
+    fn binary_changes() {
+        let current = strings(&["uv", "uvx"]);
+        let next = strings(&["uv", "uvw"]);
+
+        assert_eq!(
+            BinaryChanges::between(&current, Some(&next)),
+            BinaryChanges {
+                added: strings(&["uvw"]),
+                replaced: strings(&["uv"]),
+                removed: strings(&["uvx"]),
+            }
+        );
+        assert_eq!(
+            BinaryChanges::between(&current, None),
+            BinaryChanges {
+                replaced: current.clone(),
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn msrv_verdict() {
+        let v = |s| SemVer::new(s).unwrap();
+
+        assert_eq!(
+            MsrvVerdict::new(Some(&v("1.85.0")), Some(&v("1.80.0"))),
+            MsrvVerdict::Incompatible {
+                required: v("1.85.0"),
+                installed: v("1.80.0")
+            }
+        );
+        assert_eq!(
+            MsrvVerdict::new(Some(&v("1.85.0")), Some(&v("1.85.0"))),
+            MsrvVerdict::Compatible {
+                required: v("1.85.0")
+            }
+        );
+        assert_eq!(
+            MsrvVerdict::new(None, Some(&v("1.85.0"))),
+            MsrvVerdict::Unknown
+        );
+    }
+
+    #[test]
+    fn plan_entry_uses_strategy_and_records() {
+        let mut krate = Krate::default();
+        krate.name = "bat".to_string();
+        krate.version = SemVer::new("0.24.0").unwrap();
+        krate.binaries = strings(&["bat"]);
+        let mut config = Config::default();
+        config.install.strategy = InstallStrategy::PreferPrebuilt;
+        let mut records = InstallRecords::default();
+        records.set(
+            "bat",
+            InstallRecord {
+                method: crate::installer::InstallMethod::Source,
+                build_secs: Some(95),
+                installed_at: None,
+                updated_at: None,
+            },
+        );
+
+        let entry = PlanEntry::new(&krate, &config, &records, None, None);
+
+        assert_eq!(
+            entry.commands,
+            strings(&[
+                "cargo binstall bat --no-confirm --locked --disable-strategies compile",
+                "cargo install bat --locked"
+            ])
+        );
+        assert_eq!(entry.class, SemverClass::Other);
+        assert_eq!(entry.estimated_build, Some(Duration::from_secs(95)));
+        assert!(
+            UpdatePlan(vec![entry])
+                .to_string()
+                .contains("build time: ~1m 35s (last build)")
+        );
+    }
+}
@@ -0,0 +1,250 @@
+//! A policy file: crates that must be installed, their minimum versions and crates that are
+//! banned.
+
+use crate::config::Config;
+use crate::depot::Krates;
+use crate::errors::Error;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::fs;
+use std::path::{Path, PathBuf};
+use versions::SemVer;
+
+/// A team's rules about which crates have to be installed.
+///
+/// > required = ["cargo-deny", "cargo-nextest"]
+/// > banned = ["cargo-outdated"]
+/// >
+/// > [minimum-versions]
+/// > cargo-nextest = "0.9.70"
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+#[non_exhaustive]
+pub struct Policy {
+    /// Crates that have to be installed.
+    pub required: BTreeSet<String>,
+    /// Crates that must not be installed.
+    pub banned: BTreeSet<String>,
+    /// Installed crates must be at least this version. Doesn't require the crate on its own.
+    pub minimum_versions: BTreeMap<String, String>,
+}
+
+/// A way the installed crates break the policy.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum Violation {
+    /// A required crate is not installed.
+    Missing {
+        /// The crate's name.
+        #[serde(rename = "crate")]
+        krate: String,
+    },
+    /// A crate is older than its minimum version.
+    Outdated {
+        /// The crate's name.
+        #[serde(rename = "crate")]
+        krate: String,
+        /// The installed version.
+        installed: String,
+        /// The minimum version.
+        minimum: String,
+    },
+    /// A banned crate is installed.
+    Banned {
+        /// The crate's name.
+        #[serde(rename = "crate")]
+        krate: String,
+    },
+}
+
+/// The result of `depot policy check` as a single JSON document.
+#[derive(Debug, Serialize)]
+#[non_exhaustive]
+pub struct Compliance<'a> {
+    /// Whether nothing breaks the policy.
+    pub compliant: bool,
+    /// Everything that breaks it.
+    pub violations: &'a [Violation],
+}
+
+impl<'a> Compliance<'a> {
+    /// The result for `violations`.
+    pub fn new(violations: &'a [Violation]) -> Self {
+        Self {
+            compliant: violations.is_empty(),
+            violations,
+        }
+    }
+}
+
+impl Violation {
+    /// The crate that breaks the policy.
+    pub fn krate(&self) -> &str {
+        match self {
+            Self::Missing { krate } | Self::Outdated { krate, .. } | Self::Banned { krate } => {
+                krate
+            }
+        }
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing { krate } => write!(f, "{krate} is required but not installed"),
+            Self::Outdated {
+                krate,
+                installed,
+                minimum,
+            } => write!(f, "{krate} {installed} is older than the minimum {minimum}"),
+            Self::Banned { krate } => write!(f, "{krate} is banned"),
+        }
+    }
+}
+
+impl Policy {
+    /// Loads the policy set in the config, if there is one.
+    pub fn load_configured() -> Result<Option<Self>, Error> {
+        match Config::load()?.policy.path {
+            Some(path) => Ok(Some(Self::load(&path)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the policy file at `path`. `~` is the home directory.
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        Self::parse(&fs::read_to_string(expand_home(path))?)
+    }
+
+    fn parse(s: &str) -> Result<Self, Error> {
+        let policy: Self = toml::from_str(s)?;
+        for (name, version) in &policy.minimum_versions {
+            if SemVer::new(version).is_none() {
+                return Err(Error::Unexpected(format!(
+                    "minimum version of {name} is not a version: {version}"
+                )));
+            }
+        }
+
+        Ok(policy)
+    }
+
+    /// Checks the installed crates against the policy.
+    pub fn check(&self, krates: &Krates) -> Vec<Violation> {
+        let installed = |name: &str| krates.0.iter().find(|k| k.name == name);
+        let mut violations = vec![];
+
+        for name in &self.required {
+            if installed(name).is_none() {
+                violations.push(Violation::Missing {
+                    krate: name.clone(),
+                });
+            }
+        }
+        for (name, minimum) in &self.minimum_versions {
+            if let (Some(krate), Some(min)) = (installed(name), SemVer::new(minimum))
+                && krate.version < min
+            {
+                violations.push(Violation::Outdated {
+                    krate: name.clone(),
+                    installed: krate.version.to_string(),
+                    minimum: minimum.clone(),
+                });
+            }
+        }
+        for name in &self.banned {
+            if installed(name).is_some() {
+                violations.push(Violation::Banned {
+                    krate: name.clone(),
+                });
+            }
+        }
+
+        violations
+    }
+}
+
+/// Lets the config point at a policy in a checked-out repository with `~/...`.
+fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Policy, Violation};
+    use crate::depot::{Krate, Krates};
+    use pretty_assertions::assert_eq;
+    use versions::SemVer;
+
+    fn krate(name: &str, version: &str) -> Krate {
+        let mut k = Krate::default();
+        k.name = name.to_string();
+        k.version = SemVer::new(version).unwrap();
+        k
+    }
+
+    #[test]
+    fn check_policy() {
+        let policy = Policy::parse(
+            r#"
+required = ["cargo-deny", "cargo-nextest"]
+banned = ["cargo-outdated"]
+
+[minimum-versions]
+cargo-nextest = "0.9.70"
+ripgrep = "14.0.0"
+"#,
+        )
+        .unwrap();
+        let krates = Krates(vec![
+            krate("cargo-nextest", "0.9.68"),
+            krate("cargo-outdated", "0.15.0"),
+            krate("ripgrep", "14.1.1"),
+        ]);
+
+        assert_eq!(
+            policy.check(&krates),
+            vec![
+                Violation::Missing {
+                    krate: "cargo-deny".to_string()
+                },
+                Violation::Outdated {
+                    krate: "cargo-nextest".to_string(),
+                    installed: "0.9.68".to_string(),
+                    minimum: "0.9.70".to_string(),
+                },
+                Violation::Banned {
+                    krate: "cargo-outdated".to_string()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn serialize_violation() {
+        let violation = Violation::Outdated {
+            krate: "cargo-nextest".to_string(),
+            installed: "0.9.68".to_string(),
+            minimum: "0.9.70".to_string(),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&violation).unwrap(),
+            r#"{"kind":"outdated","crate":"cargo-nextest","installed":"0.9.68","minimum":"0.9.70"}"#
+        );
+        assert_eq!(
+            violation.to_string(),
+            "cargo-nextest 0.9.68 is older than the minimum 0.9.70"
+        );
+    }
+
+    #[test]
+    fn parse_invalid_minimum_version() {
+        assert!(Policy::parse("[minimum-versions]\nbat = \"latest\"").is_err());
+    }
+}
@@ -0,0 +1,154 @@
+//! What depot remembers about the installs it ran itself, kept in `installs.toml` in its data
+//! directory.
+
+use crate::errors::Error;
+use crate::installer::InstallMethod;
+use crate::paths;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+const RECORDS_FILE: &str = "installs.toml";
+
+/// What depot knows about the crates it has installed itself.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InstallRecords(BTreeMap<String, InstallRecord>);
+
+/// What depot knows about one of the crates it installed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[non_exhaustive]
+pub struct InstallRecord {
+    /// How the crate was last installed.
+    pub method: InstallMethod,
+    /// How long the last install took, in seconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build_secs: Option<u64>,
+    /// When depot first installed the crate, in seconds since the Unix epoch.
+    /// Unknown for crates that were installed before depot managed them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub installed_at: Option<u64>,
+    /// When depot last updated the crate, in seconds since the Unix epoch.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<u64>,
+}
+
+impl InstallRecords {
+    /// Loads the records, returning an empty set if depot has not installed anything yet.
+    pub fn load() -> Result<Self, Error> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Writes the records, creating depot's data directory if needed.
+    pub fn save(&self) -> Result<(), Error> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string(self)?)?;
+
+        Ok(())
+    }
+
+    /// The record of `krate`, if depot installed it.
+    pub fn get(&self, krate: &str) -> Option<&InstallRecord> {
+        self.0.get(krate)
+    }
+
+    /// Replaces the record of `krate`.
+    pub fn set(&mut self, krate: &str, record: InstallRecord) {
+        self.0.insert(krate.to_string(), record);
+    }
+
+    /// Drops the records of crates that are no longer installed, e.g. after `cargo uninstall`.
+    /// Gives the names of the dropped records.
+    pub fn prune(&mut self, installed: &[&str]) -> Vec<String> {
+        let stale: Vec<String> = self
+            .0
+            .keys()
+            .filter(|k| !installed.contains(&k.as_str()))
+            .cloned()
+            .collect();
+        for name in &stale {
+            self.0.remove(name);
+        }
+
+        stale
+    }
+
+    /// Shorthand for loading the records, setting the record of `krate` and saving them.
+    pub fn record(krate: &str, record: InstallRecord) -> Result<(), Error> {
+        let mut records = Self::load()?;
+        records.set(krate, record);
+        records.save()
+    }
+
+    fn path() -> Option<PathBuf> {
+        paths::data_dir().map(|d| d.join(RECORDS_FILE))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InstallRecord, InstallRecords};
+    use crate::installer::InstallMethod;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn records_round_trip() {
+        let mut records = InstallRecords::default();
+        records.set(
+            "ripgrep",
+            InstallRecord {
+                method: InstallMethod::Prebuilt,
+                build_secs: None,
+                installed_at: None,
+                updated_at: None,
+            },
+        );
+        records.set(
+            "bat",
+            InstallRecord {
+                method: InstallMethod::Source,
+                build_secs: Some(42),
+                installed_at: Some(1_743_465_599),
+                updated_at: Some(1_750_000_000),
+            },
+        );
+
+        let s = toml::to_string(&records).unwrap();
+        let parsed: InstallRecords = toml::from_str(&s).unwrap();
+
+        assert_eq!(parsed, records);
+        assert_eq!(
+            parsed.get("ripgrep").unwrap().method,
+            InstallMethod::Prebuilt
+        );
+    }
+
+    #[test]
+    fn prune_records() {
+        let record = InstallRecord {
+            method: InstallMethod::Source,
+            build_secs: None,
+            installed_at: None,
+            updated_at: None,
+        };
+        let mut records = InstallRecords::default();
+        records.set("bat", record.clone());
+        records.set("ripgrep", record);
+
+        assert_eq!(records.prune(&["ripgrep"]), vec!["bat"]);
+        assert_eq!(records.0.keys().collect::<Vec<_>>(), vec!["ripgrep"]);
+    }
+}
@@ -0,0 +1,243 @@
+//! The JSON printed by `--format json` and `--format ndjson`.
+
+use crate::depot::Krate;
+use crate::errors::Error;
+use crate::installer::InstallMethod;
+use crate::tracking::KrateSource;
+use serde::Serialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The version of the JSON printed with `--format json` and `--format ndjson`.
+///
+/// Fields may be added within a version. Renaming, removing or changing the meaning of a field
+/// bumps it, so consumers can refuse output they don't understand.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Everything depot knows about a crate. Unknown values are `null`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct KrateRecord {
+    /// The crate's name.
+    pub name: String,
+    /// `null` when the crate is not installed.
+    pub installed_version: Option<String>,
+    /// `null` when `cargo info` has not been run or could not be read.
+    pub latest_version: Option<String>,
+    /// One of `major`, `minor`, `patch` or `other`, when both versions are known.
+    pub semver_class: Option<String>,
+    /// `null` when the latest version is unknown, as it is for `list`, which does not fetch it.
+    pub outdated: Option<bool>,
+    /// Whether the installed version was yanked. `false` when it is not known.
+    pub yanked: bool,
+    /// Where the crate came from, if cargo tracks it.
+    pub source: Option<Source>,
+    /// The file names of the installed binaries.
+    pub binaries: Vec<String>,
+    /// `source` or `prebuilt`, for crates depot installed.
+    pub install_method: Option<InstallMethod>,
+    /// The combined size of the binaries.
+    pub size_bytes: u64,
+    /// Seconds since the Unix epoch, as are the other times.
+    pub installed_at: Option<u64>,
+    /// `null` when the crate has not been updated since it was installed.
+    pub updated_at: Option<u64>,
+    /// An estimate of when one of the binaries was last run.
+    pub last_used: Option<u64>,
+    /// The crate's description.
+    pub description: Option<String>,
+    /// The crate's license.
+    pub license: Option<String>,
+    /// The minimum supported Rust version.
+    pub rust_version: Option<String>,
+    /// Links to the crate.
+    pub urls: Urls,
+}
+
+/// Where an installed crate came from, told apart by `kind`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+#[non_exhaustive]
+pub enum Source {
+    /// A registry.
+    Registry {
+        /// The index URL, `null` for crates.io.
+        index: Option<String>,
+    },
+    /// A git repository.
+    Git {
+        /// The repository's URL.
+        url: String,
+        /// The commit that was installed, if cargo recorded it.
+        rev: Option<String>,
+    },
+    /// A local directory.
+    Path {
+        /// The directory.
+        path: String,
+    },
+}
+
+/// Links to a crate, each `null` when the crate has none.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[non_exhaustive]
+pub struct Urls {
+    /// The documentation.
+    pub documentation: Option<String>,
+    /// The homepage.
+    pub homepage: Option<String>,
+    /// The source repository.
+    pub repository: Option<String>,
+    /// The crate's page on crates.io.
+    pub crates_io: Option<String>,
+}
+
+/// The body of `list` and `outdated` as a single document.
+#[derive(Debug, Serialize)]
+#[non_exhaustive]
+pub struct KrateList<'a> {
+    /// The crates, in the order they were listed.
+    pub crates: &'a [KrateRecord],
+}
+
+/// The body of `info` as a single document.
+#[derive(Debug, Serialize)]
+#[non_exhaustive]
+pub struct KrateDetail<'a> {
+    /// The crate.
+    #[serde(rename = "crate")]
+    pub krate: &'a KrateRecord,
+}
+
+impl<'a> KrateList<'a> {
+    /// The document for `crates`.
+    pub fn new(crates: &'a [KrateRecord]) -> Self {
+        Self { crates }
+    }
+}
+
+impl<'a> KrateDetail<'a> {
+    /// The document for `krate`.
+    pub fn new(krate: &'a KrateRecord) -> Self {
+        Self { krate }
+    }
+}
+
+impl KrateRecord {
+    /// The record of `krate`, with `source` from cargo's tracking data.
+    pub fn new(krate: &Krate, source: Option<KrateSource>) -> Self {
+        let info = krate.info();
+        let installed = !krate.binaries.is_empty();
+        let installed_version = installed.then(|| krate.version.to_string());
+        let semver_class = match &info.latest_version {
+            Some(_) if installed => Some(krate.semver_class().to_string()),
+            _ => None,
+        };
+
+        Self {
+            name: krate.name.clone(),
+            installed_version,
+            latest_version: info.latest_version.as_ref().map(|v| v.to_string()),
+            semver_class,
+            outdated: info
+                .latest_version
+                .as_ref()
+                .map(|_| installed && !krate.is_latest()),
+            yanked: info.yanked,
+            source: source.map(Source::from),
+            binaries: krate.binaries.clone(),
+            install_method: krate.install_method,
+            size_bytes: krate.size,
+            installed_at: unix_secs(krate.installed_at),
+            updated_at: unix_secs(krate.updated_at),
+            last_used: unix_secs(krate.last_used),
+            description: info.description.clone(),
+            license: info.license.clone(),
+            rust_version: info.rust_version.as_ref().map(|v| v.to_string()),
+            urls: Urls {
+                documentation: info.documentation.clone(),
+                homepage: info.homepage.clone(),
+                repository: info.repository.clone(),
+                crates_io: info.crates_io.clone(),
+            },
+        }
+    }
+}
+
+impl From<KrateSource> for Source {
+    fn from(source: KrateSource) -> Self {
+        match source {
+            KrateSource::Registry { index } => Self::Registry { index },
+            KrateSource::Git { url, rev } => Self::Git { url, rev },
+            KrateSource::Path(path) => Self::Path { path },
+        }
+    }
+}
+
+fn unix_secs(time: Option<SystemTime>) -> Option<u64> {
+    Some(time?.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+#[derive(Serialize)]
+struct Document<'a, T: Serialize> {
+    schema_version: u32,
+    #[serde(flatten)]
+    body: &'a T,
+}
+
+/// A single JSON document, e.g. `{"schema_version": 1, "crates": [...]}` for a body with a
+/// `crates` field.
+pub fn to_json<T: Serialize>(body: &T) -> Result<String, Error> {
+    Ok(serde_json::to_string_pretty(&Document {
+        schema_version: SCHEMA_VERSION,
+        body,
+    })?)
+}
+
+/// A line of NDJSON. Every line carries the schema version, so lines can be read on their own.
+pub fn to_ndjson_line<T: Serialize>(item: &T) -> Result<String, Error> {
+    Ok(serde_json::to_string(&Document {
+        schema_version: SCHEMA_VERSION,
+        body: item,
+    })?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{KrateRecord, to_ndjson_line};
+    use crate::depot::{Krate, KrateMetadata};
+    use crate::tracking::KrateSource;
+    use pretty_assertions::assert_eq;
+    use std::time::{Duration, UNIX_EPOCH};
+    use versions::SemVer;
+
+    #[test]
+    fn serialize_krate_record() {
+        let mut krate = Krate::default();
+        krate.name = "ripgrep".to_string();
+        krate.version = SemVer::new("14.1.1").unwrap();
+        krate.binaries = vec!["rg".to_string()];
+        krate.size = 5_000_000;
+        krate.installed_at = Some(UNIX_EPOCH + Duration::from_secs(1_718_000_000));
+        let record = KrateRecord::new(&krate, Some(KrateSource::Registry { index: None }));
+
+        assert_eq!(
+            to_ndjson_line(&record).unwrap(),
+            r#"{"schema_version":1,"name":"ripgrep","installed_version":"14.1.1","latest_version":null,"semver_class":null,"outdated":null,"yanked":false,"source":{"kind":"registry","index":null},"binaries":["rg"],"install_method":null,"size_bytes":5000000,"installed_at":1718000000,"updated_at":null,"last_used":null,"description":null,"license":null,"rust_version":null,"urls":{"documentation":null,"homepage":null,"repository":null,"crates_io":null}}"#
+        );
+    }
+
+    #[test]
+    fn outdated_needs_latest_version() {
+        let mut krate = Krate::default();
+        krate.name = "bat".to_string();
+        krate.version = SemVer::new("0.24.0").unwrap();
+        krate.binaries = vec!["bat".to_string()];
+        assert_eq!(KrateRecord::new(&krate, None).outdated, None);
+
+        krate.set_metadata(KrateMetadata::with_latest(
+            "bat",
+            SemVer::new("0.25.0").unwrap(),
+        ));
+        assert_eq!(KrateRecord::new(&krate, None).outdated, Some(true));
+    }
+}
@@ -0,0 +1,111 @@
+//! Searching the registry for crates to install.
+
+use crate::commands::search_registry;
+use crate::config::SearchConfig;
+use crate::errors::Error;
+use crate::parser::{package_name, ws2};
+use nom::bytes::complete::{tag, take_until};
+use nom::character::complete::{char, not_line_ending, space0};
+use nom::combinator::{map, opt};
+use nom::sequence::{delimited, preceded};
+use nom::{IResult, Parser};
+
+/// A crate found on the registry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SearchResult {
+    /// The crate's name.
+    pub name: String,
+    /// The latest version.
+    pub version: String,
+    /// The description, if the crate has one.
+    pub description: Option<String>,
+}
+
+impl SearchResult {
+    /// > ripgrep = "14.1.1"    # ripgrep is a line-oriented search tool
+    fn parse(s: &str) -> IResult<&str, Self> {
+        let (s, name) = map(package_name, String::from).parse(s)?;
+        let (s, _) = ws2(char('=')).parse(s)?;
+        let (s, version) = map(
+            delimited(char('"'), take_until("\""), char('"')),
+            String::from,
+        )
+        .parse(s)?;
+        let (s, _) = space0(s)?;
+        let (s, description) = opt(map(preceded(tag("#"), not_line_ending), |d: &str| {
+            d.trim().to_string()
+        }))
+        .parse(s)?;
+
+        Ok((
+            s,
+            Self {
+                name,
+                version,
+                description,
+            },
+        ))
+    }
+}
+
+/// Searches the configured registry for crates matching `query`.
+pub async fn search(query: &str, config: &SearchConfig) -> Result<Vec<SearchResult>, Error> {
+    let stdout = search_registry(query, config.index.as_deref(), config.limit).await?;
+
+    Ok(parse_search_results(&stdout))
+}
+
+/// Parses the stdout of `cargo search`.
+///
+/// Lines that are not results, such as `... and 10 crates more` and notes, are skipped.
+pub fn parse_search_results(s: &str) -> Vec<SearchResult> {
+    s.lines()
+        .filter_map(|line| SearchResult::parse(line).ok().map(|(_, r)| r))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SearchResult, parse_search_results};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_search_output() {
+        let output = r#"ripgrep = "14.1.1"                 # ripgrep is a line-oriented search tool.
+ripgrep_all = "0.10.6"             # rga: ripgrep, but also search in PDFs, E-Books, Office documents, zip, tar.gz, etc.
+grep-cli = "0.1.11"
+... and 145 crates more (use --limit N to see more)
+note: to learn more about a package, run `cargo info <name>`
+"#;
+
+        assert_eq!(
+            parse_search_results(output),
+            vec![
+                SearchResult {
+                    name: "ripgrep".to_string(),
+                    version: "14.1.1".to_string(),
+                    description: Some("ripgrep is a line-oriented search tool.".to_string()),
+                },
+                SearchResult {
+                    name: "ripgrep_all".to_string(),
+                    version: "0.10.6".to_string(),
+                    description: Some(
+                        "rga: ripgrep, but also search in PDFs, E-Books, Office documents, zip, tar.gz, etc."
+                            .to_string()
+                    ),
+                },
+                SearchResult {
+                    name: "grep-cli".to_string(),
+                    version: "0.1.11".to_string(),
+                    description: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_empty_search_output() {
+        assert_eq!(parse_search_results(""), vec![]);
+    }
+}
@@ -0,0 +1,673 @@
+use depot_rs::backend::CargoBackend;
+use depot_rs::cleanup::{Cleanup, CleanupItem};
+use depot_rs::depot::{Depot, Krate, KrateMetadata};
+use depot_rs::depotfile::Depotfile;
+use depot_rs::doctor::DoctorReport;
+use depot_rs::drift::DriftReport;
+use depot_rs::errors::Error;
+use depot_rs::hooks::Hooks;
+use depot_rs::journal::{Journal, JournalEntry};
+use depot_rs::operation::{OperationKind, OperationState, Operations};
+use depot_rs::plan::UpdatePlan;
+use depot_rs::policy::Policy;
+use depot_rs::records::InstallRecords;
+use depot_rs::search::SearchResult;
+use depot_rs::tracking::{self, InstallTracking};
+use ratatui::widgets::ListState;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use throbber_widgets_tui::ThrobberState;
+use tokio::sync::Notify;
+use tokio::task::AbortHandle;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DepotMessage {
+    /// The installed crates were listed on startup.
+    Listed(Box<Depot>),
+    ListFailed(String),
+    FetchKrateInfo(Vec<KrateMetadata>),
+    /// An operation got hold of cargo.
+    OperationStarted(u64),
+    OperationFinished(Box<Finished>),
+    SearchResults(Vec<SearchResult>),
+    /// Searching the registry failed, with the error.
+    SearchFailed(String),
+    SearchInfo(Box<KrateMetadata>),
+    Diagnosed(DoctorReport),
+}
+
+impl DepotMessage {
+    pub fn handle(self, state: &mut DepotState) -> Result<(), Error> {
+        match self {
+            DepotMessage::Listed(depot) => {
+                state.depot = *depot;
+                state.listing = false;
+            }
+            DepotMessage::ListFailed(e) => {
+                state.listing = false;
+                state.startup_error = Some(e);
+            }
+            DepotMessage::FetchKrateInfo(r) => {
+                state.sync(r)?;
+                // A crate that can't be looked up is shown as unavailable, but when none can,
+                // cargo or the registry is likely unreachable.
+                let store = &state.depot.store.0;
+                if !store.is_empty() && state.depot.unfetched_krate_count() == store.len() {
+                    let error = store[0].fetch_error().unwrap_or_default();
+                    state.startup_error = Some(format!("could not look up any crate: {error}"));
+                }
+            }
+            DepotMessage::OperationStarted(id) => {
+                state.operations.start(id)?;
+            }
+            DepotMessage::OperationFinished(finished) => state.finish_operation(*finished)?,
+            DepotMessage::SearchResults(r) => {
+                state.search.searching = false;
+                state.search.results = r;
+                state.search.list_state.select(None);
+            }
+            DepotMessage::SearchFailed(e) => {
+                state.search.searching = false;
+                state.search.error = Some(e);
+                state.search.results.clear();
+                state.search.list_state.select(None);
+            }
+            DepotMessage::SearchInfo(m) => {
+                state.search.details.insert(m.name().to_string(), *m);
+            }
+            DepotMessage::Diagnosed(report) => state.doctor = Some(report),
+        }
+
+        Ok(())
+    }
+}
+
+/// The background task of an operation.
+#[derive(Debug)]
+pub struct Task {
+    /// Tells the operation to stop, after which it journals itself as cancelled.
+    pub cancel: Arc<Notify>,
+    /// Stops the task outright, e.g. when the app quits.
+    pub abort: AbortHandle,
+}
+
+/// What a background operation reports once cargo is done.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finished {
+    pub id: u64,
+    /// The error the operation failed with.
+    pub result: Result<(), String>,
+    /// Cargo's tracking data and depot's records, read again after the operation, whether it
+    /// succeeded or not.
+    pub installed: Result<(InstallTracking, InstallRecords), String>,
+    /// The metadata of a newly installed crate.
+    pub metadata: Option<KrateMetadata>,
+    /// What went wrong around the operation without failing it.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct DepotState {
+    /// Empty until the installed crates have been listed.
+    pub depot: Depot,
+    /// Set while the installed crates are being listed.
+    pub listing: bool,
+    /// Why the installed crates could not be listed.
+    pub startup_error: Option<String>,
+    pub list_state: ListState,
+    pub update_list_state: ListState,
+    pub plan_list_state: ListState,
+    pub throbber_state: ThrobberState,
+    /// The crates marked in the update view for the next plan.
+    pub plan_selection: HashSet<String>,
+    pub plan: UpdatePlan,
+    pub catalog_sort: CatalogSort,
+    /// When set, the catalog only lists crates that have not been used for this long.
+    pub forgotten_filter: Option<Duration>,
+    pub search: SearchState,
+    pub cleanup: Cleanup,
+    pub cleanup_list_state: ListState,
+    /// The orphan waiting for the user to confirm it should be deleted.
+    pub cleanup_pending: Option<String>,
+    /// Why the last cleanup failed, shown until the next key press.
+    pub cleanup_error: Option<String>,
+    /// The Depotfile being imported.
+    pub import: Depotfile,
+    pub import_list_state: ListState,
+    /// How the installed crates differ from a Depotfile.
+    pub drift: DriftReport,
+    pub drift_list_state: ListState,
+    /// The team policy set in the config, checked on the start view.
+    pub policy: Option<Policy>,
+    /// The results of `depot doctor`. `None` while the checks are running.
+    pub doctor: Option<DoctorReport>,
+    /// How cargo is run. Views clone it into the tasks they spawn.
+    pub backend: Arc<dyn CargoBackend>,
+    /// The installs, updates and uninstalls started in this session.
+    pub operations: Operations,
+    /// The tasks running the active operations, by operation id, so they can be cancelled.
+    pub tasks: HashMap<u64, Task>,
+    /// Where every operation is recorded.
+    pub journal: Journal,
+    /// The hooks set in the config, run around every operation.
+    pub hooks: Hooks,
+    pub history: HistoryState,
+}
+
+impl DepotState {
+    /// Creates the state without listing the installed crates, which is left to the caller.
+    pub fn new(backend: Arc<dyn CargoBackend>) -> Result<Self, Error> {
+        Ok(Self::with_parts(
+            backend,
+            Policy::load_configured()?,
+            Hooks::load()?,
+            Journal::open(),
+        ))
+    }
+
+    /// Creates the state with the given policy, hooks and journal instead of the configured ones.
+    pub fn with_parts(
+        backend: Arc<dyn CargoBackend>,
+        policy: Option<Policy>,
+        hooks: Hooks,
+        journal: Journal,
+    ) -> Self {
+        let depot = Depot::default();
+        let list_state = ListState::default();
+        let update_list_state = ListState::default();
+        let plan_list_state = ListState::default();
+        let throbber_state = throbber_widgets_tui::ThrobberState::default();
+
+        Self {
+            depot,
+            listing: false,
+            startup_error: None,
+            list_state,
+            update_list_state,
+            plan_list_state,
+            throbber_state,
+            plan_selection: HashSet::new(),
+            plan: UpdatePlan::default(),
+            catalog_sort: CatalogSort::default(),
+            forgotten_filter: None,
+            search: SearchState::default(),
+            cleanup: Cleanup::default(),
+            cleanup_list_state: ListState::default(),
+            cleanup_pending: None,
+            cleanup_error: None,
+            import: Depotfile::default(),
+            import_list_state: ListState::default(),
+            drift: DriftReport::default(),
+            drift_list_state: ListState::default(),
+            policy,
+            doctor: None,
+            backend,
+            operations: Operations::default(),
+            tasks: HashMap::new(),
+            journal,
+            hooks,
+            history: HistoryState::default(),
+        }
+    }
+
+    pub fn is_all_synced(&self) -> bool {
+        !self.listing && self.depot.store.0.iter().all(|k| k.is_metadata_synced())
+    }
+
+    /// The installed crates in the order chosen for the catalog.
+    pub fn catalog(&self) -> Vec<&Krate> {
+        let now = SystemTime::now();
+        let mut krates: Vec<&Krate> = self
+            .depot
+            .store
+            .0
+            .iter()
+            .filter(|k| match self.forgotten_filter {
+                Some(window) => k.is_forgotten(window, now),
+                None => true,
+            })
+            .collect();
+        match self.catalog_sort {
+            CatalogSort::Name => {}
+            CatalogSort::Size => krates.sort_by_key(|k| std::cmp::Reverse(k.size)),
+            // Crates without a date go last.
+            CatalogSort::Installed => {
+                krates.sort_by_key(|k| (k.installed_at.is_none(), k.installed_at))
+            }
+            CatalogSort::Updated => {
+                krates.sort_by_key(|k| (k.last_changed().is_none(), k.last_changed()))
+            }
+        }
+
+        krates
+    }
+
+    /// Records how an operation ended and reconciles the depot with what is now on disk.
+    pub fn finish_operation(&mut self, finished: Finished) -> Result<(), Error> {
+        self.tasks.remove(&finished.id);
+        let result = match (finished.result, &finished.installed) {
+            (Ok(()), Err(e)) => Err(format!("could not read what is installed: {e}")),
+            (result, _) => result,
+        };
+        let operation = self
+            .operations
+            .finish(finished.id, result, finished.warnings)?
+            .clone();
+        let name = &operation.krate;
+        if let Ok((tracking, records)) = &finished.installed {
+            let uninstalled = operation.kind == OperationKind::Uninstall
+                && operation.state == OperationState::Succeeded;
+            self.depot.reconcile(name, uninstalled, tracking, records);
+        }
+        if let Some(metadata) = finished.metadata
+            && let Some(krate) = self.depot.store.0.iter_mut().find(|k| &k.name == name)
+        {
+            krate.set_metadata(metadata);
+        }
+
+        if operation.state == OperationState::Succeeded {
+            self.cleanup
+                .0
+                .retain(|i| !matches!(i, CleanupItem::Missing { krate, .. } if krate == name));
+            self.drift.0.retain(|d| &d.name != name);
+        }
+        self.clamp_selection();
+
+        Ok(())
+    }
+
+    /// The catalog indexes into the crates, so it must not point past one that was removed.
+    fn clamp_selection(&mut self) {
+        if self
+            .list_state
+            .selected()
+            .is_some_and(|ix| ix >= self.catalog().len())
+        {
+            self.list_state.select(None);
+        }
+    }
+
+    /// Removes binaries that were deleted by hand from cargo's tracking files and from the depot.
+    pub fn forget_binaries(&mut self, name: &str, binaries: &[String]) -> Result<(), Error> {
+        tracking::forget(name, binaries)?;
+        self.remove_binaries(name, binaries);
+
+        Ok(())
+    }
+
+    /// Removes `binaries` from the crate in the depot, and the crate once it has none left.
+    fn remove_binaries(&mut self, name: &str, binaries: &[String]) {
+        let store = &mut self.depot.store.0;
+        if let Some(ix) = store.iter().position(|k| k.name == name) {
+            store[ix].binaries.retain(|b| !binaries.contains(b));
+            store[ix].measure();
+            if store[ix].binaries.is_empty() {
+                store.remove(ix);
+            }
+        }
+        self.depot.refresh_conflicts();
+        self.clamp_selection();
+    }
+
+    /// Attaches fetched metadata to every installed crate.
+    fn sync(&mut self, info: Vec<KrateMetadata>) -> Result<(), Error> {
+        for krate in &mut self.depot.store.0 {
+            if let Some(ki) = info.iter().find(|&i| krate.name == i.name()) {
+                krate.set_metadata(ki.clone());
+            } else {
+                return Err(Error::Unexpected("unmatched name".to_string()));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How the catalog lists the installed crates.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum CatalogSort {
+    #[default]
+    Name,
+    /// Largest first.
+    Size,
+    /// Oldest install first.
+    Installed,
+    /// Least recently updated first.
+    Updated,
+}
+
+impl CatalogSort {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Name => Self::Size,
+            Self::Size => Self::Installed,
+            Self::Installed => Self::Updated,
+            Self::Updated => Self::Name,
+        }
+    }
+}
+
+impl Display for CatalogSort {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Name => write!(f, "name"),
+            Self::Size => write!(f, "size"),
+            Self::Installed => write!(f, "install date"),
+            Self::Updated => write!(f, "last update"),
+        }
+    }
+}
+
+/// Which operations the history view lists.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutcomeFilter {
+    #[default]
+    All,
+    Succeeded,
+    Failed,
+}
+
+impl OutcomeFilter {
+    pub fn next(self) -> Self {
+        match self {
+            Self::All => Self::Succeeded,
+            Self::Succeeded => Self::Failed,
+            Self::Failed => Self::All,
+        }
+    }
+
+    fn matches(self, entry: &JournalEntry) -> bool {
+        match self {
+            Self::All => true,
+            Self::Succeeded => entry.succeeded(),
+            Self::Failed => !entry.succeeded(),
+        }
+    }
+}
+
+impl Display for OutcomeFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::All => write!(f, "all"),
+            Self::Succeeded => write!(f, "succeeded"),
+            Self::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// State of the history view.
+#[derive(Debug, Default)]
+pub struct HistoryState {
+    /// The journal, newest first. Read again whenever the view is opened.
+    pub entries: Vec<JournalEntry>,
+    /// When set, only the operations on this crate are listed.
+    pub krate: Option<String>,
+    pub outcome: OutcomeFilter,
+    pub list_state: ListState,
+    /// The build log of the highlighted operation, while it is open.
+    pub log: Option<String>,
+    /// Why the journal could not be read, shown instead of the entries.
+    pub error: Option<String>,
+}
+
+impl HistoryState {
+    /// The entries that pass the filters.
+    pub fn filtered(&self) -> Vec<&JournalEntry> {
+        self.entries
+            .iter()
+            .filter(|e| self.krate.as_ref().is_none_or(|k| &e.krate == k))
+            .filter(|e| self.outcome.matches(e))
+            .collect()
+    }
+
+    pub fn selected(&self) -> Option<&JournalEntry> {
+        self.list_state
+            .selected()
+            .and_then(|ix| self.filtered().get(ix).copied())
+    }
+}
+
+/// State of the install view.
+#[derive(Debug, Default)]
+pub struct SearchState {
+    pub query: String,
+    /// Whether key presses go to the search box.
+    pub editing: bool,
+    pub searching: bool,
+    pub results: Vec<SearchResult>,
+    /// Why the last search failed, shown instead of the results.
+    pub error: Option<String>,
+    pub list_state: ListState,
+    /// Metadata of the results that have been looked at, fetched with `cargo info`.
+    pub details: HashMap<String, KrateMetadata>,
+    /// Results whose metadata has been requested, so that it is only fetched once.
+    pub requested: HashSet<String>,
+}
+
+impl SearchState {
+    pub fn selected(&self) -> Option<&SearchResult> {
+        self.list_state
+            .selected()
+            .and_then(|ix| self.results.get(ix))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DepotMessage, DepotState, Finished, HistoryState, OutcomeFilter};
+    use depot_rs::backend::fake::FakeBackend;
+    use depot_rs::backend::{CargoBackend, InstallRequest};
+    use depot_rs::config::UsageConfig;
+    use depot_rs::depot::{Depot, KrateMetadata, fetch_metadata};
+    use depot_rs::errors::Error;
+    use depot_rs::hooks::Hooks;
+    use depot_rs::installer::InstallStrategy;
+    use depot_rs::journal::{Journal, JournalEntry};
+    use depot_rs::operation::{OperationKind, OperationState};
+    use depot_rs::records::InstallRecords;
+    use pretty_assertions::assert_eq;
+    use std::sync::Arc;
+    use versions::SemVer;
+
+    const LATEST: InstallRequest = InstallRequest::Latest(InstallStrategy::SourceOnly);
+
+    async fn listed_state(backend: &Arc<FakeBackend>) -> DepotState {
+        let mut state =
+            DepotState::with_parts(backend.clone(), None, Hooks::default(), Journal::new(None));
+        state.depot = Depot::get_with(
+            backend.as_ref(),
+            &InstallRecords::default(),
+            &UsageConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        state
+    }
+
+    /// Reports an operation back the way the background task does.
+    async fn finished(
+        id: u64,
+        result: Result<(), Error>,
+        backend: &FakeBackend,
+        metadata: Option<KrateMetadata>,
+    ) -> Finished {
+        let tracking = backend.tracking().await.unwrap();
+        Finished {
+            id,
+            result: result.map_err(|e| e.to_string()),
+            installed: Ok((tracking, InstallRecords::default())),
+            metadata,
+            warnings: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn update_reconciles_new_version() {
+        let backend = Arc::new(
+            FakeBackend::default()
+                .with_installed("ripgrep", "14.1.0")
+                .with_latest("ripgrep", "14.1.1"),
+        );
+        let mut state = listed_state(&backend).await;
+
+        let id = state.operations.queue(OperationKind::Update, "ripgrep");
+        state.operations.start(id).unwrap();
+        let result = backend.install("ripgrep", &LATEST).await.map(|_| ());
+        assert_eq!(
+            state.depot.store.0[0].version,
+            SemVer::new("14.1.0").unwrap()
+        );
+
+        let finished = finished(id, result, &backend, None).await;
+        state.finish_operation(finished).unwrap();
+
+        let krate = &state.depot.store.0[0];
+        assert_eq!(krate.version, SemVer::new("14.1.1").unwrap());
+        assert_eq!(krate.binaries, vec!["ripgrep"]);
+        assert_eq!(
+            state.operations.get(id).unwrap().state,
+            OperationState::Succeeded
+        );
+        assert_eq!(backend.calls(), vec!["install ripgrep"]);
+    }
+
+    #[tokio::test]
+    async fn install_reconciles_into_sorted_store() {
+        let backend = Arc::new(
+            FakeBackend::default()
+                .with_installed("bat", "0.24.0")
+                .with_installed("zoxide", "0.9.4")
+                .with_latest("fd-find", "10.2.0"),
+        );
+        let mut state = listed_state(&backend).await;
+
+        let id = state.operations.queue(OperationKind::Install, "fd-find");
+        state.operations.start(id).unwrap();
+        let result = backend.install("fd-find", &LATEST).await.map(|_| ());
+        let metadata = backend.info("fd-find", None).await.unwrap();
+        let finished = finished(id, result, &backend, Some(metadata)).await;
+        state.finish_operation(finished).unwrap();
+
+        let names: Vec<&str> = state
+            .depot
+            .store
+            .0
+            .iter()
+            .map(|k| k.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["bat", "fd-find", "zoxide"]);
+        assert!(state.depot.store.0[1].is_metadata_synced());
+        assert!(!state.operations.is_active("fd-find"));
+    }
+
+    #[tokio::test]
+    async fn failed_uninstall_keeps_krate() {
+        let backend = Arc::new(
+            FakeBackend::default()
+                .with_installed("bat", "0.24.0")
+                .failing("bat"),
+        );
+        let mut state = listed_state(&backend).await;
+
+        let id = state.operations.queue(OperationKind::Uninstall, "bat");
+        state.operations.start(id).unwrap();
+        let result = backend.uninstall("bat").await;
+        let finished = finished(id, result, &backend, None).await;
+        state.finish_operation(finished).unwrap();
+
+        assert_eq!(state.depot.crate_count(), 1);
+        assert!(matches!(
+            state.operations.get(id).unwrap().state,
+            OperationState::Failed(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn forgetting_the_selected_last_crate_clears_the_selection() {
+        let backend = Arc::new(
+            FakeBackend::default()
+                .with_installed("bat", "0.24.0")
+                .with_installed("ripgrep", "14.1.1"),
+        );
+        let mut state = listed_state(&backend).await;
+        state.list_state.select(Some(1));
+
+        state.remove_binaries("ripgrep", &["ripgrep".to_string()]);
+
+        assert_eq!(state.depot.crate_count(), 1);
+        assert_eq!(state.list_state.selected(), None);
+    }
+
+    #[test]
+    fn history_filters_by_crate_and_outcome() {
+        // Entries come from the journal, the library has no way to build them.
+        let entry = |krate: &str, error: Option<&str>| -> JournalEntry {
+            serde_json::from_value(serde_json::json!({
+                "at": 0,
+                "action": "update",
+                "crate": krate,
+                "duration_secs": 0,
+                "error": error,
+            }))
+            .unwrap()
+        };
+        let mut history = HistoryState {
+            entries: vec![
+                entry("bat", None),
+                entry("ripgrep", Some("timed out")),
+                entry("bat", Some("no network")),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(history.filtered().len(), 3);
+
+        history.krate = Some("bat".to_string());
+        assert_eq!(history.filtered().len(), 2);
+
+        history.outcome = OutcomeFilter::Failed;
+        let filtered = history.filtered();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].error.as_deref(), Some("no network"));
+
+        history.krate = None;
+        history.outcome = history.outcome.next();
+        assert_eq!(history.outcome, OutcomeFilter::All);
+        history.list_state.select(Some(1));
+        assert_eq!(history.selected().unwrap().krate, "ripgrep");
+    }
+
+    #[tokio::test]
+    async fn search_failure_is_shown() {
+        let backend = Arc::new(FakeBackend::default());
+        let mut state = listed_state(&backend).await;
+        state.search.searching = true;
+
+        DepotMessage::SearchFailed("search failed: no network".to_string())
+            .handle(&mut state)
+            .unwrap();
+
+        assert!(!state.search.searching);
+        assert_eq!(
+            state.search.error.as_deref(),
+            Some("search failed: no network")
+        );
+    }
+
+    #[tokio::test]
+    async fn details_failure_is_kept() {
+        let backend = Arc::new(FakeBackend::default().failing("bat"));
+        let mut state = listed_state(&backend).await;
+
+        let metadata = fetch_metadata(backend.as_ref(), "bat", None).await;
+        DepotMessage::SearchInfo(Box::new(metadata))
+            .handle(&mut state)
+            .unwrap();
+
+        assert_eq!(
+            state.search.details["bat"].fetch_error(),
+            Some("command failed: cargo info bat")
+        );
+    }
+}
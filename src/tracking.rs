@@ -0,0 +1,336 @@
+//! Cargo's own record of the installed crates, `.crates2.json` in the install root.
+
+use crate::errors::Error;
+use crate::paths;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use versions::SemVer;
+
+const TRACKING_FILE: &str = ".crates2.json";
+/// The older tracking file, which cargo still keeps in sync with [`TRACKING_FILE`].
+const LEGACY_TRACKING_FILE: &str = ".crates.toml";
+
+/// Cargo's own record of the crates installed with `cargo install`, kept in
+/// `.crates2.json` in the install root, `$CARGO_INSTALL_ROOT` or else `$CARGO_HOME`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+pub struct InstallTracking {
+    #[serde(default)]
+    installs: BTreeMap<String, TrackedInstall>,
+    /// Whether there was any tracking data. Without it, a crate missing from the tracking data
+    /// may still be installed.
+    #[serde(skip)]
+    found: bool,
+}
+
+/// The options a crate was installed with.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct TrackedInstall {
+    /// The version requirement given to `cargo install --version`.
+    pub version_req: Option<String>,
+    /// The file names of the binaries it installed.
+    pub bins: BTreeSet<String>,
+    /// The features that were turned on.
+    pub features: BTreeSet<String>,
+    /// Whether `--all-features` was given.
+    pub all_features: bool,
+    /// Whether `--no-default-features` was given.
+    pub no_default_features: bool,
+    /// The build profile, usually `release`.
+    pub profile: String,
+    /// The target triple it was built for.
+    pub target: Option<String>,
+    /// The `rustc -vV` output of the compiler that built it.
+    pub rustc: Option<String>,
+}
+
+impl InstallTracking {
+    /// Reads the tracking file, returning nothing tracked if cargo has not written one yet.
+    pub fn load() -> Result<Self, Error> {
+        let Some(path) = paths::install_root().map(|d| d.join(TRACKING_FILE)) else {
+            return Ok(Self::default());
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    fn parse(s: &str) -> Result<Self, Error> {
+        let mut tracking = if s.trim().is_empty() {
+            Self::default()
+        } else {
+            serde_json::from_str(s)?
+        };
+        tracking.found = true;
+
+        Ok(tracking)
+    }
+
+    /// Whether cargo's tracking data was there to read.
+    pub fn found(&self) -> bool {
+        self.found
+    }
+
+    /// Finds the install of the package called `name`.
+    pub fn get(&self, name: &str) -> Option<&TrackedInstall> {
+        self.installs
+            .iter()
+            .find(|(id, _)| package_name(id) == name)
+            .map(|(_, install)| install)
+    }
+
+    /// The installed version of the package called `name`.
+    pub fn version(&self, name: &str) -> Option<SemVer> {
+        self.installs
+            .keys()
+            .find(|id| package_name(id) == name)
+            .and_then(|id| id.split(' ').nth(1))
+            .and_then(SemVer::new)
+    }
+
+    /// Tracks `name` at `version` as installed from crates.io, replacing any other version.
+    pub fn insert(&mut self, name: &str, version: &SemVer, install: TrackedInstall) {
+        self.found = true;
+        self.installs.retain(|id, _| package_name(id) != name);
+        self.installs.insert(
+            format!("{name} {version} (registry+{})", KrateSource::CRATES_IO[0]),
+            install,
+        );
+    }
+
+    /// Where the package called `name` was installed from.
+    pub fn source(&self, name: &str) -> Option<KrateSource> {
+        self.installs
+            .keys()
+            .find(|id| package_name(id) == name)
+            .and_then(|id| KrateSource::parse(id))
+    }
+}
+
+/// Where an installed crate came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum KrateSource {
+    /// A registry.
+    Registry {
+        /// The index URL, `None` for crates.io.
+        index: Option<String>,
+    },
+    /// A git repository.
+    Git {
+        /// The repository's URL.
+        url: String,
+        /// The commit that was installed, if cargo recorded it.
+        rev: Option<String>,
+    },
+    /// A local directory.
+    Path(String),
+}
+
+impl KrateSource {
+    const CRATES_IO: [&str; 2] = [
+        "https://github.com/rust-lang/crates.io-index",
+        "https://index.crates.io/",
+    ];
+
+    /// > bat 0.25.0 (registry+https://github.com/rust-lang/crates.io-index)
+    /// > foo 0.1.0 (git+https://github.com/foo/foo?branch=main#9f2b6c1)
+    /// > bar 0.1.0 (path+file:///home/pigeon/bar)
+    fn parse(id: &str) -> Option<Self> {
+        let source = id.split_once(" (")?.1.strip_suffix(')')?;
+        let (kind, url) = source.split_once('+')?;
+        match kind {
+            "registry" | "sparse" => {
+                // Cargo wants the `sparse+` prefix in `--index`, but not `registry+`.
+                let index = if kind == "sparse" { source } else { url };
+                let index = (!Self::CRATES_IO.contains(&url)).then(|| index.to_string());
+                Some(Self::Registry { index })
+            }
+            "git" => {
+                let (url, rev) = match url.split_once('#') {
+                    Some((url, rev)) => (url, Some(rev.to_string())),
+                    None => (url, None),
+                };
+                let url = url.split_once('?').map_or(url, |(url, _)| url);
+                Some(Self::Git {
+                    url: url.to_string(),
+                    rev,
+                })
+            }
+            "path" => Some(Self::Path(
+                url.strip_prefix("file://").unwrap_or(url).to_string(),
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Removes `binaries` of `krate` from cargo's tracking files, dropping the crate altogether when
+/// it has no binaries left.
+///
+/// Used for binaries that were deleted by hand, which `cargo uninstall` refuses to handle.
+pub fn forget(krate: &str, binaries: &[String]) -> Result<(), Error> {
+    let Some(root) = paths::install_root() else {
+        return Ok(());
+    };
+
+    let path = root.join(TRACKING_FILE);
+    if path.exists() {
+        let s = fs::read_to_string(&path)?;
+        if !s.trim().is_empty() {
+            let mut tracking: serde_json::Value = serde_json::from_str(&s)?;
+            forget_tracked(&mut tracking, krate, binaries);
+            fs::write(&path, serde_json::to_string(&tracking)?)?;
+        }
+    }
+
+    let path = root.join(LEGACY_TRACKING_FILE);
+    if path.exists() {
+        let mut tracking: toml::Table = toml::from_str(&fs::read_to_string(&path)?)?;
+        forget_legacy(&mut tracking, krate, binaries);
+        fs::write(&path, toml::to_string(&tracking)?)?;
+    }
+
+    Ok(())
+}
+
+/// Edits the tracking file as plain JSON, so that fields depot does not know about are kept.
+fn forget_tracked(tracking: &mut serde_json::Value, krate: &str, binaries: &[String]) {
+    let Some(installs) = tracking.get_mut("installs").and_then(|i| i.as_object_mut()) else {
+        return;
+    };
+
+    installs.retain(|id, install| {
+        if package_name(id) != krate {
+            return true;
+        }
+        let Some(bins) = install.get_mut("bins").and_then(|b| b.as_array_mut()) else {
+            return true;
+        };
+        bins.retain(|b| !b.as_str().is_some_and(|b| binaries.iter().any(|x| x == b)));
+
+        !bins.is_empty()
+    });
+}
+
+/// > [v1]
+/// > "ripgrep 14.1.0 (registry+https://github.com/rust-lang/crates.io-index)" = ["rg"]
+fn forget_legacy(tracking: &mut toml::Table, krate: &str, binaries: &[String]) {
+    let Some(installs) = tracking.get_mut("v1").and_then(|v| v.as_table_mut()) else {
+        return;
+    };
+
+    installs.retain(|id, bins| {
+        if package_name(id) != krate {
+            return true;
+        }
+        let Some(bins) = bins.as_array_mut() else {
+            return true;
+        };
+        bins.retain(|b| !b.as_str().is_some_and(|b| binaries.iter().any(|x| x == b)));
+
+        !bins.is_empty()
+    });
+}
+
+/// > ripgrep 14.1.0 (registry+https://github.com/rust-lang/crates.io-index)
+fn package_name(id: &str) -> &str {
+    id.split(' ').next().unwrap_or(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InstallTracking, KrateSource, forget_legacy, forget_tracked};
+    use pretty_assertions::assert_eq;
+    use versions::SemVer;
+
+    #[test]
+    fn parse_tracking_file() {
+        let s = r#"{"installs":{"bat 0.25.0 (registry+https://github.com/rust-lang/crates.io-index)":{"version_req":null,"bins":["bat"],"features":["regex-fancy"],"all_features":false,"no_default_features":true,"profile":"release","target":"x86_64-unknown-linux-gnu","rustc":"rustc 1.86.0 (05f9846f8 2025-03-31)"}}}"#;
+        let tracking = InstallTracking::parse(s).unwrap();
+        let bat = tracking.get("bat").unwrap();
+
+        assert_eq!(bat.bins.iter().collect::<Vec<_>>(), vec!["bat"]);
+        assert_eq!(bat.features.iter().collect::<Vec<_>>(), vec!["regex-fancy"]);
+        assert!(bat.no_default_features);
+        assert_eq!(bat.profile, "release");
+        assert_eq!(tracking.version("bat"), SemVer::new("0.25.0"));
+        assert!(tracking.get("ba").is_none());
+    }
+
+    #[test]
+    fn parse_sources() {
+        assert_eq!(
+            KrateSource::parse(
+                "bat 0.25.0 (registry+https://github.com/rust-lang/crates.io-index)"
+            ),
+            Some(KrateSource::Registry { index: None })
+        );
+        assert_eq!(
+            KrateSource::parse("bat 0.25.0 (sparse+https://index.crates.io/)"),
+            Some(KrateSource::Registry { index: None })
+        );
+        assert_eq!(
+            KrateSource::parse("foo 0.1.0 (sparse+https://crates.example.com/index/)"),
+            Some(KrateSource::Registry {
+                index: Some("sparse+https://crates.example.com/index/".to_string())
+            })
+        );
+        assert_eq!(
+            KrateSource::parse("foo 0.1.0 (git+https://github.com/foo/foo?branch=main#9f2b6c1)"),
+            Some(KrateSource::Git {
+                url: "https://github.com/foo/foo".to_string(),
+                rev: Some("9f2b6c1".to_string()),
+            })
+        );
+        assert_eq!(
+            KrateSource::parse("bar 0.1.0 (path+file:///home/pigeon/bar)"),
+            Some(KrateSource::Path("/home/pigeon/bar".to_string()))
+        );
+        assert_eq!(KrateSource::parse("bar 0.1.0"), None);
+    }
+
+    #[test]
+    fn parse_empty_tracking_file() {
+        let tracking = InstallTracking::parse("").unwrap();
+
+        assert!(tracking.get("bat").is_none());
+        assert!(tracking.found());
+        assert!(!InstallTracking::default().found());
+    }
+
+    #[test]
+    fn forget_binaries() {
+        let mut tracking: serde_json::Value = serde_json::from_str(
+            r#"{"installs":{"bat 0.25.0 (registry+https://github.com/rust-lang/crates.io-index)":{"bins":["bat"],"profile":"release"},"ripgrep 14.1.0 (registry+https://github.com/rust-lang/crates.io-index)":{"bins":["rg","rg-extra"],"profile":"release"}}}"#,
+        )
+        .unwrap();
+        let mut legacy: toml::Table = toml::from_str(
+            r#"[v1]
+"bat 0.25.0 (registry+https://github.com/rust-lang/crates.io-index)" = ["bat"]
+"ripgrep 14.1.0 (registry+https://github.com/rust-lang/crates.io-index)" = ["rg", "rg-extra"]
+"#,
+        )
+        .unwrap();
+        let binaries = ["bat".to_string(), "rg-extra".to_string()];
+        for krate in ["bat", "ripgrep"] {
+            forget_tracked(&mut tracking, krate, &binaries);
+            forget_legacy(&mut legacy, krate, &binaries);
+        }
+
+        assert_eq!(
+            tracking.to_string(),
+            r#"{"installs":{"ripgrep 14.1.0 (registry+https://github.com/rust-lang/crates.io-index)":{"bins":["rg"],"profile":"release"}}}"#
+        );
+        assert_eq!(
+            toml::to_string(&legacy).unwrap(),
+            r#"[v1]
+"ripgrep 14.1.0 (registry+https://github.com/rust-lang/crates.io-index)" = ["rg"]
+"#
+        );
+    }
+}
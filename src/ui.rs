@@ -1,11 +1,19 @@
-use crate::depot::DepotState;
-use crate::errors::Error;
+use crate::state::DepotState;
 use crate::ui::views::start_view;
+use depot_rs::errors::Error;
 use ratatui::Frame;
 use ratatui::layout::Rect;
 use ratatui::style::{Color, Style};
 use views::View;
 use views::catalog_view::Catalog;
+use views::cleanup_view::Cleanup;
+use views::doctor_view::Doctor;
+use views::drift_view::Drift;
+use views::error_view::ErrorView;
+use views::history_view::History;
+use views::import_view::Import;
+use views::install_view::Install;
+use views::plan_view::Plan;
 use views::start_view::Start;
 use views::update_view::Update;
 
@@ -24,6 +32,14 @@ pub fn render(view: &mut View, state: &mut DepotState, frame: &mut Frame) -> Res
         View::Start(_) => Start::render(&start_view::Start, state, frame)?,
         View::Catalog(_) => Catalog::render(&Catalog, state, frame)?,
         View::Update(_) => Update::render(&Update, state, frame)?,
+        View::Plan(_) => Plan::render(&Plan, state, frame)?,
+        View::Install(_) => Install::render(&Install, state, frame)?,
+        View::Cleanup(_) => Cleanup::render(&Cleanup, state, frame)?,
+        View::Import(_) => Import::render(&Import, state, frame)?,
+        View::Drift(_) => Drift::render(&Drift, state, frame)?,
+        View::Doctor(_) => Doctor::render(&Doctor, state, frame)?,
+        View::History(_) => History::render(&History, state, frame)?,
+        View::Error(_) => ErrorView::render(&ErrorView, state, frame)?,
     }
 
     Ok(())
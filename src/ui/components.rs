@@ -1,2 +1,3 @@
+pub mod compliance_panel;
 pub mod load_progress;
 pub mod select_menu;
@@ -0,0 +1,49 @@
+use crate::ui::{DEFAULT_SECONDARY_COLOR, DEFAULT_STYLE};
+use depot_rs::errors::Error;
+use depot_rs::policy::Violation;
+use ratatui::{
+    style::Style,
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Paragraph},
+};
+use std::collections::HashSet;
+
+/// Lists the violations of the team policy, marking the ones being fixed.
+pub fn new(
+    violations: &[Violation],
+    fixing: &HashSet<String>,
+) -> Result<Paragraph<'static>, Error> {
+    let mut lines: Vec<Line> = if violations.is_empty() {
+        vec![Line::from("All installed crates comply with the policy.")]
+    } else {
+        violations
+            .iter()
+            .map(|v| {
+                let status = if fixing.contains(v.krate()) {
+                    " (fixing...)"
+                } else {
+                    ""
+                };
+                Line::from(format!("{v}{status}"))
+            })
+            .collect()
+    };
+    if !violations.is_empty() {
+        lines.push(Line::from(vec![
+            Span::raw("Press "),
+            Span::styled("p", Style::default().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" to fix them"),
+        ]));
+    }
+
+    let block = Block::default()
+        .title(" Policy ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded);
+    let p = Paragraph::new(lines)
+        .block(block)
+        .style(DEFAULT_STYLE)
+        .centered();
+
+    Ok(p)
+}
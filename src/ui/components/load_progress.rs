@@ -1,4 +1,5 @@
-use crate::{errors::Error, ui::DEFAULT_STYLE};
+use crate::ui::DEFAULT_STYLE;
+use depot_rs::errors::Error;
 use ratatui::widgets::Paragraph;
 use throbber_widgets_tui::{Throbber, ThrobberState};
 
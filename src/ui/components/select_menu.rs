@@ -1,5 +1,5 @@
-use crate::errors::Error;
 use crate::ui::{DEFAULT_SECONDARY_COLOR, DEFAULT_STYLE};
+use depot_rs::errors::Error;
 use ratatui::{
     style::{Modifier, Style},
     text::{Line, Span},
@@ -29,7 +29,51 @@ pub fn new() -> Result<Paragraph<'static>, Error> {
         Span::styled("pdate crates", DEFAULT_STYLE),
     ]);
 
-    let lines = vec![crates, updates];
+    let install = Line::from(vec![
+        Span::styled("󰏗 ", DEFAULT_STYLE),
+        Span::styled(
+            "I",
+            Style::default()
+                .fg(DEFAULT_SECONDARY_COLOR)
+                .add_modifier(Modifier::UNDERLINED),
+        ),
+        Span::styled("nstall crates", DEFAULT_STYLE),
+    ]);
+
+    let cleanup = Line::from(vec![
+        Span::styled("󰃢 Clea", DEFAULT_STYLE),
+        Span::styled(
+            "n",
+            Style::default()
+                .fg(DEFAULT_SECONDARY_COLOR)
+                .add_modifier(Modifier::UNDERLINED),
+        ),
+        Span::styled(" up binaries", DEFAULT_STYLE),
+    ]);
+
+    let doctor = Line::from(vec![
+        Span::styled("󰓙 ", DEFAULT_STYLE),
+        Span::styled(
+            "D",
+            Style::default()
+                .fg(DEFAULT_SECONDARY_COLOR)
+                .add_modifier(Modifier::UNDERLINED),
+        ),
+        Span::styled("octor", DEFAULT_STYLE),
+    ]);
+
+    let history = Line::from(vec![
+        Span::styled("󰋚 ", DEFAULT_STYLE),
+        Span::styled(
+            "H",
+            Style::default()
+                .fg(DEFAULT_SECONDARY_COLOR)
+                .add_modifier(Modifier::UNDERLINED),
+        ),
+        Span::styled("istory", DEFAULT_STYLE),
+    ]);
+
+    let lines = vec![crates, updates, install, cleanup, doctor, history];
     let p = Paragraph::new(lines).centered();
 
     Ok(p)
@@ -1,5 +1,13 @@
 use super::{Drawable, banner};
 use catalog_view::Catalog;
+use cleanup_view::Cleanup;
+use doctor_view::Doctor;
+use drift_view::Drift;
+use error_view::ErrorView;
+use history_view::History;
+use import_view::Import;
+use install_view::Install;
+use plan_view::Plan;
 use ratatui::layout::Constraint;
 use ratatui::layout::Flex;
 use ratatui::layout::{Layout, Rect};
@@ -7,6 +15,14 @@ use start_view::Start;
 use update_view::Update;
 
 pub mod catalog_view;
+pub mod cleanup_view;
+pub mod doctor_view;
+pub mod drift_view;
+pub mod error_view;
+pub mod history_view;
+pub mod import_view;
+pub mod install_view;
+pub mod plan_view;
 pub mod start_view;
 pub mod update_view;
 
@@ -15,6 +31,14 @@ pub enum View {
     Start(Start),
     Catalog(Catalog),
     Update(Update),
+    Plan(Plan),
+    Install(Install),
+    Cleanup(Cleanup),
+    Import(Import),
+    Drift(Drift),
+    Doctor(Doctor),
+    History(History),
+    Error(ErrorView),
 }
 
 impl Default for View {
@@ -1,17 +1,20 @@
 use super::{View, start_view::Start};
 use crate::app::App;
-use crate::depot::{DepotMessage, Krate};
-use crate::errors::ChannelError;
-use crate::events::{AppEvent, Event};
 use crate::ui::{DEFAULT_PRIMARY_COLOR, DEFAULT_SECONDARY_COLOR, DEFAULT_STYLE, HIGHLIGHT_STYLE};
-use crate::{depot::DepotState, errors::Error, keys::Selectable, ui::Drawable};
+use crate::{keys::Selectable, state::DepotState, ui::Drawable};
 use crossterm::event::{KeyCode, KeyEvent};
+use depot_rs::config::Config;
+use depot_rs::conflicts::BinaryConflict;
+use depot_rs::depot::{Krate, format_size};
+use depot_rs::errors::Error;
+use depot_rs::operation::{OperationKind, OperationState};
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
-use ratatui::style::{Modifier, Style, Stylize};
+use ratatui::style::{Color, Modifier, Style, Stylize};
 use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{Block, BorderType, List, ListItem, Paragraph, Wrap};
 use std::rc::Rc;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub struct Catalog;
@@ -19,9 +22,9 @@ pub struct Catalog;
 impl Drawable for Catalog {
     fn render(
         &self,
-        state: &mut crate::depot::DepotState,
+        state: &mut crate::state::DepotState,
         frame: &mut ratatui::Frame,
-    ) -> Result<(), crate::errors::Error> {
+    ) -> Result<(), depot_rs::errors::Error> {
         let layout = Layout::default()
             .direction(Direction::Vertical)
             .constraints(vec![Constraint::Fill(1), Constraint::Length(1)])
@@ -36,7 +39,8 @@ impl Drawable for Catalog {
         render_left(state, frame, main_layout[0])?;
 
         if let Some(ix) = state.list_state.selected() {
-            let krate = &state.depot.store.0[ix];
+            let krate = state.catalog()[ix];
+            let conflicts = state.depot.conflicts.for_krate(&krate.name);
             let title = format!("| {}@{} |", krate.name, krate.version);
             let r_block = Block::bordered()
                 .border_type(BorderType::Rounded)
@@ -46,23 +50,17 @@ impl Drawable for Catalog {
             let right = Layout::default()
                 .direction(ratatui::layout::Direction::Vertical)
                 .constraints(vec![
-                    // Decription.
-                    Constraint::Percentage(20),
-                    // License
-                    Constraint::Percentage(10),
-                    //Rust version.
-                    Constraint::Percentage(10),
-                    // Documentation.
-                    Constraint::Percentage(10),
-                    // Homepage.
-                    Constraint::Percentage(10),
-                    //Repository.
-                    Constraint::Percentage(10),
+                    // Summary.
+                    Constraint::Percentage(60),
+                    // Features.
+                    Constraint::Fill(1),
+                    // Binary conflicts.
+                    Constraint::Length(conflicts.len() as u16),
                 ])
                 .split(inner);
 
             frame.render_widget(r_block, main_layout[1]);
-            render_right(krate, frame, right)?;
+            render_right(krate, &conflicts, frame, right)?;
         }
         self.render_helpline(frame, footer)?;
 
@@ -76,10 +74,22 @@ impl Drawable for Catalog {
             Span::raw(" "),
             Span::raw("to move up/down"),
             Span::raw(", "),
+            Span::raw("s").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to change the sort order"),
+            Span::raw(", "),
+            Span::raw("f").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to show forgotten crates"),
+            Span::raw(", "),
             Span::raw("d").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
             Span::raw(" "),
             Span::raw("to uninstall crate"),
             Span::raw(", "),
+            Span::raw("c").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to cancel"),
+            Span::raw(", "),
             Span::raw("q").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
             Span::raw(" "),
             Span::raw("to go back"),
@@ -98,30 +108,74 @@ fn render_left(state: &mut DepotState, frame: &mut Frame, area: Rect) -> Result<
     Ok(())
 }
 
-fn render_right(krate: &Krate, frame: &mut Frame, area: Rc<[Rect]>) -> Result<(), Error> {
+fn render_right(
+    krate: &Krate,
+    conflicts: &[&BinaryConflict],
+    frame: &mut Frame,
+    area: Rc<[Rect]>,
+) -> Result<(), Error> {
     // NOTE: This assumes every crate must have a description.
     // This is true for crates that have been uploaded to crates.io, but it might break for local
     // crates that don't have a description yet.
     if !&krate.description().is_empty() {
         render_krate_summary(krate, frame, area[0])?;
     }
+    render_krate_features(krate, frame, area[1])?;
+    render_krate_conflicts(conflicts, frame, area[2])?;
 
     Ok(())
 }
 
 fn render_catalog(state: &mut DepotState, frame: &mut Frame, area: Rect) -> Result<(), Error> {
     let krates: Vec<ListItem> = state
-        .depot
-        .store
-        .0
-        .iter()
-        .map(|krate| ListItem::from(krate.name.clone()).fg(DEFAULT_PRIMARY_COLOR))
+        .catalog()
+        .into_iter()
+        .map(|krate| {
+            let mut spans = vec![
+                Span::raw(krate.name.clone()),
+                Span::styled(format!("  {}", krate.size_str()), DEFAULT_STYLE),
+            ];
+            if let Some(operation) = state.operations.last(&krate.name) {
+                match &operation.state {
+                    _ if operation.is_active() => spans.push(Span::styled(
+                        format!("  {}", operation.kind.progressive()),
+                        DEFAULT_STYLE.add_modifier(Modifier::ITALIC),
+                    )),
+                    OperationState::Failed(_) => spans.push(Span::styled(
+                        format!("  {} failed", operation.kind),
+                        Style::new().fg(Color::Red),
+                    )),
+                    OperationState::Cancelled => spans.push(Span::styled(
+                        format!("  {} cancelled", operation.kind),
+                        DEFAULT_STYLE.add_modifier(Modifier::ITALIC),
+                    )),
+                    _ if !operation.warnings.is_empty() => spans.push(Span::styled(
+                        format!("  {}", operation.warnings.join(", ")),
+                        Style::new().fg(Color::Yellow),
+                    )),
+                    _ => {}
+                }
+            }
+            ListItem::from(Line::from(spans)).fg(DEFAULT_PRIMARY_COLOR)
+        })
         .collect();
+    let title = match state.forgotten_filter {
+        Some(window) => format!(
+            "Forgotten crates (unused for {} days, by {})",
+            window.as_secs() / (24 * 60 * 60),
+            state.catalog_sort
+        ),
+        None => format!(
+            "Installed crates ({}, by {})",
+            format_size(state.depot.disk_usage()),
+            state.catalog_sort
+        ),
+    };
     let krate_list = List::new(krates)
         .block(
             Block::bordered()
                 .border_type(ratatui::widgets::BorderType::Rounded)
-                .title("Installed crates")
+                .title(title)
                 .style(DEFAULT_STYLE),
         )
         .highlight_symbol("* ")
@@ -133,13 +187,22 @@ fn render_catalog(state: &mut DepotState, frame: &mut Frame, area: Rect) -> Resu
     Ok(())
 }
 
-fn render_krate_summary(
+pub(super) fn render_krate_summary(
     krate: &Krate,
     frame: &mut ratatui::Frame,
     area: Rect,
 ) -> Result<(), Error> {
     let mut lines = vec![];
 
+    if let Some(e) = krate.parse_error() {
+        let error = text_with_title(" Unparseable", e)?;
+        lines.push(Line::from(error));
+    }
+    if let Some(e) = krate.fetch_error() {
+        let error = text_with_title(" Unavailable", e)?;
+        lines.push(Line::from(error));
+    }
+
     let description = vec![Span::styled(krate.description(), DEFAULT_STYLE)];
     lines.push(Line::from(description));
 
@@ -160,6 +223,30 @@ fn render_krate_summary(
     let rust_version = text_with_title(" Rust version", rv)?;
     lines.push(Line::from(rust_version));
 
+    let size = &krate.size_str();
+    let last_used = &krate.last_used_str();
+    if !krate.binaries.is_empty() {
+        let size = text_with_title("󰋊 Disk usage", size)?;
+        lines.push(Line::from(size));
+        let last_used = text_with_title("󰥔 Last used", last_used)?;
+        lines.push(Line::from(last_used));
+    }
+
+    let installed_at = &krate.installed_at_str();
+    let updated_at = &krate.updated_at_str();
+    if !krate.binaries.is_empty() {
+        let installed_at = text_with_title("󰃰 Installed", installed_at)?;
+        lines.push(Line::from(installed_at));
+        let updated_at = text_with_title("󰚰 Last update", updated_at)?;
+        lines.push(Line::from(updated_at));
+    }
+
+    let method = &krate.install_method_str();
+    if krate.install_method.is_some() {
+        let method = text_with_title("󰏗 Installed as", method)?;
+        lines.push(Line::from(method));
+    }
+
     let docs = &krate.documentation();
     if !docs.is_empty() {
         let docs = text_with_title("󰈙 Documentation", docs)?;
@@ -184,6 +271,58 @@ fn render_krate_summary(
     Ok(())
 }
 
+pub(super) fn render_krate_features(
+    krate: &Krate,
+    frame: &mut ratatui::Frame,
+    area: Rect,
+) -> Result<(), Error> {
+    let features = krate.features();
+    if features.0.is_empty() {
+        return Ok(());
+    }
+
+    let names = features
+        .0
+        .iter()
+        .map(|f| f.name.as_str())
+        .filter(|&n| n != "default");
+    let sections = match krate.enabled_features() {
+        Some(enabled) => {
+            let (on, off): (Vec<&str>, Vec<&str>) = names.partition(|&n| enabled.contains(n));
+            vec![
+                ("󰒓 Enabled features", on.join(", ")),
+                ("󰒓 Not enabled", off.join(", ")),
+            ]
+        }
+        None => vec![("󰒓 Available features", names.collect::<Vec<_>>().join(", "))],
+    };
+
+    let mut lines = vec![];
+    for (title, text) in sections.iter().filter(|(_, t)| !t.is_empty()) {
+        lines.push(Line::from(text_with_title(title, text)?));
+    }
+
+    frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), area);
+
+    Ok(())
+}
+
+fn render_krate_conflicts(
+    conflicts: &[&BinaryConflict],
+    frame: &mut ratatui::Frame,
+    area: Rect,
+) -> Result<(), Error> {
+    let conflicts: Vec<String> = conflicts.iter().map(|c| c.to_string()).collect();
+    let mut lines = vec![];
+    for conflict in &conflicts {
+        lines.push(Line::from(text_with_title(" Conflict", conflict)?));
+    }
+
+    frame.render_widget(Paragraph::new(lines), area);
+
+    Ok(())
+}
+
 fn text_with_title<'a>(title: &'a str, text: &'a str) -> Result<Vec<Span<'a>>, Error> {
     let lines = vec![
         Span::styled(
@@ -205,7 +344,10 @@ impl Selectable for Catalog {
             // Here, we assume all of the crate info has been fetched.
             (_, KeyCode::Char('j')) | (_, KeyCode::Down) => select_next(&mut app.state)?,
             (_, KeyCode::Char('k')) | (_, KeyCode::Up) => select_previous(&mut app.state)?,
+            (_, KeyCode::Char('s')) => toggle_sort(&mut app.state),
+            (_, KeyCode::Char('f')) => toggle_forgotten(&mut app.state)?,
             (_, KeyCode::Char('d')) => delete_selected_crate(app),
+            (_, KeyCode::Char('c')) => cancel_selected_crate(app)?,
             _ => {}
         }
         Ok(())
@@ -213,13 +355,17 @@ impl Selectable for Catalog {
 }
 
 fn select_next(state: &mut DepotState) -> Result<(), Error> {
+    let count = state.catalog().len();
+    if count == 0 {
+        return Ok(());
+    }
     if state.list_state.selected().is_none() {
         state.list_state.select_first();
         return Ok(());
     }
     // Prevents selecting an index out of bounds. This is most likely a bug on ratatui's
     // side.
-    if state.list_state.selected().unwrap() + 1 == state.depot.crate_count() as usize {
+    if state.list_state.selected().unwrap() + 1 == count {
         Ok(())
     } else {
         state.list_state.select_next();
@@ -227,29 +373,52 @@ fn select_next(state: &mut DepotState) -> Result<(), Error> {
     }
 }
 
+/// Switches the sort order, keeping the highlighted crate highlighted.
+fn toggle_sort(state: &mut DepotState) {
+    let selected = state
+        .list_state
+        .selected()
+        .map(|ix| state.catalog()[ix].name.clone());
+    state.catalog_sort = state.catalog_sort.next();
+    if let Some(name) = selected {
+        let ix = state.catalog().iter().position(|k| k.name == name);
+        state.list_state.select(ix);
+    }
+}
+
+/// Switches between every crate and the ones that have not been used within the configured window.
+fn toggle_forgotten(state: &mut DepotState) -> Result<(), Error> {
+    state.forgotten_filter = match state.forgotten_filter {
+        Some(_) => None,
+        None => {
+            let days = Config::load()?.usage.forgotten_after_days;
+            Some(Duration::from_secs(days * 24 * 60 * 60))
+        }
+    };
+    state.list_state.select(None);
+
+    Ok(())
+}
+
 fn select_previous(state: &mut DepotState) -> Result<(), Error> {
     state.list_state.select_previous();
     Ok(())
 }
 
+/// Uninstalls the crate in the background. It stays in the catalog until cargo is done.
 fn delete_selected_crate(app: &mut App) {
     if let Some(ix) = app.state.list_state.selected() {
-        app.state.list_state.select(None);
-        let k = &app.state.depot.store.0[ix];
-        let kk = k.clone();
-        let tx = app.events.get_sender();
-        tokio::spawn(async move {
-            let _ = match &kk.uninstall().await {
-                Ok(_) => tx.send(Event::App(AppEvent::DepotEvent(
-                    DepotMessage::UninstallKrate,
-                ))),
-                Err(_) => tx.send(Event::App(AppEvent::DepotEvent(DepotMessage::DepotError(
-                    ChannelError::UninstallKrate,
-                )))),
-            };
-        });
-        // NOTE: Is it safe to assume `ix` in `app.list_state` is the same as in
-        // `app.state.depot.store`?
-        app.state.depot.store.0.remove(ix);
+        let name = app.state.catalog()[ix].name.clone();
+        app.spawn_operation(OperationKind::Uninstall, name, None);
     };
 }
+
+/// Cancels the uninstall or update of the crate, if one is pending or running.
+fn cancel_selected_crate(app: &mut App) -> Result<(), Error> {
+    if let Some(ix) = app.state.list_state.selected() {
+        let name = app.state.catalog()[ix].name.clone();
+        app.cancel_operation(&name)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,212 @@
+use super::{View, start_view::Start};
+use crate::app::App;
+use crate::keys::Selectable;
+use crate::state::DepotState;
+use crate::ui::{
+    DEFAULT_PRIMARY_COLOR, DEFAULT_SECONDARY_COLOR, DEFAULT_STYLE, Drawable, HIGHLIGHT_STYLE,
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use depot_rs::cleanup::{CleanupItem, orphan_path, remove_orphan};
+use depot_rs::errors::Error;
+use depot_rs::operation::OperationKind;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, List, ListItem, Paragraph};
+use throbber_widgets_tui::Throbber;
+
+#[derive(Debug)]
+pub struct Cleanup;
+
+impl Drawable for Cleanup {
+    fn render(&self, state: &mut DepotState, frame: &mut Frame) -> Result<(), Error> {
+        let throbber_style = Style::new().fg(Color::White).add_modifier(Modifier::ITALIC);
+        let installing = state.operations.active_krates();
+        let items: Vec<ListItem> = state
+            .cleanup
+            .0
+            .iter()
+            .map(|item| {
+                let line = match item {
+                    CleanupItem::Orphan(name) => Line::from(vec![
+                        Span::styled(name.clone(), DEFAULT_STYLE).fg(DEFAULT_PRIMARY_COLOR),
+                        Span::styled("  not owned by any crate", throbber_style),
+                    ]),
+                    CleanupItem::Missing { krate, binary } => {
+                        let mut spans = vec![
+                            Span::styled(binary.clone(), DEFAULT_STYLE).fg(DEFAULT_PRIMARY_COLOR),
+                            Span::styled(format!("  missing from {krate}"), throbber_style),
+                        ];
+                        if installing.contains(krate) {
+                            spans.push(Span::raw("  "));
+                            spans.push(
+                                Throbber::default()
+                                    .style(throbber_style)
+                                    .to_symbol_span(&state.throbber_state),
+                            );
+                            spans.push(Span::styled("reinstalling", throbber_style));
+                        }
+                        Line::from(spans)
+                    }
+                    item => Line::from(
+                        Span::styled(item.name().to_string(), DEFAULT_STYLE)
+                            .fg(DEFAULT_PRIMARY_COLOR),
+                    ),
+                };
+
+                ListItem::from(line)
+            })
+            .collect();
+
+        let title = format!(
+            "Cleanup ({} orphaned, {} missing)",
+            state.cleanup.orphan_count(),
+            state.cleanup.missing_count()
+        );
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(title)
+                    .style(DEFAULT_STYLE),
+            )
+            .highlight_symbol("* ")
+            .highlight_style(HIGHLIGHT_STYLE)
+            .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Fill(1), Constraint::Length(1)])
+            .split(frame.area().inner(Margin::new(10, 3)));
+
+        let (main_area, footer) = (layout[0], layout[1]);
+
+        frame.render_stateful_widget(list, main_area, &mut state.cleanup_list_state);
+        match (&state.cleanup_pending, &state.cleanup_error) {
+            (Some(name), _) => render_confirm(name, frame, footer),
+            (None, Some(e)) => {
+                let error = Paragraph::new(e.clone()).style(Style::new().fg(Color::Red));
+                frame.render_widget(error, footer);
+            }
+            (None, None) => self.render_helpline(frame, footer)?,
+        }
+
+        Ok(())
+    }
+
+    fn render_helpline(&self, frame: &mut Frame, area: Rect) -> Result<(), Error> {
+        let line = Line::from(vec![
+            Span::raw("Press "),
+            Span::raw("k/j").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to move up/down"),
+            Span::raw(", "),
+            Span::raw("d").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to delete an orphan"),
+            Span::raw(", "),
+            Span::raw("r").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to reinstall"),
+            Span::raw(", "),
+            Span::raw("f").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to forget a missing binary"),
+            Span::raw(", "),
+            Span::raw("q").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to go back"),
+        ]);
+
+        let footer_bar = Paragraph::new(line);
+        frame.render_widget(footer_bar, area);
+
+        Ok(())
+    }
+}
+
+/// Asks before deleting an orphan, since nothing can bring it back.
+fn render_confirm(name: &str, frame: &mut Frame, area: Rect) {
+    let path = orphan_path(name).map_or_else(|| name.to_string(), |p| p.display().to_string());
+    let line = Line::from(vec![
+        Span::raw(format!("Delete {path}? Press ")),
+        Span::raw("y").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+        Span::raw(" "),
+        Span::raw("to delete it"),
+        Span::raw(", "),
+        Span::raw("any other key").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+        Span::raw(" "),
+        Span::raw("to keep it"),
+    ]);
+
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+impl Selectable for Cleanup {
+    async fn select(app: &mut App, key: &KeyEvent) -> Result<(), Error> {
+        app.state.cleanup_error = None;
+        if let Some(name) = app.state.cleanup_pending.take() {
+            if key.code == KeyCode::Char('y') {
+                match remove_orphan(&name) {
+                    Ok(()) => app
+                        .state
+                        .cleanup
+                        .0
+                        .retain(|i| !matches!(i, CleanupItem::Orphan(n) if n == &name)),
+                    Err(e) => {
+                        app.state.cleanup_error = Some(format!("Could not delete {name}: {e}"))
+                    }
+                }
+            }
+            return Ok(());
+        }
+
+        let selected = app
+            .state
+            .cleanup_list_state
+            .selected()
+            .and_then(|ix| app.state.cleanup.0.get(ix).cloned().map(|item| (ix, item)));
+
+        match (key.modifiers, key.code, selected) {
+            (_, KeyCode::Esc | KeyCode::Char('q'), _) => app.view = View::Start(Start),
+            (_, KeyCode::Char('j') | KeyCode::Down, _) => select_next(&mut app.state),
+            (_, KeyCode::Char('k') | KeyCode::Up, _) => {
+                app.state.cleanup_list_state.select_previous()
+            }
+            (_, KeyCode::Char('d'), Some((_, CleanupItem::Orphan(name)))) => {
+                app.state.cleanup_pending = Some(name);
+            }
+            (_, KeyCode::Char('r'), Some((_, CleanupItem::Missing { krate, .. })))
+                if !app.state.operations.active_krates().contains(&krate) =>
+            {
+                app.spawn_operation(OperationKind::Install, krate, None);
+            }
+            (_, KeyCode::Char('f'), Some((ix, CleanupItem::Missing { krate, binary }))) => {
+                match app
+                    .state
+                    .forget_binaries(&krate, std::slice::from_ref(&binary))
+                {
+                    Ok(()) => {
+                        app.state.cleanup.0.remove(ix);
+                    }
+                    Err(e) => {
+                        app.state.cleanup_error = Some(format!("Could not forget {binary}: {e}"))
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+fn select_next(state: &mut DepotState) {
+    let last = state.cleanup.0.len().saturating_sub(1);
+    match state.cleanup_list_state.selected() {
+        Some(ix) if ix >= last => {}
+        _ if state.cleanup.0.is_empty() => {}
+        _ => state.cleanup_list_state.select_next(),
+    }
+}
@@ -0,0 +1,119 @@
+use super::{View, start_view::Start};
+use crate::app::App;
+use crate::events::{AppEvent, Event};
+use crate::keys::Selectable;
+use crate::state::{DepotMessage, DepotState};
+use crate::ui::{DEFAULT_PRIMARY_COLOR, DEFAULT_SECONDARY_COLOR, DEFAULT_STYLE, Drawable};
+use crossterm::event::{KeyCode, KeyEvent};
+use depot_rs::doctor::{DoctorReport, Status};
+use depot_rs::errors::Error;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, List, ListItem, Paragraph};
+use throbber_widgets_tui::Throbber;
+
+#[derive(Debug)]
+pub struct Doctor;
+
+impl Drawable for Doctor {
+    fn render(&self, state: &mut DepotState, frame: &mut Frame) -> Result<(), Error> {
+        let hint_style = Style::new().fg(Color::White).add_modifier(Modifier::ITALIC);
+        let items: Vec<ListItem> = match &state.doctor {
+            None => vec![ListItem::from(Line::from(vec![
+                Throbber::default()
+                    .style(hint_style)
+                    .to_symbol_span(&state.throbber_state),
+                Span::styled("running checks", hint_style),
+            ]))],
+            Some(report) => report
+                .0
+                .iter()
+                .map(|d| {
+                    let status_color = match d.status {
+                        Status::Pass => Color::Green,
+                        Status::Warning => DEFAULT_PRIMARY_COLOR,
+                        Status::Fail => Color::Red,
+                    };
+                    let mut lines = vec![Line::from(vec![
+                        Span::raw(format!("{:<7}", format!("[{}]", d.status))).fg(status_color),
+                        Span::raw(format!("{:<18}", d.name)).fg(DEFAULT_PRIMARY_COLOR),
+                        Span::raw(d.detail.clone()),
+                    ])];
+                    if let Some(hint) = &d.hint {
+                        lines.push(Line::from(vec![
+                            Span::raw(" ".repeat(25)),
+                            Span::styled(hint.clone(), hint_style),
+                        ]));
+                    }
+
+                    ListItem::from(lines)
+                })
+                .collect(),
+        };
+
+        let list = List::new(items).block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title("Doctor")
+                .style(DEFAULT_STYLE),
+        );
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Fill(1), Constraint::Length(1)])
+            .split(frame.area().inner(Margin::new(10, 3)));
+
+        let (main_area, footer) = (layout[0], layout[1]);
+
+        frame.render_widget(list, main_area);
+        self.render_helpline(frame, footer)?;
+
+        Ok(())
+    }
+
+    fn render_helpline(&self, frame: &mut Frame, area: Rect) -> Result<(), Error> {
+        let line = Line::from(vec![
+            Span::raw("Press "),
+            Span::raw("r").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to run the checks again"),
+            Span::raw(", "),
+            Span::raw("q").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to go back"),
+        ]);
+
+        let footer_bar = Paragraph::new(line);
+        frame.render_widget(footer_bar, area);
+
+        Ok(())
+    }
+}
+
+impl Selectable for Doctor {
+    async fn select(app: &mut App, key: &KeyEvent) -> Result<(), Error> {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => app.view = View::Start(Start),
+            KeyCode::Char('r') if app.state.doctor.is_some() => spawn_doctor(app),
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Runs the checks in the background, since some of them wait for the network.
+pub(super) fn spawn_doctor(app: &mut App) {
+    app.state.doctor = None;
+
+    let backend = app.state.backend.clone();
+    let tx = app.events.get_sender();
+    tokio::spawn(async move {
+        let report = DoctorReport::run(backend.as_ref()).await;
+        let _ = tx.send(Event::App(AppEvent::DepotEvent(DepotMessage::Diagnosed(
+            report,
+        ))));
+    });
+}
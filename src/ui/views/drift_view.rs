@@ -0,0 +1,183 @@
+use super::{View, start_view::Start};
+use crate::app::App;
+use crate::keys::Selectable;
+use crate::state::DepotState;
+use crate::ui::{
+    DEFAULT_PRIMARY_COLOR, DEFAULT_SECONDARY_COLOR, DEFAULT_STYLE, Drawable, HIGHLIGHT_STYLE,
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use depot_rs::drift::{self, DriftKind};
+use depot_rs::errors::Error;
+use depot_rs::operation::OperationKind;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, BorderType, List, ListItem, Paragraph};
+use throbber_widgets_tui::Throbber;
+
+#[derive(Debug)]
+pub struct Drift;
+
+impl Drawable for Drift {
+    fn render(&self, state: &mut DepotState, frame: &mut Frame) -> Result<(), Error> {
+        let throbber_style = Style::new().fg(Color::White).add_modifier(Modifier::ITALIC);
+        let header_style = Style::default()
+            .fg(DEFAULT_PRIMARY_COLOR)
+            .add_modifier(Modifier::BOLD);
+        let fixing = state.operations.active_krates();
+
+        let mut items: Vec<ListItem> = vec![];
+        let mut previous = None;
+        for drift in &state.drift.0 {
+            let mut lines = vec![];
+            // Each group starts with a header, which is part of its first item so that the
+            // selection stays in step with the report.
+            if previous != Some(drift.kind) {
+                if previous.is_some() {
+                    lines.push(Line::raw(""));
+                }
+                let count = state.drift.group(drift.kind).count();
+                lines.push(Line::styled(
+                    format!("{} ({count})", drift.kind),
+                    header_style,
+                ));
+                previous = Some(drift.kind);
+            }
+
+            let mut spans = vec![
+                Span::styled(format!("  {}  ", drift.name), DEFAULT_STYLE),
+                Span::styled(drift.detail.clone(), throbber_style),
+            ];
+            if fixing.contains(&drift.name) {
+                spans.push(Span::raw("  "));
+                spans.push(
+                    Throbber::default()
+                        .style(throbber_style)
+                        .to_symbol_span(&state.throbber_state),
+                );
+                spans.push(Span::styled("fixing", throbber_style));
+            }
+            lines.push(Line::from(spans));
+
+            items.push(ListItem::new(Text::from(lines)));
+        }
+
+        let title = if state.drift.is_empty() {
+            "Drift (none)".to_string()
+        } else {
+            format!("Drift ({} differences)", state.drift.0.len())
+        };
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(title)
+                    .style(DEFAULT_STYLE),
+            )
+            .highlight_symbol("* ")
+            .highlight_style(HIGHLIGHT_STYLE)
+            .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Fill(1), Constraint::Length(1)])
+            .split(frame.area().inner(Margin::new(10, 3)));
+
+        let (main_area, footer) = (layout[0], layout[1]);
+
+        frame.render_stateful_widget(list, main_area, &mut state.drift_list_state);
+        self.render_helpline(frame, footer)?;
+
+        Ok(())
+    }
+
+    fn render_helpline(&self, frame: &mut Frame, area: Rect) -> Result<(), Error> {
+        let key = |k| Span::raw(k).style(Style::new().fg(DEFAULT_SECONDARY_COLOR));
+        let line = Line::from(vec![
+            Span::raw("Press "),
+            key("ENTER"),
+            Span::raw(" "),
+            Span::raw("to fix the selected crate"),
+            Span::raw(", "),
+            key("i"),
+            Span::raw(" "),
+            Span::raw("to install missing"),
+            Span::raw(", "),
+            key("x"),
+            Span::raw(" "),
+            Span::raw("to uninstall extras"),
+            Span::raw(", "),
+            key("v"),
+            Span::raw(" "),
+            Span::raw("to fix versions"),
+            Span::raw(", "),
+            key("o"),
+            Span::raw(" "),
+            Span::raw("to fix options"),
+            Span::raw(", "),
+            key("q"),
+            Span::raw(" "),
+            Span::raw("to go back"),
+        ]);
+
+        let footer_bar = Paragraph::new(line);
+        frame.render_widget(footer_bar, area);
+
+        Ok(())
+    }
+}
+
+impl Selectable for Drift {
+    async fn select(app: &mut App, key: &KeyEvent) -> Result<(), Error> {
+        match (key.modifiers, key.code) {
+            (_, KeyCode::Esc | KeyCode::Char('q')) => app.view = View::Start(Start),
+            (_, KeyCode::Char('j')) | (_, KeyCode::Down)
+                if app.state.drift_list_state.selected().map_or(0, |ix| ix + 1)
+                    < app.state.drift.0.len() =>
+            {
+                app.state.drift_list_state.select_next();
+            }
+            (_, KeyCode::Char('k')) | (_, KeyCode::Up) => {
+                app.state.drift_list_state.select_previous()
+            }
+            (_, KeyCode::Enter) => {
+                if let Some(drift) = app
+                    .state
+                    .drift_list_state
+                    .selected()
+                    .and_then(|ix| app.state.drift.0.get(ix))
+                    .cloned()
+                {
+                    fix(app, drift);
+                }
+            }
+            (_, KeyCode::Char('i')) => fix_group(app, DriftKind::Missing),
+            (_, KeyCode::Char('x')) => fix_group(app, DriftKind::Extra),
+            (_, KeyCode::Char('v')) => fix_group(app, DriftKind::Version),
+            (_, KeyCode::Char('o')) => fix_group(app, DriftKind::Options),
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+fn fix_group(app: &mut App, kind: DriftKind) {
+    let group: Vec<drift::Drift> = app.state.drift.group(kind).cloned().collect();
+    for drift in group {
+        fix(app, drift);
+    }
+}
+
+/// Installs the crate as the Depotfile describes it, or uninstalls it when it is not in there.
+fn fix(app: &mut App, drift: drift::Drift) {
+    if app.state.operations.active_krates().contains(&drift.name) {
+        return;
+    }
+
+    match drift.spec {
+        Some(spec) => app.spawn_operation(OperationKind::Install, drift.name, Some(spec)),
+        None => app.spawn_operation(OperationKind::Uninstall, drift.name, None),
+    }
+}
@@ -0,0 +1,85 @@
+use super::{View, center, start_view::Start};
+use crate::app::App;
+use crate::keys::Selectable;
+use crate::state::DepotState;
+use crate::ui::{DEFAULT_SECONDARY_COLOR, DEFAULT_STYLE, Drawable};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use depot_rs::errors::Error;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, Paragraph, Wrap};
+
+/// Shown instead of the start view when the installed crates could not be listed or looked up.
+#[derive(Debug)]
+pub struct ErrorView;
+
+impl Drawable for ErrorView {
+    fn render(&self, state: &mut DepotState, frame: &mut Frame) -> Result<(), Error> {
+        let message = state.startup_error.as_deref().unwrap_or_default();
+        let paragraph = Paragraph::new(vec![
+            Line::raw("The installed crates could not be loaded:"),
+            Line::raw(""),
+            Line::styled(message.to_string(), Style::new().fg(Color::Red)),
+            Line::raw(""),
+            Line::raw("Run `depot doctor` to check your setup."),
+        ])
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title("Error")
+                .style(DEFAULT_STYLE),
+        );
+
+        let area = center(
+            frame.area(),
+            Constraint::Percentage(60),
+            Constraint::Length(10),
+        );
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Fill(1), Constraint::Length(1)])
+            .split(area);
+
+        frame.render_widget(paragraph, layout[0]);
+        self.render_helpline(frame, layout[1])?;
+
+        Ok(())
+    }
+
+    fn render_helpline(&self, frame: &mut Frame, area: Rect) -> Result<(), Error> {
+        let line = Line::from(vec![
+            Span::raw("Press "),
+            Span::raw("r").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to retry"),
+            Span::raw(", "),
+            Span::raw("q").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to quit"),
+        ]);
+
+        let footer_bar = Paragraph::new(line);
+        frame.render_widget(footer_bar, area);
+
+        Ok(())
+    }
+}
+
+impl Selectable for ErrorView {
+    async fn select(app: &mut App, key: &KeyEvent) -> Result<(), Error> {
+        match (key.modifiers, key.code) {
+            (_, KeyCode::Esc | KeyCode::Char('q'))
+            | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => app.quit(),
+            (_, KeyCode::Char('r')) => {
+                app.list_krates();
+                app.view = View::Start(Start);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+use super::{View, start_view::Start};
+use crate::app::App;
+use crate::keys::Selectable;
+use crate::state::DepotState;
+use crate::ui::{
+    DEFAULT_PRIMARY_COLOR, DEFAULT_SECONDARY_COLOR, DEFAULT_STYLE, Drawable, HIGHLIGHT_STYLE,
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use depot_rs::errors::Error;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, List, ListItem, Paragraph};
+use std::fs;
+
+#[derive(Debug)]
+pub struct History;
+
+impl Drawable for History {
+    fn render(&self, state: &mut DepotState, frame: &mut Frame) -> Result<(), Error> {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Fill(1), Constraint::Length(1)])
+            .split(frame.area().inner(Margin::new(10, 3)));
+        let (main_area, footer) = (layout[0], layout[1]);
+
+        match &state.history.log {
+            Some(log) => render_log(state, log, frame, main_area),
+            None => render_entries(state, frame, main_area),
+        }
+        self.render_helpline(frame, footer)?;
+
+        Ok(())
+    }
+
+    fn render_helpline(&self, frame: &mut Frame, area: Rect) -> Result<(), Error> {
+        let line = Line::from(vec![
+            Span::raw("Press "),
+            Span::raw("k/j").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to move up/down"),
+            Span::raw(", "),
+            Span::raw("ENTER").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to open the log"),
+            Span::raw(", "),
+            Span::raw("c").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to filter by crate"),
+            Span::raw(", "),
+            Span::raw("o").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to filter by outcome"),
+            Span::raw(", "),
+            Span::raw("q").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to go back"),
+        ]);
+
+        let footer_bar = Paragraph::new(line);
+        frame.render_widget(footer_bar, area);
+
+        Ok(())
+    }
+}
+
+fn render_entries(state: &mut DepotState, frame: &mut Frame, area: Rect) {
+    if let Some(e) = &state.history.error {
+        let paragraph = Paragraph::new(format!("Could not read the journal: {e}"))
+            .style(Style::new().fg(Color::Red))
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title("History")
+                    .style(DEFAULT_STYLE),
+            );
+        frame.render_widget(paragraph, area);
+        return;
+    }
+
+    let detail_style = Style::new().fg(Color::White).add_modifier(Modifier::ITALIC);
+    let items: Vec<ListItem> = state
+        .history
+        .filtered()
+        .into_iter()
+        .map(|entry| {
+            let outcome = match &entry.error {
+                None if !entry.warnings.is_empty() => Span::styled(
+                    format!("  ok, but {}", entry.warnings.join(", ")),
+                    Style::new().fg(Color::Yellow),
+                ),
+                None => Span::styled("  ok", Style::new().fg(Color::Green)),
+                Some(_) if entry.cancelled() => {
+                    Span::styled("  cancelled", Style::new().fg(Color::Yellow))
+                }
+                Some(e) => Span::styled(format!("  failed: {e}"), Style::new().fg(Color::Red)),
+            };
+            ListItem::from(Line::from(vec![
+                Span::styled(format!("{}  ", entry.at_str()), DEFAULT_STYLE),
+                Span::styled(format!("{:<10}", entry.action.to_string()), DEFAULT_STYLE),
+                Span::raw(entry.krate.clone()).fg(DEFAULT_PRIMARY_COLOR),
+                Span::styled(
+                    format!("  {}  {}s", entry.versions_str(), entry.duration_secs),
+                    detail_style,
+                ),
+                outcome,
+            ]))
+        })
+        .collect();
+
+    let title = format!(
+        "History ({}, {})",
+        state.history.krate.as_deref().unwrap_or("every crate"),
+        state.history.outcome
+    );
+    let list = List::new(items)
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(title)
+                .style(DEFAULT_STYLE),
+        )
+        .highlight_symbol("* ")
+        .highlight_style(HIGHLIGHT_STYLE)
+        .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+
+    frame.render_stateful_widget(list, area, &mut state.history.list_state);
+}
+
+/// Shows the end of the log, where cargo reports what went wrong.
+fn render_log(state: &DepotState, log: &str, frame: &mut Frame, area: Rect) {
+    let title = match state.history.selected() {
+        Some(entry) => format!("| {} {} |", entry.action, entry.krate),
+        None => "Log".to_string(),
+    };
+    let height = area.height.saturating_sub(2) as usize;
+    let offset = log.lines().count().saturating_sub(height);
+    let paragraph = Paragraph::new(log.to_string())
+        .scroll((offset as u16, 0))
+        .block(
+            Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(title)
+                .style(DEFAULT_STYLE),
+        );
+
+    frame.render_widget(paragraph, area);
+}
+
+impl Selectable for History {
+    async fn select(app: &mut App, key: &KeyEvent) -> Result<(), Error> {
+        let history = &mut app.state.history;
+        if history.log.is_some() {
+            if let KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter = key.code {
+                history.log = None;
+            }
+            return Ok(());
+        }
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => app.view = View::Start(Start),
+            KeyCode::Char('j') | KeyCode::Down => history.list_state.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => history.list_state.select_previous(),
+            KeyCode::Enter => {
+                if let Some(entry) = history.selected() {
+                    // Logs may have been deleted or rotated since.
+                    history.log = Some(match &entry.log {
+                        Some(path) => fs::read_to_string(path)
+                            .unwrap_or_else(|_| format!("Log not found: {}", path.display())),
+                        None => "No commands were run.".to_string(),
+                    });
+                }
+            }
+            // Narrows the list down to the highlighted crate, or lists every crate again.
+            KeyCode::Char('c') => {
+                history.krate = match history.krate {
+                    Some(_) => None,
+                    None => history.selected().map(|e| e.krate.clone()),
+                };
+                history.list_state.select(None);
+            }
+            KeyCode::Char('o') => {
+                history.outcome = history.outcome.next();
+                history.list_state.select(None);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Reads the journal again and opens the history view.
+pub(super) fn open_history(app: &mut App) {
+    let history = &mut app.state.history;
+    match app.state.journal.entries() {
+        Ok(mut entries) => {
+            entries.reverse();
+            history.entries = entries;
+            history.error = None;
+        }
+        Err(e) => {
+            history.entries = vec![];
+            history.error = Some(e.to_string());
+        }
+    }
+    history.list_state.select(None);
+    history.log = None;
+    app.view = View::History(History);
+}
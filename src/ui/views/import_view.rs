@@ -0,0 +1,141 @@
+use super::{View, start_view::Start};
+use crate::app::App;
+use crate::keys::Selectable;
+use crate::state::DepotState;
+use crate::ui::{
+    DEFAULT_PRIMARY_COLOR, DEFAULT_SECONDARY_COLOR, DEFAULT_STYLE, Drawable, HIGHLIGHT_STYLE,
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use depot_rs::depotfile::ToolSpec;
+use depot_rs::errors::Error;
+use depot_rs::operation::OperationKind;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, List, ListItem, Paragraph};
+use throbber_widgets_tui::Throbber;
+
+#[derive(Debug)]
+pub struct Import;
+
+impl Drawable for Import {
+    fn render(&self, state: &mut DepotState, frame: &mut Frame) -> Result<(), Error> {
+        let throbber_style = Style::new().fg(Color::White).add_modifier(Modifier::ITALIC);
+        let installing = state.operations.active_krates();
+        let missing: Vec<&str> = state
+            .import
+            .missing(&state.depot.store)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+
+        let items: Vec<ListItem> = state
+            .import
+            .crates
+            .iter()
+            .map(|(name, spec)| {
+                let mut spans = vec![
+                    Span::raw(format!("{name}  {}", spec.summary())).fg(DEFAULT_PRIMARY_COLOR),
+                    Span::raw("  "),
+                ];
+                if installing.contains(name) {
+                    spans.push(
+                        Throbber::default()
+                            .style(throbber_style)
+                            .to_symbol_span(&state.throbber_state),
+                    );
+                    spans.push(Span::styled("installing", throbber_style));
+                } else if state.listing {
+                    spans.push(Span::styled("checking", throbber_style));
+                } else if missing.contains(&name.as_str()) {
+                    spans.push(Span::styled("missing", throbber_style));
+                } else {
+                    spans.push(Span::styled("(installed)", throbber_style));
+                }
+
+                ListItem::from(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(
+                Block::bordered()
+                    .border_type(BorderType::Rounded)
+                    .title(if state.listing {
+                        "Import".to_string()
+                    } else {
+                        format!("Import ({} missing)", missing.len())
+                    })
+                    .style(DEFAULT_STYLE),
+            )
+            .highlight_symbol("* ")
+            .highlight_style(HIGHLIGHT_STYLE)
+            .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Fill(1), Constraint::Length(1)])
+            .split(frame.area().inner(Margin::new(10, 3)));
+
+        let (main_area, footer) = (layout[0], layout[1]);
+
+        frame.render_stateful_widget(list, main_area, &mut state.import_list_state);
+        self.render_helpline(frame, footer)?;
+
+        Ok(())
+    }
+
+    fn render_helpline(&self, frame: &mut Frame, area: Rect) -> Result<(), Error> {
+        let line = Line::from(vec![
+            Span::raw("Press "),
+            Span::raw("k/j").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to move up/down"),
+            Span::raw(", "),
+            Span::raw("ENTER").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to install the missing crates"),
+            Span::raw(", "),
+            Span::raw("q").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to go back"),
+        ]);
+
+        let footer_bar = Paragraph::new(line);
+        frame.render_widget(footer_bar, area);
+
+        Ok(())
+    }
+}
+
+impl Selectable for Import {
+    async fn select(app: &mut App, key: &KeyEvent) -> Result<(), Error> {
+        match (key.modifiers, key.code) {
+            (_, KeyCode::Esc | KeyCode::Char('q')) => app.view = View::Start(Start),
+            (_, KeyCode::Char('j')) | (_, KeyCode::Down) => {
+                app.state.import_list_state.select_next()
+            }
+            (_, KeyCode::Char('k')) | (_, KeyCode::Up) => {
+                app.state.import_list_state.select_previous()
+            }
+            (_, KeyCode::Enter) if !app.state.listing => {
+                let installing = app.state.operations.active_krates();
+                let missing: Vec<(String, ToolSpec)> = app
+                    .state
+                    .import
+                    .missing(&app.state.depot.store)
+                    .into_iter()
+                    .filter(|(name, _)| !installing.contains(*name))
+                    .map(|(name, spec)| (name.to_string(), spec.clone()))
+                    .collect();
+                for (name, spec) in missing {
+                    app.spawn_operation(OperationKind::Install, name, Some(spec));
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
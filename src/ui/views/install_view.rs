@@ -0,0 +1,320 @@
+use super::catalog_view::{render_krate_features, render_krate_summary};
+use super::{View, start_view::Start};
+use crate::app::App;
+use crate::events::{AppEvent, Event};
+use crate::keys::Selectable;
+use crate::state::{DepotMessage, DepotState};
+use crate::ui::{
+    DEFAULT_PRIMARY_COLOR, DEFAULT_SECONDARY_COLOR, DEFAULT_STYLE, Drawable, HIGHLIGHT_STYLE,
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use depot_rs::config::Config;
+use depot_rs::depot::{Krate, fetch_metadata};
+use depot_rs::errors::Error;
+use depot_rs::operation::OperationKind;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Margin, Position, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, BorderType, List, ListItem, Paragraph, Wrap};
+use throbber_widgets_tui::Throbber;
+
+#[derive(Debug)]
+pub struct Install;
+
+impl Drawable for Install {
+    fn render(&self, state: &mut DepotState, frame: &mut Frame) -> Result<(), Error> {
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Fill(1), Constraint::Length(1)])
+            .split(frame.area().inner(Margin::new(5, 5)));
+
+        let (main_area, footer) = (layout[0], layout[1]);
+        let main_layout = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![Constraint::Percentage(30), Constraint::Fill(1)])
+            .split(main_area);
+        let left = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(3), Constraint::Fill(1)])
+            .split(main_layout[0]);
+
+        render_search_box(state, frame, left[0])?;
+        render_results(state, frame, left[1])?;
+        render_details(state, frame, main_layout[1])?;
+        self.render_helpline(frame, footer)?;
+
+        Ok(())
+    }
+
+    fn render_helpline(&self, frame: &mut Frame, area: Rect) -> Result<(), Error> {
+        let line = Line::from(vec![
+            Span::raw("Press "),
+            Span::raw("/").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to search"),
+            Span::raw(", "),
+            Span::raw("k/j").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to move up/down"),
+            Span::raw(", "),
+            Span::raw("ENTER").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to install crate"),
+            Span::raw(", "),
+            Span::raw("c").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to cancel"),
+            Span::raw(", "),
+            Span::raw("q").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to go back"),
+        ]);
+
+        let footer_bar = Paragraph::new(line);
+        frame.render_widget(footer_bar, area);
+
+        Ok(())
+    }
+}
+
+fn render_search_box(state: &DepotState, frame: &mut Frame, area: Rect) -> Result<(), Error> {
+    let border_style = if state.search.editing {
+        Style::new().fg(DEFAULT_SECONDARY_COLOR)
+    } else {
+        DEFAULT_STYLE
+    };
+    let search_box = Paragraph::new(state.search.query.as_str()).block(
+        Block::bordered()
+            .border_type(BorderType::Rounded)
+            .border_style(border_style)
+            .title("Search crates.io"),
+    );
+    frame.render_widget(search_box.style(DEFAULT_STYLE), area);
+
+    if state.search.editing {
+        frame.set_cursor_position(Position::new(
+            area.x + 1 + state.search.query.chars().count() as u16,
+            area.y + 1,
+        ));
+    }
+
+    Ok(())
+}
+
+fn render_results(state: &mut DepotState, frame: &mut Frame, area: Rect) -> Result<(), Error> {
+    let block = Block::bordered()
+        .border_type(BorderType::Rounded)
+        .title("Results")
+        .style(DEFAULT_STYLE);
+
+    if state.search.searching {
+        let throbber = Throbber::default()
+            .label("searching")
+            .style(DEFAULT_STYLE)
+            .to_line(&state.throbber_state);
+        frame.render_widget(Paragraph::new(throbber).block(block), area);
+        return Ok(());
+    }
+    if let Some(e) = &state.search.error {
+        let error = Paragraph::new(e.clone())
+            .style(Style::new().fg(Color::Red))
+            .wrap(Wrap { trim: true })
+            .block(block);
+        frame.render_widget(error, area);
+        return Ok(());
+    }
+
+    let throbber_style = Style::new().fg(Color::White).add_modifier(Modifier::ITALIC);
+    let installing = state.operations.active_krates();
+    let results: Vec<ListItem> = state
+        .search
+        .results
+        .iter()
+        .map(|r| {
+            let mut spans = vec![Span::styled(
+                format!("{}  {}", r.name, r.version),
+                DEFAULT_STYLE,
+            )];
+            if installing.contains(&r.name) {
+                spans.push(Span::raw("  "));
+                spans.push(
+                    Throbber::default()
+                        .style(throbber_style)
+                        .to_symbol_span(&state.throbber_state),
+                );
+                spans.push(Span::styled("installing", throbber_style));
+            } else if state.depot.store.0.iter().any(|k| k.name == r.name) {
+                spans.push(Span::styled("  (installed)", throbber_style));
+            }
+
+            ListItem::from(Line::from(spans)).fg(DEFAULT_PRIMARY_COLOR)
+        })
+        .collect();
+
+    let list = List::new(results)
+        .block(block)
+        .highlight_symbol("* ")
+        .highlight_style(HIGHLIGHT_STYLE)
+        .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+
+    frame.render_stateful_widget(list, area, &mut state.search.list_state);
+
+    Ok(())
+}
+
+fn render_details(state: &DepotState, frame: &mut Frame, area: Rect) -> Result<(), Error> {
+    let Some(result) = state.search.selected() else {
+        return Ok(());
+    };
+
+    let block = Block::bordered()
+        .border_type(BorderType::Rounded)
+        .style(DEFAULT_STYLE)
+        .title(format!("| {}@{} |", result.name, result.version));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let krate = state
+        .search
+        .details
+        .get(&result.name)
+        .map(|m| Krate::from_metadata(m.clone()));
+    let status = match &krate {
+        Some(krate) => match krate.fetch_error() {
+            Some(e) => Span::styled(
+                format!("could not fetch details: {e}"),
+                Style::new().fg(Color::Red),
+            ),
+            None => {
+                let details = Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints(vec![Constraint::Percentage(60), Constraint::Fill(1)])
+                    .split(inner);
+                render_krate_summary(krate, frame, details[0])?;
+                render_krate_features(krate, frame, details[1])?;
+                return Ok(());
+            }
+        },
+        None => Span::styled("fetching details...", DEFAULT_STYLE).italic(),
+    };
+
+    // Until the details are there, or when they can't be, the search tells what it can.
+    let description = result.description.clone().unwrap_or_default();
+    let text = vec![
+        Line::from(Span::styled(description, DEFAULT_STYLE)),
+        Line::raw(""),
+        Line::from(status),
+    ];
+    frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: true }), inner);
+
+    Ok(())
+}
+
+impl Selectable for Install {
+    async fn select(app: &mut App, key: &KeyEvent) -> Result<(), Error> {
+        if app.state.search.editing {
+            match key.code {
+                KeyCode::Char(c) => app.state.search.query.push(c),
+                KeyCode::Backspace => {
+                    app.state.search.query.pop();
+                }
+                KeyCode::Enter => {
+                    app.state.search.editing = false;
+                    search(app)?;
+                }
+                KeyCode::Esc => app.state.search.editing = false,
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        match (key.modifiers, key.code) {
+            (_, KeyCode::Esc | KeyCode::Char('q')) => app.view = View::Start(Start),
+            (_, KeyCode::Char('/')) => app.state.search.editing = true,
+            (_, KeyCode::Char('j')) | (_, KeyCode::Down) => {
+                if app.state.search.list_state.selected().is_none() {
+                    app.state.search.list_state.select_first();
+                } else if app.state.search.list_state.selected().unwrap() + 1
+                    < app.state.search.results.len()
+                {
+                    app.state.search.list_state.select_next();
+                }
+                fetch_details(app);
+            }
+            (_, KeyCode::Char('k')) | (_, KeyCode::Up) => {
+                app.state.search.list_state.select_previous();
+                fetch_details(app);
+            }
+            (_, KeyCode::Enter) => {
+                if let Some(result) = app.state.search.selected() {
+                    let name = result.name.clone();
+                    app.spawn_operation(OperationKind::Install, name, None);
+                }
+            }
+            (_, KeyCode::Char('c')) => {
+                if let Some(result) = app.state.search.selected() {
+                    let name = result.name.clone();
+                    app.cancel_operation(&name)?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
+
+/// Searches the configured registry in the background.
+fn search(app: &mut App) -> Result<(), Error> {
+    let query = app.state.search.query.trim().to_string();
+    if query.is_empty() {
+        return Ok(());
+    }
+
+    let config = match Config::load() {
+        Ok(config) => config.search,
+        Err(e) => {
+            app.state.search.error = Some(format!("search failed: {e}"));
+            return Ok(());
+        }
+    };
+    app.state.search.searching = true;
+    app.state.search.error = None;
+    let backend = app.state.backend.clone();
+    let tx = app.events.get_sender();
+    tokio::spawn(async move {
+        match backend.search(&query, &config).await {
+            Ok(r) => tx.send(Event::App(AppEvent::DepotEvent(
+                DepotMessage::SearchResults(r),
+            ))),
+            Err(e) => tx.send(Event::App(AppEvent::DepotEvent(
+                DepotMessage::SearchFailed(format!("search failed: {e}")),
+            ))),
+        }
+    });
+
+    Ok(())
+}
+
+/// Fetches the metadata of the highlighted result for the detail pane, unless it has already been
+/// requested.
+fn fetch_details(app: &mut App) {
+    let Some(result) = app.state.search.selected() else {
+        return;
+    };
+    let name = result.name.clone();
+    if !app.state.search.requested.insert(name.clone()) {
+        return;
+    }
+
+    let backend = app.state.backend.clone();
+    let tx = app.events.get_sender();
+    tokio::spawn(async move {
+        // A failure is kept with the details, so that the pane can show it.
+        let metadata = fetch_metadata(backend.as_ref(), &name, None).await;
+        let _ = tx.send(Event::App(AppEvent::DepotEvent(DepotMessage::SearchInfo(
+            Box::new(metadata),
+        ))));
+    });
+}
@@ -0,0 +1,126 @@
+use super::View;
+use super::update_view::Update;
+use crate::app::App;
+use crate::keys::Selectable;
+use crate::state::DepotState;
+use crate::ui::{
+    DEFAULT_PRIMARY_COLOR, DEFAULT_SECONDARY_COLOR, DEFAULT_STYLE, Drawable, HIGHLIGHT_STYLE,
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use depot_rs::errors::Error;
+use depot_rs::operation::OperationKind;
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{Block, List, ListItem, Paragraph};
+
+#[derive(Debug)]
+pub struct Plan;
+
+impl Drawable for Plan {
+    fn render(&self, state: &mut DepotState, frame: &mut Frame) -> Result<(), Error> {
+        let entries: Vec<ListItem> = state
+            .plan
+            .0
+            .iter()
+            .map(|entry| {
+                let mut lines = vec![Line::from(Span::styled(
+                    format!(
+                        "{}  {} -> {} ({})",
+                        entry.name, entry.from, entry.to, entry.class
+                    ),
+                    Style::default()
+                        .fg(DEFAULT_PRIMARY_COLOR)
+                        .add_modifier(Modifier::BOLD),
+                ))];
+                for (label, value) in entry.fields() {
+                    lines.push(Line::from(vec![
+                        Span::styled(format!("    {label}: "), DEFAULT_STYLE),
+                        Span::raw(value),
+                    ]));
+                }
+                lines.push(Line::raw(""));
+
+                ListItem::new(Text::from(lines))
+            })
+            .collect();
+
+        let plan = List::new(entries)
+            .block(
+                Block::bordered()
+                    .border_type(ratatui::widgets::BorderType::Rounded)
+                    .title("Update plan")
+                    .style(DEFAULT_STYLE),
+            )
+            .highlight_symbol("* ")
+            .highlight_style(HIGHLIGHT_STYLE)
+            .highlight_spacing(ratatui::widgets::HighlightSpacing::Always);
+
+        let layout = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Fill(1), Constraint::Length(1)])
+            .split(frame.area().inner(Margin::new(10, 3)));
+
+        let (main_area, footer) = (layout[0], layout[1]);
+
+        frame.render_stateful_widget(plan, main_area, &mut state.plan_list_state);
+        self.render_helpline(frame, footer)?;
+
+        Ok(())
+    }
+
+    fn render_helpline(&self, frame: &mut Frame, area: Rect) -> Result<(), Error> {
+        let line = Line::from(vec![
+            Span::raw("Press "),
+            Span::raw("k/j").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to move up/down"),
+            Span::raw(", "),
+            Span::raw("ENTER").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to run the plan"),
+            Span::raw(", "),
+            Span::raw("q").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to go back"),
+        ]);
+
+        let footer_bar = Paragraph::new(line);
+        frame.render_widget(footer_bar, area);
+
+        Ok(())
+    }
+}
+
+impl Selectable for Plan {
+    async fn select(app: &mut App, key: &KeyEvent) -> Result<(), Error> {
+        match (key.modifiers, key.code) {
+            (_, KeyCode::Esc | KeyCode::Char('q')) => app.view = View::Update(Update),
+            (_, KeyCode::Char('j')) | (_, KeyCode::Down) => app.state.plan_list_state.select_next(),
+            (_, KeyCode::Char('k')) | (_, KeyCode::Up) => {
+                app.state.plan_list_state.select_previous()
+            }
+            (_, KeyCode::Enter) => {
+                let names: Vec<String> = app.state.plan.0.iter().map(|e| e.name.clone()).collect();
+                let krates: Vec<_> = app
+                    .state
+                    .depot
+                    .store
+                    .0
+                    .iter()
+                    .filter(|k| names.contains(&k.name))
+                    .cloned()
+                    .collect();
+                for krate in krates {
+                    app.spawn_operation(OperationKind::Update, krate.name, None);
+                }
+                app.state.plan_selection.clear();
+                app.view = View::Update(Update);
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+}
@@ -1,10 +1,19 @@
 use super::catalog_view::Catalog;
+use super::cleanup_view::Cleanup;
+use super::doctor_view::{Doctor, spawn_doctor};
+use super::history_view::open_history;
+use super::install_view::Install;
 use super::update_view::Update;
 use super::{Drawable, View, banner, center};
 use crate::ui::DEFAULT_STYLE;
-use crate::ui::components::{load_progress, select_menu};
-use crate::{app::App, depot::DepotState, errors::Error, keys::Selectable};
+use crate::ui::components::{compliance_panel, load_progress, select_menu};
+use crate::{app::App, keys::Selectable, state::DepotState};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use depot_rs::cleanup;
+use depot_rs::depot::format_size;
+use depot_rs::errors::Error;
+use depot_rs::operation::OperationKind;
+use depot_rs::policy::Violation;
 use ratatui::Frame;
 use ratatui::layout::Layout;
 use ratatui::layout::{Constraint, Rect};
@@ -22,7 +31,7 @@ impl Drawable for Start {
                 Constraint::Percentage(50),
                 Constraint::Percentage(10),
                 Constraint::Percentage(10),
-                Constraint::Percentage(5),
+                Constraint::Length(6),
                 Constraint::Percentage(25),
             ])
             .split(frame.area());
@@ -48,10 +57,29 @@ impl Drawable for Start {
             } else {
                 "All crates are up-to-date!".to_string()
             };
+            let unparseable_count = state.depot.unparseable_krate_count();
+            let unparseable_str = if unparseable_count != 0 {
+                format!("\n{unparseable_count} crates could not be read from `cargo info`.")
+            } else {
+                "".to_string()
+            };
+            let unfetched_count = state.depot.unfetched_krate_count();
+            let unfetched_str = if unfetched_count != 0 {
+                format!("\n{unfetched_count} crates could not be looked up with `cargo info`.")
+            } else {
+                "".to_string()
+            };
+            let conflict_count = state.depot.conflicts.0.len();
+            let conflict_str = if conflict_count != 0 {
+                format!("\n{conflict_count} binaries are shadowed in $PATH or installed twice.")
+            } else {
+                "".to_string()
+            };
             frame.render_widget(
                 Paragraph::new(format!(
-                    "You have {} crates installed.\n\n{outdated_crate_str}",
-                    state.depot.crate_count()
+                    "You have {} crates installed, taking up {}.\n\n{outdated_crate_str}{unparseable_str}{unfetched_str}{conflict_str}",
+                    state.depot.crate_count(),
+                    format_size(state.depot.disk_usage())
                 ))
                 .style(DEFAULT_STYLE)
                 .centered(),
@@ -59,6 +87,19 @@ impl Drawable for Start {
             );
 
             frame.render_widget(select_menu::new()?, layout[3]);
+
+            if let Some(policy) = &state.policy {
+                let violations = policy.check(&state.depot.store);
+                let area = center(
+                    layout[4],
+                    Constraint::Percentage(60),
+                    Constraint::Length(violations.len().max(1) as u16 + 3),
+                );
+                frame.render_widget(
+                    compliance_panel::new(&violations, &state.operations.active_krates())?,
+                    area,
+                );
+            }
         }
 
         Ok(())
@@ -75,15 +116,55 @@ impl Selectable for Start {
         match (key.modifiers, key.code) {
             (_, KeyCode::Esc | KeyCode::Char('q'))
             | (KeyModifiers::CONTROL, KeyCode::Char('c') | KeyCode::Char('C')) => app.quit(),
-            (_, KeyCode::Char('c')) => {
-                if app.state.is_all_synced() {
-                    app.view = View::Catalog(Catalog)
-                }
+            (_, KeyCode::Char('c')) if app.state.is_all_synced() => {
+                app.view = View::Catalog(Catalog)
             }
             (_, KeyCode::Char('u')) => app.view = View::Update(Update),
+            (_, KeyCode::Char('i')) => app.view = View::Install(Install),
+            // Every binary would look orphaned before the crates are listed.
+            (_, KeyCode::Char('n')) if !app.state.listing => {
+                app.state.cleanup = cleanup::Cleanup::get(&app.state.depot.store)?;
+                app.state.cleanup_list_state.select(None);
+                app.view = View::Cleanup(Cleanup);
+            }
+            (_, KeyCode::Char('d')) => {
+                spawn_doctor(app);
+                app.view = View::Doctor(Doctor);
+            }
+            (_, KeyCode::Char('h')) => open_history(app),
+            (_, KeyCode::Char('p')) if app.state.is_all_synced() => fix_violations(app),
             _ => {}
         }
 
         Ok(())
     }
 }
+
+/// Installs the missing crates, updates the outdated ones and uninstalls the banned ones.
+fn fix_violations(app: &mut App) {
+    let Some(policy) = &app.state.policy else {
+        return;
+    };
+    let fixing = app.state.operations.active_krates();
+    let violations: Vec<Violation> = policy
+        .check(&app.state.depot.store)
+        .into_iter()
+        .filter(|v| !fixing.contains(v.krate()))
+        .collect();
+
+    for violation in violations {
+        match violation {
+            Violation::Missing { krate } => {
+                app.spawn_operation(OperationKind::Install, krate, None)
+            }
+            Violation::Outdated { krate, .. } => {
+                app.spawn_operation(OperationKind::Update, krate, None)
+            }
+            Violation::Banned { krate } => {
+                app.spawn_operation(OperationKind::Uninstall, krate, None)
+            }
+            // Left for the user to fix until depot knows how to.
+            _ => {}
+        }
+    }
+}
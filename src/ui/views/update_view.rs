@@ -1,13 +1,15 @@
+use super::plan_view::Plan;
 use super::{View, start_view::Start};
 use crate::app::App;
-use crate::depot::DepotMessage;
-use crate::errors::{ChannelError, Error};
-use crate::events::{AppEvent, Event};
 use crate::keys::Selectable;
 use crate::ui::{
     DEFAULT_PRIMARY_COLOR, DEFAULT_SECONDARY_COLOR, DEFAULT_STYLE, Drawable, HIGHLIGHT_STYLE,
 };
 use crossterm::event::{KeyCode, KeyEvent};
+use depot_rs::depot::Krate;
+use depot_rs::errors::Error;
+use depot_rs::operation::{OperationKind, OperationState};
+use depot_rs::plan::UpdatePlan;
 use ratatui::Frame;
 use ratatui::layout::{Constraint, Direction, Layout, Margin, Rect};
 use ratatui::style::{Color, Modifier, Style, Stylize};
@@ -21,11 +23,10 @@ pub struct Update;
 impl Drawable for Update {
     fn render(
         &self,
-        state: &mut crate::depot::DepotState,
+        state: &mut crate::state::DepotState,
         frame: &mut ratatui::Frame,
-    ) -> Result<(), crate::errors::Error> {
+    ) -> Result<(), depot_rs::errors::Error> {
         let outdated_krates = state.depot.get_outdated_krates()?.0.clone();
-        let updating_krates = state.get_update_items();
         let mut krates: Vec<ListItem> = Vec::new();
 
         let throbber_style = Style::new().fg(Color::White).add_modifier(Modifier::ITALIC);
@@ -34,9 +35,15 @@ impl Drawable for Update {
             .to_symbol_span(&state.throbber_state);
 
         for krate in outdated_krates {
-            let item = if updating_krates.contains(&krate.name) {
+            let marker = if state.plan_selection.contains(&krate.name) {
+                "● "
+            } else {
+                "  "
+            };
+            let operation = state.operations.last(&krate.name);
+            let item = if let Some(operation) = operation.filter(|o| o.is_active()) {
                 let line = Span::raw(format!(
-                    "{}  {} -> {}",
+                    "{marker}{}  {} -> {}",
                     krate.name.clone(),
                     krate.version.clone(),
                     krate.latest_version()
@@ -44,13 +51,29 @@ impl Drawable for Update {
                 .style(DEFAULT_STYLE);
 
                 let tab_spacer = Span::raw("  ");
-                let label = Span::styled("updating", throbber_style);
+                // Pending operations are waiting for another cargo process to finish.
+                let label = match operation.state {
+                    OperationState::Pending => "waiting",
+                    _ => operation.kind.progressive(),
+                };
+                let label = Span::styled(label, throbber_style);
                 let line = Line::from(vec![line, tab_spacer, throbber.clone(), label]);
 
                 ListItem::from(line)
+            } else if let Some(OperationState::Failed(e)) = operation.map(|o| &o.state) {
+                let line = Span::raw(format!(
+                    "{marker}{}  {} -> {}",
+                    krate.name.clone(),
+                    krate.version.clone(),
+                    krate.latest_version()
+                ))
+                .fg(DEFAULT_PRIMARY_COLOR);
+                let error = Span::styled(format!("  failed: {e}"), Style::new().fg(Color::Red));
+
+                ListItem::from(Line::from(vec![line, error]))
             } else {
                 ListItem::from(format!(
-                    "{}  {} -> {}",
+                    "{marker}{}  {} -> {}",
                     krate.name.clone(),
                     krate.version.clone(),
                     krate.latest_version()
@@ -95,6 +118,18 @@ impl Drawable for Update {
             Span::raw(" "),
             Span::raw("to update crate"),
             Span::raw(", "),
+            Span::raw("c").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to cancel"),
+            Span::raw(", "),
+            Span::raw("SPACE").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to mark crate"),
+            Span::raw(", "),
+            Span::raw("p").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
+            Span::raw(" "),
+            Span::raw("to preview plan"),
+            Span::raw(", "),
             Span::raw("q").style(Style::new().fg(DEFAULT_SECONDARY_COLOR)),
             Span::raw(" "),
             Span::raw("to go back"),
@@ -129,28 +164,54 @@ impl Selectable for Update {
             }
             (_, KeyCode::Enter) => {
                 if let Some(ix) = app.state.update_list_state.selected() {
-                    let k = &app.state.depot.get_outdated_krates()?.0[ix];
-                    app.state.append_to_update_queue(&k.name);
-
-                    let kk = k.clone();
-                    let tx = app.events.get_sender();
-                    // Decouples the update logic to so that this doesn't block the UI
-                    tokio::spawn(async move {
-                        let res = kk.update().await;
-                        match res {
-                            Ok(_) => tx.send(Event::App(AppEvent::DepotEvent(
-                                DepotMessage::UpdateKrate { krate: kk.name },
-                            ))),
-                            Err(_) => tx.send(Event::App(AppEvent::DepotEvent(
-                                DepotMessage::DepotError(ChannelError::UpdateKrate),
-                            ))),
-                        }
-                    });
+                    let k = app.state.depot.get_outdated_krates()?.0[ix].clone();
+                    app.spawn_operation(OperationKind::Update, k.name, None);
+                }
+            }
+            (_, KeyCode::Char(' ')) => {
+                if let Some(ix) = app.state.update_list_state.selected() {
+                    let name = &app.state.depot.get_outdated_krates()?.0[ix].name;
+                    if !app.state.plan_selection.remove(name) {
+                        app.state.plan_selection.insert(name.clone());
+                    }
+                }
+            }
+            (_, KeyCode::Char('c')) => {
+                if let Some(ix) = app.state.update_list_state.selected() {
+                    let name = app.state.depot.get_outdated_krates()?.0[ix].name.clone();
+                    app.cancel_operation(&name)?;
                 }
             }
+            (_, KeyCode::Char('p')) => open_plan(app).await?,
             _ => {}
         }
 
         Ok(())
     }
 }
+
+/// Previews the marked crates, or the highlighted one when nothing is marked.
+async fn open_plan(app: &mut App) -> Result<(), Error> {
+    let outdated = app.state.depot.get_outdated_krates()?.0;
+    let krates: Vec<Krate> = if app.state.plan_selection.is_empty() {
+        app.state
+            .update_list_state
+            .selected()
+            .and_then(|ix| outdated.get(ix).cloned())
+            .into_iter()
+            .collect()
+    } else {
+        outdated
+            .into_iter()
+            .filter(|k| app.state.plan_selection.contains(&k.name))
+            .collect()
+    };
+
+    if !krates.is_empty() {
+        app.state.plan = UpdatePlan::get(&krates).await?;
+        app.state.plan_list_state.select_first();
+        app.view = View::Plan(Plan);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,255 @@
+use crate::config::UsageConfig;
+use crate::depot::Krates;
+use crate::paths;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// When each binary was last run, as far as depot can tell.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Usage(HashMap<String, SystemTime>);
+
+impl Usage {
+    /// Estimates when the binaries of `krates` were last run.
+    ///
+    /// The access time of a binary is either the last time it was run or, on filesystems that
+    /// don't record access times, when it was installed, so it is a lower bound at worst.
+    /// Shell history is only read when enabled in the config.
+    pub fn get(krates: &Krates, config: &UsageConfig) -> Self {
+        let mut usage = Self::default();
+        if let Some(bin_dir) = paths::cargo_bin_dir() {
+            for binary in krates.0.iter().flat_map(|k| &k.binaries) {
+                if let Ok(accessed) = fs::metadata(bin_dir.join(binary)).and_then(|m| m.accessed())
+                {
+                    usage.record(binary, accessed);
+                }
+            }
+        }
+
+        if config.shell_history {
+            for (shell, path) in history_files() {
+                if let Ok(bytes) = fs::read(path) {
+                    let history = String::from_utf8_lossy(&bytes);
+                    for (time, command) in shell.parse(&history) {
+                        for binary in invoked_binaries(command) {
+                            usage.record(&binary, time);
+                        }
+                    }
+                }
+            }
+        }
+
+        usage
+    }
+
+    fn record(&mut self, binary: &str, time: SystemTime) {
+        let last = self.0.entry(binary.to_string()).or_insert(time);
+        if time > *last {
+            *last = time;
+        }
+    }
+
+    /// The last time any of `binaries` was used.
+    pub fn last_used(&self, binaries: &[String]) -> Option<SystemTime> {
+        binaries.iter().filter_map(|b| self.0.get(b)).max().copied()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// Gives the timestamped commands in a history file. Commands without a timestamp are
+    /// skipped, since they don't tell when a binary was used.
+    fn parse(self, history: &str) -> Vec<(SystemTime, &str)> {
+        match self {
+            // > #1718000000
+            // > rg foo
+            Shell::Bash => {
+                let mut commands = vec![];
+                let mut time = None;
+                for line in history.lines() {
+                    match line.strip_prefix('#').and_then(parse_timestamp) {
+                        Some(t) => time = Some(t),
+                        None => {
+                            if let Some(t) = time.take() {
+                                commands.push((t, line));
+                            }
+                        }
+                    }
+                }
+                commands
+            }
+            // > : 1718000000:0;rg foo
+            Shell::Zsh => history
+                .lines()
+                .filter_map(|line| {
+                    let (meta, command) = line.strip_prefix(": ")?.split_once(';')?;
+                    let (time, _) = meta.split_once(':')?;
+                    Some((parse_timestamp(time)?, command))
+                })
+                .collect(),
+            // > - cmd: rg foo
+            // >   when: 1718000000
+            Shell::Fish => {
+                let mut commands = vec![];
+                let mut command = None;
+                for line in history.lines() {
+                    if let Some(c) = line.strip_prefix("- cmd: ") {
+                        command = Some(c);
+                    } else if let Some(t) = line.trim().strip_prefix("when: ")
+                        && let (Some(c), Some(t)) = (command.take(), parse_timestamp(t))
+                    {
+                        commands.push((t, c));
+                    }
+                }
+                commands
+            }
+        }
+    }
+}
+
+fn parse_timestamp(s: &str) -> Option<SystemTime> {
+    let secs = s.trim().parse().ok()?;
+    Some(UNIX_EPOCH + Duration::from_secs(secs))
+}
+
+/// Gives the programs a command line runs, e.g. `rg` and `bat` for `rg -l foo | xargs bat`.
+/// Cargo subcommands are resolved to their binaries, so `cargo nextest run` gives
+/// `cargo-nextest`.
+fn invoked_binaries(command: &str) -> Vec<String> {
+    command
+        .split(['|', ';', '&'])
+        .filter_map(|segment| {
+            let mut words = segment
+                .split_whitespace()
+                .skip_while(|w| w.contains('=') || *w == "sudo" || *w == "xargs");
+            let program = words.next()?;
+            let program = program.rsplit('/').next().unwrap_or(program);
+            match (program, words.next()) {
+                ("cargo", Some(sub)) if !sub.starts_with(['-', '+']) => {
+                    Some(format!("cargo-{sub}"))
+                }
+                _ => Some(program.to_string()),
+            }
+        })
+        .collect()
+}
+
+fn history_files() -> Vec<(Shell, PathBuf)> {
+    let mut files = vec![];
+    let home = dirs::home_dir();
+    if let Some(home) = &home {
+        files.push((Shell::Bash, home.join(".bash_history")));
+        let zdotdir = env::var_os("ZDOTDIR").map_or(home.clone(), PathBuf::from);
+        files.push((Shell::Zsh, zdotdir.join(".zsh_history")));
+    }
+    // Fish uses the XDG location on every platform.
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home.map(|h| h.join(".local").join("share")));
+    if let Some(data_home) = data_home {
+        files.push((Shell::Fish, data_home.join("fish").join("fish_history")));
+    }
+
+    files
+}
+
+/// Formats how long ago `time` was, e.g. `3 days ago`.
+pub fn format_age(time: SystemTime, now: SystemTime) -> String {
+    let days = now.duration_since(time).unwrap_or_default().as_secs() / (24 * 60 * 60);
+    match days {
+        0 => "today".to_string(),
+        1 => "yesterday".to_string(),
+        n => format!("{n} days ago"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Shell, Usage, format_age, invoked_binaries};
+    use pretty_assertions::assert_eq;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn at(secs: u64) -> std::time::SystemTime {
+        UNIX_EPOCH + Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn parse_bash_history() {
+        let history = "ls\n#1718000000\nrg foo\n#1718000100\nbat README.md\ncd ..\n";
+
+        assert_eq!(
+            Shell::Bash.parse(history),
+            vec![
+                (at(1718000000), "rg foo"),
+                (at(1718000100), "bat README.md")
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_zsh_history() {
+        let history = ": 1718000000:0;rg foo\n: 1718000100:2;cargo nextest run\nplain\n";
+
+        assert_eq!(
+            Shell::Zsh.parse(history),
+            vec![
+                (at(1718000000), "rg foo"),
+                (at(1718000100), "cargo nextest run")
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_fish_history() {
+        let history = "- cmd: rg foo\n  when: 1718000000\n- cmd: bat x\n  when: 1718000100\n  paths:\n    - x\n";
+
+        assert_eq!(
+            Shell::Fish.parse(history),
+            vec![(at(1718000000), "rg foo"), (at(1718000100), "bat x")]
+        );
+    }
+
+    #[test]
+    fn find_invoked_binaries() {
+        assert_eq!(
+            invoked_binaries("RUST_LOG=debug rg -l foo | xargs bat && ~/.cargo/bin/fd x"),
+            vec!["rg", "bat", "fd"]
+        );
+        assert_eq!(
+            invoked_binaries("cargo +nightly build; cargo nextest run"),
+            vec!["cargo", "cargo-nextest"]
+        );
+    }
+
+    #[test]
+    fn last_used_binary() {
+        let mut usage = Usage::default();
+        usage.record("rg", at(100));
+        usage.record("rg", at(50));
+        usage.record("rga", at(200));
+
+        assert_eq!(usage.last_used(&["rg".to_string()]), Some(at(100)));
+        assert_eq!(
+            usage.last_used(&["rg".to_string(), "rga".to_string()]),
+            Some(at(200))
+        );
+        assert_eq!(usage.last_used(&["fd".to_string()]), None);
+    }
+
+    #[test]
+    fn format_ages() {
+        let now = at(10 * 24 * 60 * 60);
+
+        assert_eq!(format_age(now, now), "today");
+        assert_eq!(format_age(at(9 * 24 * 60 * 60 - 1), now), "yesterday");
+        assert_eq!(format_age(at(0), now), "10 days ago");
+    }
+}
@@ -0,0 +1,31 @@
+//! Kept in its own test binary, since it sets `DEPOT_DATA_DIR` and `DEPOT_CONFIG` for the whole
+//! process.
+
+use depot_rs::backend::fake::FakeBackend;
+use depot_rs::depot::install_krate;
+use depot_rs::installer::InstallMethod;
+use pretty_assertions::assert_eq;
+use std::{env, fs};
+
+#[tokio::test]
+async fn records_failure_keeps_the_install() {
+    // A file where the data directory should be, so no record can be written.
+    let dir = env::temp_dir().join(format!("depot-install-records-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    let data_dir = dir.join("data");
+    fs::write(&data_dir, "").unwrap();
+    // SAFETY: this is the only test in the binary, so nothing reads the environment meanwhile.
+    unsafe {
+        env::set_var("DEPOT_DATA_DIR", &data_dir);
+        env::set_var("DEPOT_CONFIG", dir.join("config.toml"));
+    }
+    let backend = FakeBackend::default().with_latest("ripgrep", "14.1.1");
+
+    let outcome = install_krate(&backend, "ripgrep").await;
+
+    assert_eq!(outcome.result.unwrap(), InstallMethod::Source);
+    assert_eq!(outcome.warnings.len(), 1);
+    assert!(outcome.warnings[0].starts_with("could not record the install"));
+    assert_eq!(backend.calls(), vec!["install ripgrep"]);
+}
@@ -0,0 +1,36 @@
+//! Kept in its own test binary, since it sets `CARGO_INSTALL_ROOT` for the whole process.
+
+use depot_rs::depot::Depot;
+use depot_rs::records::InstallRecords;
+use depot_rs::tracking::{self, InstallTracking};
+use pretty_assertions::assert_eq;
+use std::{env, fs};
+use versions::SemVer;
+
+#[test]
+fn tracking_under_install_root() {
+    let root = env::temp_dir().join(format!("depot-install-root-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(root.join("bin")).unwrap();
+    fs::write(
+        root.join(".crates2.json"),
+        r#"{"installs":{"bat 0.25.0 (registry+https://github.com/rust-lang/crates.io-index)":{"bins":["bat"],"profile":"release"}}}"#,
+    )
+    .unwrap();
+    // SAFETY: this is the only test in the binary, so nothing reads the environment meanwhile.
+    unsafe { env::set_var("CARGO_INSTALL_ROOT", &root) };
+
+    let tracking = InstallTracking::load().unwrap();
+    assert!(tracking.found());
+    assert_eq!(tracking.version("bat"), SemVer::new("0.25.0"));
+
+    // An install that just finished must end up in the depot, not be dropped from it.
+    let mut depot = Depot::default();
+    depot.reconcile("bat", false, &tracking, &InstallRecords::default());
+    assert_eq!(depot.crate_count(), 1);
+    assert_eq!(depot.store.0[0].binaries, vec!["bat"]);
+
+    // Binaries deleted by hand are forgotten in the same tracking file.
+    tracking::forget("bat", &["bat".to_string()]).unwrap();
+    assert!(InstallTracking::load().unwrap().get("bat").is_none());
+}